@@ -1,7 +1,9 @@
 use core::ffi::c_float;
 use core::ffi::c_int;
+use core::ffi::c_void;
 use core::ptr::NonNull;
 
+use sys::ffi::PDButtonCallbackFunction;
 use sys::ffi::PDButtons;
 use sys::ffi::PDPeripherals;
 use sys::ffi::playdate_sys;
@@ -88,6 +90,13 @@ impl Api for Cache {
 	fn set_crank_sounds_disabled(&self) -> unsafe extern "C" fn(flag: c_int) -> c_int {
 		self.0.setCrankSoundsDisabled.expect("setCrankSoundsDisabled")
 	}
+
+	#[inline(always)]
+	fn set_button_callback(
+		&self)
+		-> unsafe extern "C" fn(cb: PDButtonCallbackFunction, buttonud: *mut c_void, queuesize: c_int) {
+		self.0.setButtonCallback.expect("setButtonCallback")
+	}
 }
 
 
@@ -136,6 +145,13 @@ impl Api for system::api::Cache {
 		    .setCrankSoundsDisabled
 		    .expect("setCrankSoundsDisabled")
 	}
+
+	#[inline(always)]
+	fn set_button_callback(
+		&self)
+		-> unsafe extern "C" fn(cb: PDButtonCallbackFunction, buttonud: *mut c_void, queuesize: c_int) {
+		self.as_inner().setButtonCallback.expect("setButtonCallback")
+	}
 }
 
 pub trait Api {
@@ -178,4 +194,12 @@ pub trait Api {
 	fn set_crank_sounds_disabled(&self) -> unsafe extern "C" fn(flag: c_int) -> c_int {
 		*sys::api!(system.setCrankSoundsDisabled)
 	}
+
+	/// Returns [`sys::ffi::playdate_sys::setButtonCallback`]
+	#[doc(alias = "sys::ffi::playdate_sys::setButtonCallback")]
+	fn set_button_callback(
+		&self)
+		-> unsafe extern "C" fn(cb: PDButtonCallbackFunction, buttonud: *mut c_void, queuesize: c_int) {
+		*sys::api!(system.setButtonCallback)
+	}
 }
@@ -192,6 +192,27 @@ pub enum Button {
 	A,
 }
 
+impl Button {
+	/// All buttons in order of their bits in [`PDButtons`].
+	pub const ALL: [Button; 6] = [
+	                              Button::Left,
+	                              Button::Right,
+	                              Button::Up,
+	                              Button::Down,
+	                              Button::B,
+	                              Button::A,
+	];
+
+	/// Returns single button if `buttons` contains exactly one.
+	pub fn from_single(buttons: PDButtons) -> Option<Self> {
+		if buttons.0.count_ones() == 1 {
+			Self::ALL.into_iter().find(|single| single == &buttons)
+		} else {
+			None
+		}
+	}
+}
+
 impl Into<PDButtons> for Button {
 	fn into(self) -> PDButtons {
 		match self {
@@ -230,14 +251,6 @@ impl<T: Iterator<Item = <PDButtons as PDButtonsIntoIter>::Item>> IterSingleButto
 	type Iter = impl Iterator<Item = Button>;
 
 	fn singles(self) -> Self::Iter {
-		static ALL: [Button; 6] = [
-		                           Button::Left,
-		                           Button::Right,
-		                           Button::Up,
-		                           Button::Down,
-		                           Button::B,
-		                           Button::A,
-		];
-		self.flat_map(|buttons| ALL.into_iter().filter(move |single| single == buttons))
+		self.flat_map(|buttons| Button::ALL.into_iter().filter(move |single| single == buttons))
 	}
 }
@@ -0,0 +1,312 @@
+//! Per-frame crank input helper.
+//!
+//! [`CrankState`] is updated once per frame and tracks ratchet ticks, revolutions,
+//! direction reversals, smoothed velocity and dock/undock edges.
+//! [`Confirm`] turns continuous cranking into a single "crank to confirm" gesture.
+
+use core::ffi::c_float;
+use core::ffi::c_int;
+
+use crate::api;
+use crate::record::Readings;
+use crate::source::InputSource;
+use crate::source::Live;
+
+
+/// Direction of the crank rotation, as viewed from the right side of the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	Clockwise,
+	CounterClockwise,
+}
+
+impl Direction {
+	fn of(change: c_float) -> Option<Self> {
+		if change > 0.0 {
+			Some(Self::Clockwise)
+		} else if change < 0.0 {
+			Some(Self::CounterClockwise)
+		} else {
+			None
+		}
+	}
+}
+
+
+/// Crank input manager, should be updated once per frame.
+///
+/// Reads the crank of its [`InputSource`], the device by default.
+#[derive(Debug, Clone)]
+pub struct CrankState<S: InputSource = Live> {
+	source: S,
+	docked: bool,
+	docked_changed: bool,
+	angle: c_float,
+	change: c_float,
+	/// Total signed degrees since creation or reset.
+	total: c_float,
+	velocity: c_float,
+	smoothing: c_float,
+	direction: Option<Direction>,
+	/// Degrees moved against the current direction, not yet counted as reversal.
+	against: c_float,
+	reversal_threshold: c_float,
+	reversed: bool,
+	reversals: u32,
+	initialized: bool,
+}
+
+impl<Api: api::Api + Default + Copy> CrankState<Live<Api>> {
+	/// Creates manager reading the crank of the device.
+	pub fn new() -> Self { Self::new_with(Live::new(Readings::CRANK)) }
+}
+
+impl<Api: api::Api + Default + Copy> Default for CrankState<Live<Api>> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<S: InputSource> CrankState<S> {
+	pub fn new_with(source: S) -> Self {
+		Self { source,
+		       docked: true,
+		       docked_changed: false,
+		       angle: 0.0,
+		       change: 0.0,
+		       total: 0.0,
+		       velocity: 0.0,
+		       smoothing: 0.3,
+		       direction: None,
+		       against: 0.0,
+		       reversal_threshold: 10.0,
+		       reversed: false,
+		       reversals: 0,
+		       initialized: false }
+	}
+
+	/// Weight of the newest frame in the smoothed velocity, clamped to `0.01..=1`.
+	pub fn set_smoothing(&mut self, smoothing: c_float) { self.smoothing = smoothing.clamp(0.01, 1.0) }
+
+	/// Degrees the crank must move against the current direction to count as reversal.
+	///
+	/// Filters out jitter, default is `10`.
+	pub fn set_reversal_threshold(&mut self, degrees: c_float) { self.reversal_threshold = degrees.max(0.0) }
+
+
+	pub fn source(&self) -> &S { &self.source }
+	pub fn source_mut(&mut self) -> &mut S { &mut self.source }
+	pub fn into_source(self) -> S { self.source }
+
+
+	/// Advances the source to the next frame, then advances by `dt` seconds with its crank readings.
+	///
+	/// The device source calls [`Crank::change`](crate::peripherals::Crank::change),
+	/// so it should not be used elsewhere in the same frame.
+	pub fn update(&mut self, dt: c_float) {
+		self.source.advance();
+		let (docked, angle, change) = self.source.crank();
+		self.update_with(docked, angle, change, dt);
+	}
+
+	/// Advances by `dt` seconds with the given crank readings.
+	pub fn update_with(&mut self, docked: bool, angle: c_float, change: c_float, dt: c_float) {
+		self.docked_changed = self.initialized && docked != self.docked;
+		self.initialized = true;
+		self.docked = docked;
+		self.angle = angle;
+		self.change = if docked { 0.0 } else { change };
+		self.total += self.change;
+
+		if dt > 0.0 {
+			let velocity = self.change / dt;
+			self.velocity += (velocity - self.velocity) * self.smoothing;
+		}
+
+		self.reversed = false;
+		match (self.direction, Direction::of(self.change)) {
+			(None, Some(direction)) => self.direction = Some(direction),
+			(Some(current), Some(direction)) if current != direction => {
+				self.against += libm::fabsf(self.change);
+				if self.against >= self.reversal_threshold {
+					self.direction = Some(direction);
+					self.against = 0.0;
+					self.reversed = true;
+					self.reversals = self.reversals.saturating_add(1);
+				}
+			},
+			(Some(_), Some(_)) => self.against = 0.0,
+			_ => {},
+		}
+	}
+
+
+	pub fn docked(&self) -> bool { self.docked }
+	/// Crank was docked during the last update.
+	pub fn just_docked(&self) -> bool { self.docked_changed && self.docked }
+	/// Crank was undocked during the last update.
+	pub fn just_undocked(&self) -> bool { self.docked_changed && !self.docked }
+
+	/// Current angle in degrees, `0..360`.
+	pub fn angle(&self) -> c_float { self.angle }
+	/// Change in degrees during the last update, `0` while docked.
+	pub fn change(&self) -> c_float { self.change }
+
+	/// Returns the number of ratchet ticks crossed during the last update,
+	/// with `ticks_per_revolution` ticks evenly spaced around the crank starting at `0`.
+	///
+	/// Negative values are counter-clockwise. Same as `playdate.getCrankTicks` in Lua API,
+	/// but can be called any number of times per frame.
+	pub fn ticks(&self, ticks_per_revolution: c_int) -> c_int {
+		if ticks_per_revolution <= 0 || self.change == 0.0 {
+			return 0;
+		}
+		let step = 360.0 / ticks_per_revolution as c_float;
+		let segment = |angle: c_float| libm::ceilf(angle / step) as c_int;
+		segment(self.angle) - segment(self.angle - self.change)
+	}
+
+	/// Total signed degrees turned since creation or [reset](Self::reset_total).
+	pub fn total(&self) -> c_float { self.total }
+	/// Total signed revolutions since creation or [reset](Self::reset_total).
+	pub fn revolutions(&self) -> c_float { self.total / 360.0 }
+	pub fn reset_total(&mut self) { self.total = 0.0 }
+
+	/// Smoothed velocity in degrees per second, negative is counter-clockwise.
+	pub fn velocity(&self) -> c_float { self.velocity }
+	/// Smoothed velocity in revolutions per minute.
+	pub fn rpm(&self) -> c_float { self.velocity / 6.0 }
+
+	/// Direction of the latest rotation.
+	pub fn direction(&self) -> Option<Direction> { self.direction }
+	/// Direction reversed during the last update.
+	pub fn reversed(&self) -> bool { self.reversed }
+	/// Number of direction reversals since creation.
+	pub fn reversals(&self) -> u32 { self.reversals }
+}
+
+
+/// "Crank to confirm" gesture.
+///
+/// Progress grows while the crank turns in the required direction and decays while it stands still.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Confirm {
+	/// Degrees required to confirm.
+	pub degrees: c_float,
+	/// Required direction, any if `None`.
+	pub direction: Option<Direction>,
+	/// Degrees per second lost while not cranking.
+	pub decay: c_float,
+	progress: c_float,
+	confirmed: bool,
+}
+
+impl Confirm {
+	pub const fn new(degrees: c_float) -> Self {
+		Self { degrees,
+		       direction: None,
+		       decay: 180.0,
+		       progress: 0.0,
+		       confirmed: false }
+	}
+
+	pub const fn with_direction(mut self, direction: Direction) -> Self {
+		self.direction = Some(direction);
+		self
+	}
+
+	pub const fn with_decay(mut self, decay: c_float) -> Self {
+		self.decay = decay;
+		self
+	}
+
+	/// Advances the gesture with the last update of the `crank`.
+	///
+	/// Returns `true` once when the gesture is confirmed, then starts over.
+	pub fn update<S: InputSource>(&mut self, crank: &CrankState<S>, dt: c_float) -> bool {
+		self.update_with(crank.change(), dt)
+	}
+
+	/// Advances the gesture with the crank `change` in degrees.
+	pub fn update_with(&mut self, change: c_float, dt: c_float) -> bool {
+		if self.confirmed {
+			self.reset();
+		}
+
+		let along = match self.direction {
+			None => libm::fabsf(change),
+			Some(Direction::Clockwise) => change,
+			Some(Direction::CounterClockwise) => -change,
+		};
+
+		if along != 0.0 {
+			self.progress += along;
+		} else {
+			self.progress -= self.decay * dt;
+		}
+		self.progress = self.progress.max(0.0);
+
+		if self.progress >= self.degrees {
+			self.confirmed = true;
+		}
+		self.confirmed
+	}
+
+	/// Completed part of the gesture, `0..=1`.
+	pub fn progress(&self) -> c_float {
+		if self.degrees > 0.0 {
+			(self.progress / self.degrees).min(1.0)
+		} else {
+			1.0
+		}
+	}
+
+	pub fn is_confirmed(&self) -> bool { self.confirmed }
+
+	pub fn reset(&mut self) {
+		self.progress = 0.0;
+		self.confirmed = false;
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::source::Script;
+
+	#[test]
+	fn ticks_and_reversals() {
+		let mut crank = CrankState::new_with(Script::new());
+		crank.update_with(true, 0.0, 0.0, 0.1);
+		assert!(!crank.just_docked());
+		crank.update_with(false, 10.0, 10.0, 0.1);
+		assert!(crank.just_undocked());
+		assert_eq!(1, crank.ticks(6));
+
+		crank.update_with(false, 130.0, 120.0, 0.1);
+		assert_eq!(2, crank.ticks(6));
+		assert_eq!(Some(Direction::Clockwise), crank.direction());
+
+		crank.update_with(false, 125.0, -5.0, 0.1);
+		assert!(!crank.reversed());
+		crank.update_with(false, 110.0, -15.0, 0.1);
+		assert!(crank.reversed());
+		assert_eq!(-1, crank.ticks(6));
+		assert_eq!(110.0, crank.total());
+
+		crank.update_with(true, 110.0, 50.0, 0.1);
+		assert!(crank.just_docked());
+		assert_eq!(0, crank.ticks(6));
+	}
+
+	#[test]
+	fn confirm() {
+		let mut confirm = Confirm::new(360.0).with_direction(Direction::Clockwise);
+		assert!(!confirm.update_with(200.0, 0.1));
+		assert!(!confirm.update_with(0.0, 0.5));
+		assert_eq!(110.0 / 360.0, confirm.progress());
+		assert!(!confirm.update_with(-20.0, 0.1));
+		assert!(confirm.update_with(300.0, 0.1));
+		assert!(!confirm.update_with(0.0, 0.1));
+		assert_eq!(0.0, confirm.progress());
+	}
+}
@@ -0,0 +1,35 @@
+use core::fmt;
+
+
+pub type ApiError = sys::error::Error<self::Error>;
+
+
+#[derive(Debug)]
+pub enum Error {
+	/// Causes when reading or writing a file fails.
+	Fs(fs::error::Error),
+
+	/// Input recording has invalid or unsupported format.
+	InvalidRecording,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self {
+			Error::Fs(err) => err.fmt(f),
+			Error::InvalidRecording => write!(f, "Invalid input recording"),
+		}
+	}
+}
+
+impl From<fs::error::Error> for Error {
+	fn from(err: fs::error::Error) -> Self { Error::Fs(err) }
+}
+
+
+impl Into<ApiError> for Error {
+	fn into(self) -> ApiError { ApiError::Api(self) }
+}
+
+
+impl core::error::Error for Error {}
@@ -0,0 +1,423 @@
+//! Per-frame button input manager.
+//!
+//! [`InputState`] is updated once per frame and answers higher-level questions
+//! about each [`Button`]: edges, hold duration, key-repeat, double-tap and buffered presses.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_void;
+use sys::ffi::PDButtons;
+
+use crate::api;
+use crate::buttons::Button;
+use crate::peripherals::State;
+use crate::record::Readings;
+use crate::source::InputSource;
+use crate::source::Live;
+
+
+/// Key-repeat timing, e.g. for menu navigation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Repeat {
+	/// Seconds from the press to the first repeat.
+	pub delay: c_float,
+	/// Seconds between subsequent repeats.
+	pub interval: c_float,
+}
+
+impl Repeat {
+	pub const fn new(delay: c_float, interval: c_float) -> Self { Self { delay, interval } }
+}
+
+impl Default for Repeat {
+	fn default() -> Self { Self::new(0.3, 0.1) }
+}
+
+
+/// Single button up/down event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonEvent {
+	pub button: Button,
+	pub down: bool,
+	/// Time of the event in milliseconds, as reported by the system.
+	pub when: u32,
+}
+
+
+/// Queue of button events filled by [`sys::ffi::playdate_sys::setButtonCallback`].
+///
+/// Catches every press and release, even several of the same button during one frame.
+///
+/// On drop, automatically resets system registered button callback.
+pub struct ButtonEvents<Api: api::Api = api::Default> {
+	/// Boxed to keep the address passed to the system stable.
+	#[allow(clippy::box_collection)]
+	events: Box<Vec<ButtonEvent>>,
+	api: Api,
+}
+
+impl<Api: api::Api + Default> ButtonEvents<Api> {
+	/// Registers button callback with queue of `queue_size` events per frame.
+	///
+	/// At 30 FPS queue size of 5 should be adequate, lower frame rates need larger queue.
+	pub fn new(queue_size: c_int) -> Self { Self::new_with(Api::default(), queue_size) }
+}
+
+impl<Api: api::Api> ButtonEvents<Api> {
+	/// Registers button callback with queue of `queue_size` events per frame.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sys::setButtonCallback`].
+	#[doc(alias = "sys::ffi::playdate_sys::setButtonCallback")]
+	pub fn new_with(api: Api, queue_size: c_int) -> Self {
+		let mut events = Box::new(Vec::with_capacity(queue_size.max(0) as usize));
+		let ptr = events.as_mut() as *mut Vec<ButtonEvent> as *mut c_void;
+		let f = api.set_button_callback();
+		unsafe { f(Some(Self::proxy), ptr, queue_size) };
+		Self { events, api }
+	}
+
+	unsafe extern "C" fn proxy(button: PDButtons, down: c_int, when: u32, userdata: *mut c_void) -> c_int {
+		let Some(events) = (userdata as *mut Vec<ButtonEvent>).as_mut() else {
+			return 1;
+		};
+		if let Some(button) = Button::from_single(button) {
+			events.push(ButtonEvent { button,
+			                          down: down != 0,
+			                          when });
+		}
+		0
+	}
+
+	/// Events received since the last call, oldest first.
+	pub fn drain(&mut self) -> impl Iterator<Item = ButtonEvent> + '_ { self.events.drain(..) }
+
+	pub fn is_empty(&self) -> bool { self.events.is_empty() }
+}
+
+impl<Api: api::Api> Drop for ButtonEvents<Api> {
+	fn drop(&mut self) {
+		let f = self.api.set_button_callback();
+		unsafe { f(None, core::ptr::null_mut(), 0) };
+	}
+}
+
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Track {
+	down: bool,
+	pressed: bool,
+	released: bool,
+	repeated: bool,
+	double: bool,
+	/// Seconds the button is held.
+	held: c_float,
+	/// Hold time of the next repeat.
+	next_repeat: c_float,
+	/// Time of the last press not yet part of a double-tap.
+	last_press: Option<c_float>,
+}
+
+impl Track {
+	fn clear_edges(&mut self) {
+		self.pressed = false;
+		self.released = false;
+		self.repeated = false;
+		self.double = false;
+	}
+}
+
+
+/// Number of recent presses kept for [`InputState::buffered`] and [`InputState::combo`].
+pub const BUFFER_SIZE: usize = 16;
+
+
+/// Button input manager, should be updated once per frame.
+///
+/// Reads buttons of its [`InputSource`], the device by default.
+#[derive(Debug, Clone)]
+pub struct InputState<S: InputSource = Live> {
+	source: S,
+	tracks: [Track; 6],
+	repeat: Repeat,
+	double_tap: c_float,
+	/// Seconds since creation.
+	time: c_float,
+	/// Ring of recent presses, `None` if consumed.
+	presses: [Option<(Button, c_float)>; BUFFER_SIZE],
+	head: usize,
+}
+
+impl<Api: api::Api + Default + Copy> InputState<Live<Api>> {
+	/// Creates manager reading buttons of the device.
+	pub fn new() -> Self { Self::new_with(Live::new(Readings::BUTTONS)) }
+}
+
+impl<Api: api::Api + Default + Copy> Default for InputState<Live<Api>> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<S: InputSource> InputState<S> {
+	pub fn new_with(source: S) -> Self {
+		Self { source,
+		       tracks: Default::default(),
+		       repeat: Repeat::default(),
+		       double_tap: 0.25,
+		       time: 0.0,
+		       presses: [None; BUFFER_SIZE],
+		       head: 0 }
+	}
+
+	pub fn repeat(&self) -> Repeat { self.repeat }
+	pub fn set_repeat(&mut self, repeat: Repeat) { self.repeat = repeat }
+
+	/// Maximum seconds between two presses counted as double-tap.
+	pub fn double_tap_window(&self) -> c_float { self.double_tap }
+	pub fn set_double_tap_window(&mut self, window: c_float) { self.double_tap = window }
+
+	/// Seconds passed through updates.
+	pub fn time(&self) -> c_float { self.time }
+
+	pub fn source(&self) -> &S { &self.source }
+	pub fn source_mut(&mut self) -> &mut S { &mut self.source }
+	pub fn into_source(self) -> S { self.source }
+
+
+	/// Advances the source to the next frame, then advances by `dt` seconds with its buttons state.
+	///
+	/// For the device source it's equivalent to [`sys::ffi::playdate_sys::getButtonState`].
+	#[doc(alias = "sys::ffi::playdate_sys::getButtonState")]
+	pub fn update(&mut self, dt: c_float) {
+		self.source.advance();
+		let state = self.source.buttons();
+		self.update_with(&state, dt);
+	}
+
+	/// Advances by `dt` seconds with the given buttons `state`.
+	pub fn update_with(&mut self, state: &State, dt: c_float) {
+		self.begin(dt);
+		for (i, button) in Button::ALL.into_iter().enumerate() {
+			if button == state.pushed {
+				self.press(i);
+			}
+			if button == state.released {
+				self.release(i);
+			}
+			self.tracks[i].down = button == state.current;
+		}
+		self.end(dt);
+	}
+
+	/// Advances by `dt` seconds with the `events` received from [`ButtonEvents`].
+	///
+	/// Unlike polling, several presses of the same button during one frame are all registered,
+	/// so a very fast double-tap is not missed.
+	pub fn update_with_events(&mut self, events: impl IntoIterator<Item = ButtonEvent>, dt: c_float) {
+		self.begin(dt);
+		for event in events {
+			let i = event.button as usize;
+			if event.down {
+				self.press(i);
+			} else {
+				self.release(i);
+			}
+		}
+		self.end(dt);
+	}
+
+
+	fn begin(&mut self, dt: c_float) {
+		self.time += dt.max(0.0);
+		self.tracks.iter_mut().for_each(Track::clear_edges);
+	}
+
+	fn press(&mut self, i: usize) {
+		let time = self.time;
+		let track = &mut self.tracks[i];
+		track.pressed = true;
+		track.repeated = true;
+		track.down = true;
+		track.held = 0.0;
+		track.next_repeat = self.repeat.delay;
+		match track.last_press {
+			Some(last) if time - last <= self.double_tap => {
+				track.double = true;
+				track.last_press = None;
+			},
+			_ => track.last_press = Some(time),
+		}
+
+		self.presses[self.head] = Some((Button::ALL[i], time));
+		self.head = (self.head + 1) % BUFFER_SIZE;
+	}
+
+	fn release(&mut self, i: usize) {
+		let track = &mut self.tracks[i];
+		track.released = true;
+		track.down = false;
+	}
+
+	fn end(&mut self, dt: c_float) {
+		let repeat = self.repeat;
+		for track in self.tracks
+		                 .iter_mut()
+		                 .filter(|track| track.down && !track.pressed)
+		{
+			track.held += dt;
+			if repeat.interval > 0.0 && track.held >= track.next_repeat {
+				track.repeated = true;
+				while track.next_repeat <= track.held {
+					track.next_repeat += repeat.interval;
+				}
+			}
+		}
+	}
+
+
+	/// Button is currently down.
+	pub fn is_down(&self, button: Button) -> bool { self.tracks[button as usize].down }
+
+	/// Button was pressed during the last update.
+	pub fn just_pressed(&self, button: Button) -> bool { self.tracks[button as usize].pressed }
+
+	/// Button was released during the last update.
+	pub fn just_released(&self, button: Button) -> bool { self.tracks[button as usize].released }
+
+	/// Seconds the button is held down, `0` if it is up.
+	pub fn held_for(&self, button: Button) -> c_float {
+		let track = &self.tracks[button as usize];
+		if track.down { track.held } else { 0.0 }
+	}
+
+	/// Button is held down for at least `seconds`.
+	pub fn is_held(&self, button: Button, seconds: c_float) -> bool {
+		self.is_down(button) && self.held_for(button) >= seconds
+	}
+
+	/// Button was just pressed or auto-repeated according to the [`Repeat`] settings.
+	pub fn repeated(&self, button: Button) -> bool { self.tracks[button as usize].repeated }
+
+	/// Button was pressed second time within the [double-tap window](Self::double_tap_window).
+	pub fn double_tapped(&self, button: Button) -> bool { self.tracks[button as usize].double }
+
+
+	/// Button was pressed within the last `window` seconds and the press was not [taken](Self::take_buffered) yet.
+	pub fn buffered(&self, button: Button, window: c_float) -> bool {
+		self.find_buffered(button, window).is_some()
+	}
+
+	/// Consumes the latest press of the button made within the last `window` seconds.
+	///
+	/// Useful for e.g. jump buffering, when a press slightly before landing should still count.
+	pub fn take_buffered(&mut self, button: Button, window: c_float) -> bool {
+		match self.find_buffered(button, window) {
+			Some(i) => {
+				self.presses[i] = None;
+				true
+			},
+			None => false,
+		}
+	}
+
+	fn find_buffered(&self, button: Button, window: c_float) -> Option<usize> {
+		self.recent()
+		    .take_while(|(_, (_, time))| self.time - time <= window)
+		    .find(|(_, (pressed, _))| *pressed == button)
+		    .map(|(i, _)| i)
+	}
+
+	/// Indices and values of recent presses, newest first.
+	fn recent(&self) -> impl Iterator<Item = (usize, (Button, c_float))> + '_ {
+		(1..=BUFFER_SIZE).map(move |n| (self.head + BUFFER_SIZE - n) % BUFFER_SIZE)
+		                 .filter_map(move |i| self.presses[i].map(|press| (i, press)))
+	}
+
+	/// The latest presses form `sequence`, all made within `window` seconds.
+	///
+	/// Buttons pressed in between break the combo, the last button must be [just pressed](Self::just_pressed).
+	pub fn combo(&self, sequence: &[Button], window: c_float) -> bool {
+		let Some(last) = sequence.last() else {
+			return false;
+		};
+		if sequence.len() > BUFFER_SIZE || !self.just_pressed(*last) {
+			return false;
+		}
+
+		let mut recent = self.recent();
+		sequence.iter().rev().all(|expected| {
+			                     matches!(recent.next(), Some((_, (button, time))) if button == *expected && self.time - time <= window)
+		                     })
+	}
+
+	/// Forgets all buffered presses.
+	pub fn clear_buffer(&mut self) { self.presses = [None; BUFFER_SIZE] }
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::source::Script;
+
+	fn state(current: PDButtons, pushed: PDButtons, released: PDButtons) -> State {
+		State { current,
+		        pushed,
+		        released }
+	}
+
+	const NONE: PDButtons = PDButtons(0);
+	const A: PDButtons = PDButtons::kButtonA;
+	const B: PDButtons = PDButtons::kButtonB;
+
+	#[test]
+	fn edges_hold_and_repeat() {
+		let mut input = InputState::new_with(Script::new());
+		input.set_repeat(Repeat::new(0.3, 0.1));
+
+		input.update_with(&state(A, A, NONE), 0.05);
+		assert!(input.just_pressed(Button::A) && input.repeated(Button::A));
+		assert!(!input.just_pressed(Button::B));
+
+		input.update_with(&state(A, NONE, NONE), 0.2);
+		assert!(!input.just_pressed(Button::A) && !input.repeated(Button::A));
+		input.update_with(&state(A, NONE, NONE), 0.15);
+		assert!(input.repeated(Button::A));
+		assert!(input.is_held(Button::A, 0.3));
+
+		input.update_with(&state(NONE, NONE, A), 0.05);
+		assert!(input.just_released(Button::A) && !input.is_down(Button::A));
+		assert_eq!(0.0, input.held_for(Button::A));
+	}
+
+	#[test]
+	fn double_tap_buffer_and_combo() {
+		let mut input = InputState::new_with(Script::new());
+
+		input.update_with_events(
+		                         [
+			ButtonEvent { button: Button::B,
+			              down: true,
+			              when: 0, },
+			ButtonEvent { button: Button::B,
+			              down: false,
+			              when: 10, },
+			ButtonEvent { button: Button::B,
+			              down: true,
+			              when: 20, },
+		],
+		                         0.03,
+		);
+		assert!(input.double_tapped(Button::B) && input.is_down(Button::B));
+
+		input.update_with(&state(A, A, B), 0.1);
+		assert!(input.combo(&[Button::B, Button::A], 0.5));
+		assert!(!input.combo(&[Button::A, Button::B], 0.5));
+
+		assert!(input.take_buffered(Button::B, 0.5));
+		assert!(input.take_buffered(Button::B, 0.5));
+		assert!(!input.buffered(Button::B, 0.5));
+		input.update_with(&state(NONE, NONE, A), 1.0);
+		assert!(!input.buffered(Button::A, 0.5));
+	}
+}
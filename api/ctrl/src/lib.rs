@@ -9,4 +9,10 @@ extern crate sys;
 
 pub mod api;
 pub mod buttons;
+pub mod crank;
+pub mod error;
+pub mod input;
+pub mod motion;
 pub mod peripherals;
+pub mod record;
+pub mod source;
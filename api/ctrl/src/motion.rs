@@ -0,0 +1,432 @@
+//! Accelerometer processing.
+//!
+//! Filters for noisy readings, calibration of the neutral orientation,
+//! pitch/roll angles and shake/flip detectors.
+//! [`Motion`] combines all of them and should be updated once per frame.
+
+use core::ffi::c_float;
+use core::ops::Add;
+use core::ops::Mul;
+use core::ops::Sub;
+
+use fs::Path;
+
+use crate::api;
+use crate::error::ApiError;
+use crate::record::Readings;
+use crate::source::InputSource;
+use crate::source::Live;
+
+
+/// Single accelerometer reading in g.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Reading {
+	pub x: c_float,
+	pub y: c_float,
+	pub z: c_float,
+}
+
+impl Reading {
+	pub const fn new(x: c_float, y: c_float, z: c_float) -> Self { Self { x, y, z } }
+
+	pub fn magnitude(&self) -> c_float { libm::sqrtf(self.dot(self)) }
+
+	pub fn dot(&self, other: &Self) -> c_float { self.x * other.x + self.y * other.y + self.z * other.z }
+
+	/// Returns reading scaled to magnitude `1`, or zero reading as is.
+	pub fn normalized(&self) -> Self {
+		let magnitude = self.magnitude();
+		if magnitude > 0.0 {
+			*self * (1.0 / magnitude)
+		} else {
+			*self
+		}
+	}
+
+	/// Forward/backward tilt in degrees.
+	pub fn pitch(&self) -> c_float {
+		libm::atan2f(self.y, libm::sqrtf(self.x * self.x + self.z * self.z)).to_degrees()
+	}
+
+	/// Left/right tilt in degrees.
+	pub fn roll(&self) -> c_float {
+		libm::atan2f(self.x, libm::sqrtf(self.y * self.y + self.z * self.z)).to_degrees()
+	}
+}
+
+impl From<(c_float, c_float, c_float)> for Reading {
+	fn from((x, y, z): (c_float, c_float, c_float)) -> Self { Self { x, y, z } }
+}
+
+impl Add for Reading {
+	type Output = Self;
+	fn add(self, rhs: Self) -> Self { Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z) }
+}
+
+impl Sub for Reading {
+	type Output = Self;
+	fn sub(self, rhs: Self) -> Self { Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z) }
+}
+
+impl Mul<c_float> for Reading {
+	type Output = Self;
+	fn mul(self, rhs: c_float) -> Self { Self::new(self.x * rhs, self.y * rhs, self.z * rhs) }
+}
+
+
+/// Exponential low-pass filter, smooths out jitter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LowPass {
+	/// Weight of the newest reading, `0..=1`. Lower is smoother but slower.
+	pub alpha: c_float,
+	value: Option<Reading>,
+}
+
+impl LowPass {
+	pub const fn new(alpha: c_float) -> Self { Self { alpha, value: None } }
+
+	pub fn filter(&mut self, reading: Reading) -> Reading {
+		let value = match self.value {
+			Some(value) => value + (reading - value) * self.alpha,
+			None => reading,
+		};
+		self.value = Some(value);
+		value
+	}
+
+	pub fn value(&self) -> Option<Reading> { self.value }
+	pub fn reset(&mut self) { self.value = None }
+}
+
+
+/// High-pass filter, removes gravity and slow tilting, leaving quick movements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HighPass(LowPass);
+
+impl HighPass {
+	/// `alpha` is weight of the newest reading in the removed low-frequency part.
+	pub const fn new(alpha: c_float) -> Self { Self(LowPass::new(alpha)) }
+
+	pub fn filter(&mut self, reading: Reading) -> Reading { reading - self.0.filter(reading) }
+
+	pub fn reset(&mut self) { self.0.reset() }
+}
+
+
+/// Neutral orientation of the device, angles are measured relative to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+	pub neutral: Reading,
+}
+
+impl Calibration {
+	/// Size of [serialized](Self::to_bytes) calibration.
+	pub const SIZE: usize = 12;
+
+	pub const fn new(neutral: Reading) -> Self { Self { neutral } }
+
+	/// Pitch of the `reading` relative to the neutral orientation.
+	pub fn pitch(&self, reading: &Reading) -> c_float { reading.pitch() - self.neutral.pitch() }
+
+	/// Roll of the `reading` relative to the neutral orientation.
+	pub fn roll(&self, reading: &Reading) -> c_float { reading.roll() - self.neutral.roll() }
+
+	/// Cosine of the angle between the `reading` and the neutral orientation.
+	pub fn alignment(&self, reading: &Reading) -> c_float { reading.normalized().dot(&self.neutral.normalized()) }
+
+	pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+		let mut bytes = [0; Self::SIZE];
+		bytes[0..4].copy_from_slice(&self.neutral.x.to_le_bytes());
+		bytes[4..8].copy_from_slice(&self.neutral.y.to_le_bytes());
+		bytes[8..12].copy_from_slice(&self.neutral.z.to_le_bytes());
+		bytes
+	}
+
+	/// Returns `None` if `bytes` are not a valid calibration.
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		if bytes.len() != Self::SIZE {
+			return None;
+		}
+		let float = |i: usize| c_float::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+		let neutral = Reading::new(float(0), float(4), float(8));
+		if neutral.magnitude().is_finite() && neutral.magnitude() > 0.0 {
+			Some(Self { neutral })
+		} else {
+			None
+		}
+	}
+
+	/// Writes calibration to the file at `path` in the data directory.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ApiError> {
+		fs::write(path, self.to_bytes()).map_err(ApiError::from_err)
+	}
+
+	/// Reads calibration from the file at `path` in the data directory.
+	///
+	/// Returns `None` if the file does not contain a valid calibration.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Option<Self>, ApiError> {
+		fs::read(path, true).map(|bytes| Self::from_bytes(&bytes))
+		                    .map_err(ApiError::from_err)
+	}
+}
+
+impl Default for Calibration {
+	/// Device lying flat, screen up.
+	fn default() -> Self { Self::new(Reading::new(0.0, 0.0, 1.0)) }
+}
+
+
+/// Captures neutral orientation by averaging several readings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibrator {
+	sum: Reading,
+	samples: u32,
+	required: u32,
+}
+
+impl Calibrator {
+	/// Calibrator averaging `samples` readings, e.g. half a second of frames.
+	pub const fn new(samples: u32) -> Self {
+		Self { sum: Reading::new(0.0, 0.0, 0.0),
+		       samples: 0,
+		       required: if samples == 0 { 1 } else { samples } }
+	}
+
+	/// Adds a reading, returns calibration when enough samples were collected.
+	pub fn add(&mut self, reading: Reading) -> Option<Calibration> {
+		if self.samples < self.required {
+			self.sum = self.sum + reading;
+			self.samples += 1;
+		}
+		self.calibration()
+	}
+
+	pub fn calibration(&self) -> Option<Calibration> {
+		(self.samples >= self.required).then(|| Calibration::new(self.sum * (1.0 / self.samples as c_float)))
+	}
+
+	/// Collected part of required samples, `0..=1`.
+	pub fn progress(&self) -> c_float { self.samples as c_float / self.required as c_float }
+
+	pub fn reset(&mut self) { *self = Self::new(self.required) }
+}
+
+
+/// Detects quick jolts of the device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShakeDetector {
+	/// Minimal change between readings in g counted as shake.
+	pub threshold: c_float,
+	/// Seconds after a shake when no other one is reported.
+	pub cooldown: c_float,
+	previous: Option<Reading>,
+	wait: c_float,
+	intensity: c_float,
+}
+
+impl ShakeDetector {
+	pub const fn new(threshold: c_float, cooldown: c_float) -> Self {
+		Self { threshold,
+		       cooldown,
+		       previous: None,
+		       wait: 0.0,
+		       intensity: 0.0 }
+	}
+
+	/// Returns `true` if the `reading` starts a shake.
+	pub fn update(&mut self, reading: Reading, dt: c_float) -> bool {
+		self.intensity = self.previous
+		                     .map_or(0.0, |previous| (reading - previous).magnitude());
+		self.previous = Some(reading);
+		self.wait = (self.wait - dt).max(0.0);
+
+		if self.wait <= 0.0 && self.intensity >= self.threshold {
+			self.wait = self.cooldown;
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Change between the last two readings in g.
+	pub fn intensity(&self) -> c_float { self.intensity }
+}
+
+impl Default for ShakeDetector {
+	fn default() -> Self { Self::new(1.5, 0.5) }
+}
+
+
+/// Detects the device being turned upside down relative to the neutral orientation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlipDetector {
+	/// Alignment with the neutral orientation below `-threshold` is flipped,
+	/// above `threshold` is back to normal. Between is hysteresis.
+	pub threshold: c_float,
+	flipped: bool,
+}
+
+impl FlipDetector {
+	pub const fn new(threshold: c_float) -> Self {
+		Self { threshold,
+		       flipped: false }
+	}
+
+	/// Returns `Some` with the new state when it changes.
+	pub fn update(&mut self, reading: &Reading, calibration: &Calibration) -> Option<bool> {
+		let alignment = calibration.alignment(reading);
+		let flipped = if self.flipped {
+			alignment < self.threshold
+		} else {
+			alignment < -self.threshold
+		};
+		(flipped != self.flipped).then(|| {
+			                         self.flipped = flipped;
+			                         flipped
+		                         })
+	}
+
+	pub fn is_flipped(&self) -> bool { self.flipped }
+}
+
+impl Default for FlipDetector {
+	fn default() -> Self { Self::new(0.5) }
+}
+
+
+/// Accelerometer processor, should be updated once per frame.
+///
+/// Reads the accelerometer of its [`InputSource`], the device by default.
+/// The device accelerometer must be [enabled](crate::peripherals::Accelerometer::enable) separately.
+#[derive(Debug, Clone)]
+pub struct Motion<S: InputSource = Live> {
+	source: S,
+	pub low_pass: LowPass,
+	pub calibration: Calibration,
+	pub shake: ShakeDetector,
+	pub flip: FlipDetector,
+	raw: Reading,
+	filtered: Reading,
+	shaken: bool,
+	flip_changed: bool,
+}
+
+impl<Api: api::Api + Default + Copy> Motion<Live<Api>> {
+	/// Creates processor reading the accelerometer of the device.
+	pub fn new() -> Self { Self::new_with(Live::new(Readings::ACCELEROMETER)) }
+}
+
+impl<Api: api::Api + Default + Copy> Default for Motion<Live<Api>> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<S: InputSource> Motion<S> {
+	pub fn new_with(source: S) -> Self {
+		Self { source,
+		       low_pass: LowPass::new(0.2),
+		       calibration: Calibration::default(),
+		       shake: ShakeDetector::default(),
+		       flip: FlipDetector::default(),
+		       raw: Reading::default(),
+		       filtered: Reading::default(),
+		       shaken: false,
+		       flip_changed: false }
+	}
+
+	pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+		self.calibration = calibration;
+		self
+	}
+
+	pub fn source(&self) -> &S { &self.source }
+	pub fn source_mut(&mut self) -> &mut S { &mut self.source }
+	pub fn into_source(self) -> S { self.source }
+
+	/// Advances the source to the next frame, then advances by `dt` seconds with its accelerometer reading.
+	///
+	/// Nothing changes if the frame has no accelerometer reading.
+	pub fn update(&mut self, dt: c_float) {
+		self.source.advance();
+		if let Some(reading) = self.source.accelerometer() {
+			self.update_with(reading, dt);
+		}
+	}
+
+	/// Advances by `dt` seconds with the given `reading`.
+	pub fn update_with(&mut self, reading: Reading, dt: c_float) {
+		self.raw = reading;
+		self.filtered = self.low_pass.filter(reading);
+		self.shaken = self.shake.update(reading, dt);
+		self.flip_changed = self.flip.update(&self.filtered, &self.calibration).is_some();
+	}
+
+	/// Sets the current filtered reading as neutral orientation.
+	pub fn calibrate(&mut self) -> Calibration {
+		self.calibration = Calibration::new(self.filtered);
+		self.calibration
+	}
+
+	pub fn raw(&self) -> Reading { self.raw }
+	pub fn filtered(&self) -> Reading { self.filtered }
+
+	/// Filtered pitch in degrees relative to the neutral orientation.
+	pub fn pitch(&self) -> c_float { self.calibration.pitch(&self.filtered) }
+	/// Filtered roll in degrees relative to the neutral orientation.
+	pub fn roll(&self) -> c_float { self.calibration.roll(&self.filtered) }
+
+	/// Shake started during the last update.
+	pub fn shaken(&self) -> bool { self.shaken }
+
+	pub fn is_flipped(&self) -> bool { self.flip.is_flipped() }
+	/// Device was turned upside down during the last update.
+	pub fn just_flipped(&self) -> bool { self.flip_changed && self.flip.is_flipped() }
+	/// Device was turned back from upside down during the last update.
+	pub fn just_unflipped(&self) -> bool { self.flip_changed && !self.flip.is_flipped() }
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::source::Script;
+
+	#[test]
+	fn calibration_bytes() {
+		let calibration = Calibration::new(Reading::new(0.1, -0.7, 0.7));
+		assert_eq!(
+		           Some(calibration),
+		           Calibration::from_bytes(&calibration.to_bytes())
+		);
+		assert_eq!(None, Calibration::from_bytes(&[0; Calibration::SIZE]));
+		assert_eq!(None, Calibration::from_bytes(&[1, 2, 3]));
+
+		let mut calibrator = Calibrator::new(2);
+		assert_eq!(None, calibrator.add(Reading::new(0.0, 1.0, 0.0)));
+		let calibration = calibrator.add(Reading::new(0.0, 0.0, 1.0)).unwrap();
+		assert_eq!(Reading::new(0.0, 0.5, 0.5), calibration.neutral);
+	}
+
+	#[test]
+	fn shake_and_flip() {
+		let mut motion = Motion::new_with(Script::new());
+		motion.low_pass.alpha = 1.0;
+
+		motion.update_with(Reading::new(0.0, 0.0, 1.0), 0.1);
+		assert!(!motion.shaken() && !motion.is_flipped());
+		assert_eq!(0.0, motion.pitch());
+
+		motion.update_with(Reading::new(2.0, 0.0, 1.0), 0.1);
+		assert!(motion.shaken());
+		motion.update_with(Reading::new(0.0, 0.0, 1.0), 0.1);
+		assert!(!motion.shaken());
+
+		motion.update_with(Reading::new(0.0, 0.0, -1.0), 0.1);
+		assert!(motion.just_flipped());
+		motion.update_with(Reading::new(0.0, 0.0, -1.0), 0.1);
+		assert!(!motion.just_flipped() && motion.is_flipped());
+		motion.update_with(Reading::new(0.0, 0.9, 0.4), 0.1);
+		assert!(motion.is_flipped());
+		motion.update_with(Reading::new(0.0, 0.0, 1.0), 0.1);
+		assert!(motion.just_unflipped());
+	}
+}
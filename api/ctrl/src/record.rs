@@ -0,0 +1,323 @@
+//! Deterministic input recording and replay.
+//!
+//! [`Recorder`] captures per-frame buttons, crank and accelerometer readings together with an RNG seed,
+//! [`Replay`] plays them back frame-exact. Both are [input sources](crate::source::InputSource),
+//! wrap the [live](crate::source::Live) one into [`Recording`](crate::source::Recording) to record it,
+//! so game code reads its input the same way in all three modes.
+//!
+//! Recording format, all numbers little-endian:
+//! - header: magic `PDIR`, version `u8`, seed `u32`, number of frames `u32`;
+//! - each frame: current, pushed and released buttons as `u8`, flags `u8`
+//!   (bit `0` crank docked, bit `1` accelerometer present),
+//!   crank angle and change as `f32` if not docked,
+//!   accelerometer `x, y, z` as `f32` if present.
+
+use alloc::vec::Vec;
+use core::ffi::c_float;
+use sys::ffi::PDButtons;
+
+use fs::Path;
+
+use crate::api;
+use crate::error::ApiError;
+use crate::error::Error;
+use crate::motion::Reading;
+use crate::peripherals::Peripherals;
+use crate::peripherals::State;
+
+
+const MAGIC: &[u8; 4] = b"PDIR";
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 13;
+
+const DOCKED: u8 = 0b01;
+const ACCELEROMETER: u8 = 0b10;
+
+
+/// Input readings of a single frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct InputFrame {
+	/// Bits of buttons currently down.
+	pub current: u8,
+	/// Bits of buttons pushed over the previous update cycle.
+	pub pushed: u8,
+	/// Bits of buttons released over the previous update cycle.
+	pub released: u8,
+	pub docked: bool,
+	/// Crank angle, `0` while docked.
+	pub angle: c_float,
+	/// Crank change, `0` while docked.
+	pub change: c_float,
+	/// Accelerometer reading, if it was captured.
+	pub accelerometer: Option<Reading>,
+}
+
+impl InputFrame {
+	/// Frame with nothing pressed and the crank docked.
+	pub const IDLE: Self = Self { current: 0,
+	                              pushed: 0,
+	                              released: 0,
+	                              docked: true,
+	                              angle: 0.0,
+	                              change: 0.0,
+	                              accelerometer: None };
+
+	/// Reads all peripherals, including accelerometer if `accelerometer` is `true`.
+	///
+	/// Calls [`Crank::change`](crate::peripherals::Crank::change),
+	/// so it should not be used elsewhere in the same frame.
+	pub fn capture<Api: api::Api + Copy>(peripherals: &Peripherals<Api>, accelerometer: bool) -> Self {
+		let readings = Readings { accelerometer,
+		                          ..Readings::BUTTONS_AND_CRANK };
+		Self::capture_only(peripherals, readings)
+	}
+
+	/// Reads only the given peripherals, the rest stays [idle](Self::IDLE).
+	pub fn capture_only<Api: api::Api + Copy>(peripherals: &Peripherals<Api>, readings: Readings) -> Self {
+		let mut frame = Self::IDLE;
+		if readings.buttons {
+			let buttons = peripherals.buttons().get();
+			frame.current = buttons.current.0 as u8;
+			frame.pushed = buttons.pushed.0 as u8;
+			frame.released = buttons.released.0 as u8;
+		}
+		if readings.crank {
+			let crank = peripherals.crank();
+			frame.docked = crank.docked();
+			if !frame.docked {
+				frame.angle = crank.angle();
+				frame.change = crank.change();
+			}
+		}
+		if readings.accelerometer {
+			frame.accelerometer = Some(peripherals.accelerometer().get().into());
+		}
+		frame
+	}
+
+	/// Buttons state as returned by [`Buttons::get`](crate::peripherals::Buttons::get).
+	pub fn buttons(&self) -> State {
+		State { current: PDButtons(self.current as _),
+		        pushed: PDButtons(self.pushed as _),
+		        released: PDButtons(self.released as _) }
+	}
+
+	fn encode(&self, out: &mut Vec<u8>) {
+		let mut flags = 0;
+		if self.docked {
+			flags |= DOCKED;
+		}
+		if self.accelerometer.is_some() {
+			flags |= ACCELEROMETER;
+		}
+		out.extend_from_slice(&[self.current, self.pushed, self.released, flags]);
+		if !self.docked {
+			out.extend_from_slice(&self.angle.to_le_bytes());
+			out.extend_from_slice(&self.change.to_le_bytes());
+		}
+		if let Some(reading) = self.accelerometer {
+			out.extend_from_slice(&reading.x.to_le_bytes());
+			out.extend_from_slice(&reading.y.to_le_bytes());
+			out.extend_from_slice(&reading.z.to_le_bytes());
+		}
+	}
+
+	fn decode(bytes: &mut &[u8]) -> Option<Self> {
+		let [current, pushed, released, flags] = take(bytes)?;
+		let docked = flags & DOCKED != 0;
+		let (angle, change) = if docked {
+			(0.0, 0.0)
+		} else {
+			(float(bytes)?, float(bytes)?)
+		};
+		let accelerometer = if flags & ACCELEROMETER != 0 {
+			Some(Reading::new(float(bytes)?, float(bytes)?, float(bytes)?))
+		} else {
+			None
+		};
+		Some(Self { current,
+		            pushed,
+		            released,
+		            docked,
+		            angle,
+		            change,
+		            accelerometer })
+	}
+}
+
+
+/// Peripherals read into an [`InputFrame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Readings {
+	pub buttons: bool,
+	pub crank: bool,
+	pub accelerometer: bool,
+}
+
+impl Readings {
+	pub const BUTTONS: Self = Self { buttons: true,
+	                                 crank: false,
+	                                 accelerometer: false };
+	pub const CRANK: Self = Self { buttons: false,
+	                               crank: true,
+	                               accelerometer: false };
+	pub const ACCELEROMETER: Self = Self { buttons: false,
+	                                       crank: false,
+	                                       accelerometer: true };
+	pub const BUTTONS_AND_CRANK: Self = Self { buttons: true,
+	                                           crank: true,
+	                                           accelerometer: false };
+	pub const ALL: Self = Self { buttons: true,
+	                             crank: true,
+	                             accelerometer: true };
+}
+
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+	let (head, tail) = bytes.split_first_chunk::<N>()?;
+	*bytes = tail;
+	Some(*head)
+}
+
+fn float(bytes: &mut &[u8]) -> Option<c_float> { take(bytes).map(c_float::from_le_bytes) }
+
+
+/// Accumulates encoded input frames.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+	seed: u32,
+	frames: u32,
+	data: Vec<u8>,
+}
+
+impl Recorder {
+	/// Creates recorder of a session using the given RNG `seed`.
+	pub fn new(seed: u32) -> Self {
+		let mut data = Vec::new();
+		data.extend_from_slice(MAGIC);
+		data.push(VERSION);
+		data.extend_from_slice(&seed.to_le_bytes());
+		data.extend_from_slice(&0_u32.to_le_bytes());
+		Self { seed,
+		       frames: 0,
+		       data }
+	}
+
+	/// RNG seed to use for the recorded session.
+	pub fn seed(&self) -> u32 { self.seed }
+
+	/// Number of recorded frames.
+	pub fn len(&self) -> usize { self.frames as usize }
+	pub fn is_empty(&self) -> bool { self.frames == 0 }
+
+	pub fn record(&mut self, frame: &InputFrame) {
+		frame.encode(&mut self.data);
+		self.frames += 1;
+		self.data[9..HEADER_SIZE].copy_from_slice(&self.frames.to_le_bytes());
+	}
+
+	/// Encoded recording.
+	pub fn as_bytes(&self) -> &[u8] { &self.data }
+
+	/// Writes recording to the file at `path` in the data directory.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ApiError> {
+		fs::write(path, &self.data).map_err(ApiError::from_err)
+	}
+}
+
+
+/// Decoded recording played back frame by frame.
+#[derive(Debug, Clone)]
+pub struct Replay {
+	seed: u32,
+	frames: Vec<InputFrame>,
+	/// Number of frames played, past the end once advanced beyond the last one.
+	position: usize,
+}
+
+impl Replay {
+	pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+		let header: [u8; HEADER_SIZE] = take(&mut bytes).ok_or(Error::InvalidRecording)?;
+		if &header[0..4] != MAGIC || header[4] != VERSION {
+			return Err(Error::InvalidRecording);
+		}
+		let seed = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+		let count = u32::from_le_bytes([header[9], header[10], header[11], header[12]]) as usize;
+
+		let mut frames = Vec::with_capacity(count.min(bytes.len() / 4));
+		for _ in 0..count {
+			frames.push(InputFrame::decode(&mut bytes).ok_or(Error::InvalidRecording)?);
+		}
+		if !bytes.is_empty() {
+			return Err(Error::InvalidRecording);
+		}
+
+		Ok(Self { seed,
+		          frames,
+		          position: 0 })
+	}
+
+	/// Reads recording from the file at `path` in the data directory.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApiError> {
+		let bytes = fs::read(path, true).map_err(ApiError::from_err)?;
+		Self::from_bytes(&bytes).map_err(Into::into)
+	}
+
+	/// RNG seed of the recorded session.
+	pub fn seed(&self) -> u32 { self.seed }
+
+	pub fn frames(&self) -> &[InputFrame] { &self.frames }
+	/// Index of the next frame.
+	pub fn position(&self) -> usize { self.position.min(self.frames.len()) }
+	pub fn is_finished(&self) -> bool { self.position >= self.frames.len() }
+	pub fn rewind(&mut self) { self.position = 0 }
+
+	/// Returns the next frame, `None` at the end of the recording.
+	pub fn next_frame(&mut self) -> Option<InputFrame> {
+		let frame = self.frames.get(self.position).copied();
+		self.position = (self.position + 1).min(self.frames.len() + 1);
+		frame
+	}
+
+	/// Frame last returned by [`next_frame`](Self::next_frame), `None` before the start and after the end.
+	pub fn current(&self) -> Option<InputFrame> {
+		self.position
+		    .checked_sub(1)
+		    .and_then(|i| self.frames.get(i))
+		    .copied()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let frames = [
+		              InputFrame { current: 0b100000,
+		                           pushed: 0b100000,
+		                           docked: true,
+		                           ..Default::default() },
+		              InputFrame { angle: 45.0,
+		                           change: -3.5,
+		                           accelerometer: Some(Reading::new(0.0, 0.5, -1.0)),
+		                           ..Default::default() },
+		];
+		let mut recorder = Recorder::new(42);
+		frames.iter().for_each(|frame| recorder.record(frame));
+		assert_eq!(HEADER_SIZE + 4 + 4 + 8 + 12, recorder.as_bytes().len());
+
+		let mut replay = Replay::from_bytes(recorder.as_bytes()).unwrap();
+		assert_eq!(42, replay.seed());
+		assert_eq!(&frames[..], replay.frames());
+		assert_eq!(Some(frames[0]), replay.next_frame());
+		assert!(replay.next_frame().unwrap().buttons().current.0 == 0);
+		assert_eq!(None, replay.next_frame());
+
+		let bytes = recorder.as_bytes();
+		assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+		assert!(Replay::from_bytes(b"PDIX").is_err());
+	}
+}
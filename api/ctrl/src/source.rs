@@ -0,0 +1,347 @@
+//! Pluggable input sources.
+//!
+//! [`InputSource`] abstracts buttons, crank and accelerometer readings,
+//! so game code and input helpers like [`InputState`](crate::input::InputState),
+//! [`CrankState`](crate::crank::CrankState) and [`Motion`](crate::motion::Motion)
+//! can be driven by the device, a script, a recording, AI or remote input the same way.
+
+use alloc::vec::Vec;
+use core::ffi::c_float;
+use sys::ffi::PDButtons;
+
+use crate::api;
+use crate::buttons::Button;
+use crate::motion::Reading;
+use crate::peripherals::Peripherals;
+use crate::peripherals::State;
+use crate::record::InputFrame;
+use crate::record::Readings;
+use crate::record::Recorder;
+use crate::record::Replay;
+
+
+/// Source of per-frame input readings.
+pub trait InputSource {
+	/// Moves to the next frame. Should be called once per frame before reading.
+	fn advance(&mut self);
+
+	/// Readings of the current frame.
+	fn frame(&self) -> InputFrame;
+
+	/// Buttons state of the current frame.
+	fn buttons(&self) -> State { self.frame().buttons() }
+
+	/// Crank `(docked, angle, change)` of the current frame.
+	fn crank(&self) -> (bool, c_float, c_float) {
+		let frame = self.frame();
+		(frame.docked, frame.angle, frame.change)
+	}
+
+	/// Accelerometer reading of the current frame, if available.
+	fn accelerometer(&self) -> Option<Reading> { self.frame().accelerometer }
+}
+
+impl<T: InputSource + ?Sized> InputSource for &'_ mut T {
+	fn advance(&mut self) { (**self).advance() }
+	fn frame(&self) -> InputFrame { (**self).frame() }
+}
+
+
+/// Reads the device.
+#[derive(Debug, Clone)]
+pub struct Live<Api: api::Api = api::Default> {
+	peripherals: Peripherals<Api>,
+	readings: Readings,
+	frame: InputFrame,
+}
+
+impl<Api: api::Api + Default> Live<Api> {
+	/// Creates live source reading the given peripherals.
+	///
+	/// The accelerometer must be [enabled](crate::peripherals::Accelerometer::enable) separately.
+	pub fn new(readings: Readings) -> Self { Self::new_with(Peripherals::new(), readings) }
+}
+
+impl<Api: api::Api + Default> Default for Live<Api> {
+	/// Reads buttons and crank.
+	fn default() -> Self { Self::new(Readings::BUTTONS_AND_CRANK) }
+}
+
+impl<Api: api::Api> Live<Api> {
+	pub fn new_with(peripherals: Peripherals<Api>, readings: Readings) -> Self {
+		Self { peripherals,
+		       readings,
+		       frame: InputFrame::IDLE }
+	}
+
+	pub fn readings(&self) -> Readings { self.readings }
+}
+
+impl<Api: api::Api + Copy> InputSource for Live<Api> {
+	/// Reads the device.
+	///
+	/// Reading the crank calls [`Crank::change`](crate::peripherals::Crank::change),
+	/// so only one source should read it per frame.
+	fn advance(&mut self) { self.frame = InputFrame::capture_only(&self.peripherals, self.readings) }
+	fn frame(&self) -> InputFrame { self.frame }
+}
+
+
+/// Plays recorded frames, nothing is pressed after the end.
+impl InputSource for Replay {
+	fn advance(&mut self) { let _ = self.next_frame(); }
+	fn frame(&self) -> InputFrame { self.current().unwrap_or(InputFrame::IDLE) }
+}
+
+
+/// Records every frame of the inner source.
+#[derive(Debug, Clone)]
+pub struct Recording<S: InputSource> {
+	pub source: S,
+	pub recorder: Recorder,
+}
+
+impl<S: InputSource> Recording<S> {
+	/// Records the `source` of a session using the given RNG `seed`.
+	pub fn new(source: S, seed: u32) -> Self {
+		Self { source,
+		       recorder: Recorder::new(seed) }
+	}
+
+	/// RNG seed of the recorded session.
+	pub fn seed(&self) -> u32 { self.recorder.seed() }
+}
+
+impl<S: InputSource> InputSource for Recording<S> {
+	fn advance(&mut self) {
+		self.source.advance();
+		self.recorder.record(&self.source.frame());
+	}
+
+	fn frame(&self) -> InputFrame { self.source.frame() }
+}
+
+
+fn bits(buttons: &[Button]) -> u8 {
+	buttons.iter()
+	       .map(|button| Into::<PDButtons>::into(*button).0 as u8)
+	       .fold(0, |acc, bit| acc | bit)
+}
+
+
+/// Predefined sequence of frames, e.g. for tests and demos.
+///
+/// Built step by step, pushed and released buttons are derived from held ones.
+/// Nothing is pressed after the end.
+#[derive(Debug, Clone)]
+pub struct Script {
+	frames: Vec<InputFrame>,
+	/// State continued by the next step.
+	last: InputFrame,
+	position: usize,
+	looping: bool,
+}
+
+impl Default for Script {
+	fn default() -> Self { Self::new() }
+}
+
+impl Script {
+	pub fn new() -> Self {
+		Self { frames: Vec::new(),
+		       last: InputFrame::IDLE,
+		       position: 0,
+		       looping: false }
+	}
+
+	/// Starts over after the last frame instead of staying idle.
+	pub fn looping(mut self, looping: bool) -> Self {
+		self.looping = looping;
+		self
+	}
+
+	/// Appends raw `frame`, keeping its pushed and released buttons as is.
+	pub fn push(mut self, frame: InputFrame) -> Self {
+		self.last = frame;
+		self.frames.push(frame);
+		self
+	}
+
+	/// Holds `buttons` for `frames` frames, releasing all others.
+	pub fn hold(mut self, buttons: &[Button], frames: usize) -> Self {
+		let current = bits(buttons);
+		for _ in 0..frames {
+			let last = self.last;
+			let previous = last.current;
+			self = self.push(InputFrame { current,
+			                              pushed: current & !previous,
+			                              released: previous & !current,
+			                              change: 0.0,
+			                              ..last });
+		}
+		self
+	}
+
+	/// Presses and immediately releases `buttons`, taking two frames.
+	pub fn tap(self, buttons: &[Button]) -> Self { self.hold(buttons, 1).wait(1) }
+
+	/// Releases all buttons for `frames` frames.
+	pub fn wait(self, frames: usize) -> Self { self.hold(&[], frames) }
+
+	/// Undocks the crank and turns it by `change` degrees per frame for `frames` frames,
+	/// keeping buttons held.
+	pub fn crank(mut self, change: c_float, frames: usize) -> Self {
+		for _ in 0..frames {
+			let last = self.last;
+			let angle = (last.angle + change) % 360.0;
+			let angle = if angle < 0.0 { angle + 360.0 } else { angle };
+			self = self.push(InputFrame { pushed: 0,
+			                              released: 0,
+			                              docked: false,
+			                              angle,
+			                              change,
+			                              ..last });
+		}
+		self
+	}
+
+	/// Docks the crank.
+	pub fn dock(self) -> Self {
+		let last = self.last;
+		self.push(InputFrame { pushed: 0,
+		                       released: 0,
+		                       docked: true,
+		                       angle: 0.0,
+		                       change: 0.0,
+		                       ..last })
+	}
+
+	/// Sets accelerometer `reading` for `frames` frames.
+	pub fn tilt(mut self, reading: Reading, frames: usize) -> Self {
+		for _ in 0..frames {
+			let last = self.last;
+			self = self.push(InputFrame { pushed: 0,
+			                              released: 0,
+			                              change: 0.0,
+			                              accelerometer: Some(reading),
+			                              ..last });
+		}
+		self
+	}
+
+	pub fn len(&self) -> usize { self.frames.len() }
+	pub fn is_empty(&self) -> bool { self.frames.is_empty() }
+	pub fn is_finished(&self) -> bool { !self.looping && self.position > self.frames.len() }
+	pub fn rewind(&mut self) { self.position = 0 }
+}
+
+impl InputSource for Script {
+	fn advance(&mut self) {
+		if self.looping && self.position >= self.frames.len() {
+			self.position = 0;
+		}
+		self.position = (self.position + 1).min(self.frames.len() + 1);
+	}
+
+	fn frame(&self) -> InputFrame {
+		self.position
+		    .checked_sub(1)
+		    .and_then(|i| self.frames.get(i))
+		    .copied()
+		    .unwrap_or(InputFrame::IDLE)
+	}
+}
+
+
+/// Combines two sources, e.g. the device and remote input.
+///
+/// Buttons of both are combined, crank and accelerometer are taken from
+/// the first source if available there, otherwise from the second.
+#[derive(Debug, Clone)]
+pub struct Merged<A: InputSource, B: InputSource>(pub A, pub B);
+
+impl<A: InputSource, B: InputSource> InputSource for Merged<A, B> {
+	fn advance(&mut self) {
+		self.0.advance();
+		self.1.advance();
+	}
+
+	fn frame(&self) -> InputFrame {
+		let a = self.0.frame();
+		let b = self.1.frame();
+		let current = a.current | b.current;
+		let crank = if a.docked { b } else { a };
+		InputFrame { current,
+		             pushed: a.pushed | b.pushed,
+		             released: (a.released | b.released) & !current,
+		             docked: crank.docked,
+		             angle: crank.angle,
+		             change: crank.change,
+		             accelerometer: a.accelerometer.or(b.accelerometer) }
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::crank::CrankState;
+	use crate::input::InputState;
+
+	#[test]
+	fn script_and_merge() {
+		let script = Script::new().tap(&[Button::A])
+		                          .crank(30.0, 2)
+		                          .hold(&[Button::B], 1);
+		assert_eq!(5, script.len());
+
+		let mut input = InputState::new_with(Merged(script.clone(), Script::new().hold(&[Button::Up], 10)));
+		let mut crank = CrankState::new_with(script);
+
+		input.update(0.1);
+		crank.update(0.1);
+		assert!(input.just_pressed(Button::A) && input.just_pressed(Button::Up));
+
+		input.update(0.1);
+		crank.update(0.1);
+		assert!(input.just_released(Button::A) && input.is_down(Button::Up));
+
+		input.update(0.1);
+		input.update(0.1);
+		crank.update(0.1);
+		crank.update(0.1);
+		assert_eq!(60.0, crank.angle());
+		assert_eq!(1, crank.ticks(12));
+
+		input.update(0.1);
+		assert!(input.just_pressed(Button::B) && input.is_down(Button::Up));
+		assert!(!input.source().0.is_finished());
+
+		input.update(0.1);
+		assert!(input.source().0.is_finished());
+		assert_eq!(0, input.source().0.frame().current);
+	}
+
+	#[test]
+	fn record_and_replay() {
+		let script = Script::new().tap(&[Button::A]).crank(-15.0, 1);
+		let mut recording = Recording::new(script, 7);
+		(0..3).for_each(|_| recording.advance());
+		assert_eq!(3, recording.recorder.len());
+
+		let replay = Replay::from_bytes(recording.recorder.as_bytes()).unwrap();
+		assert_eq!(7, replay.seed());
+		let mut input = InputState::new_with(replay);
+		input.update(0.1);
+		assert!(input.just_pressed(Button::A));
+		input.update(0.1);
+		assert!(input.just_released(Button::A));
+		input.update(0.1);
+		assert_eq!(Some(-15.0), input.source().current().map(|frame| frame.change));
+		assert!(input.source().is_finished());
+
+		input.update(0.1);
+		assert_eq!(None, input.source().current());
+		assert_eq!(InputFrame::IDLE, input.source().frame());
+	}
+}
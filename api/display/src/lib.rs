@@ -1,10 +1,13 @@
 #![cfg_attr(not(test), no_std)]
 extern crate sys;
+extern crate alloc;
 
 use core::ffi::c_float;
 use core::ffi::c_int;
 use core::ffi::c_uint;
 
+pub mod state;
+
 
 #[derive(Debug, Clone, Copy)]
 pub struct Display<Api = api::Default>(Api);
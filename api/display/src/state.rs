@@ -0,0 +1,475 @@
+//! Display settings that can be read back, stacked and animated.
+//!
+//! The system only has setters for display settings, so [`DisplayState`] remembers every value it sets.
+//! Whole configurations can be pushed and popped, e.g. for a pause screen,
+//! and the display offset can be animated for screen shake.
+
+use alloc::vec::Vec;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_uint;
+
+use crate::api;
+use crate::Display;
+use crate::DisplayScale;
+
+
+/// Complete display configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplaySettings {
+	pub refresh_rate: c_float,
+	pub scale: DisplayScale,
+	pub inverted: bool,
+	/// Mosaic effect `(x, y)`, each in `0..=3`.
+	pub mosaic: (c_uint, c_uint),
+	/// Flipped on `(x, y)` axes.
+	pub flipped: (bool, bool),
+	pub offset: (c_int, c_int),
+}
+
+impl DisplaySettings {
+	/// Settings the system starts with.
+	pub const DEFAULT: Self = Self { refresh_rate: 30.0,
+	                                 scale: DisplayScale::Normal,
+	                                 inverted: false,
+	                                 mosaic: (0, 0),
+	                                 flipped: (false, false),
+	                                 offset: (0, 0) };
+
+	pub const fn with_refresh_rate(mut self, rate: c_float) -> Self {
+		self.refresh_rate = rate;
+		self
+	}
+
+	pub const fn with_scale(mut self, scale: DisplayScale) -> Self {
+		self.scale = scale;
+		self
+	}
+
+	pub const fn with_inverted(mut self, inverted: bool) -> Self {
+		self.inverted = inverted;
+		self
+	}
+
+	pub const fn with_mosaic(mut self, x: c_uint, y: c_uint) -> Self {
+		self.mosaic = (x, y);
+		self
+	}
+
+	pub const fn with_flipped(mut self, x: bool, y: bool) -> Self {
+		self.flipped = (x, y);
+		self
+	}
+
+	pub const fn with_offset(mut self, x: c_int, y: c_int) -> Self {
+		self.offset = (x, y);
+		self
+	}
+}
+
+impl Default for DisplaySettings {
+	fn default() -> Self { Self::DEFAULT }
+}
+
+
+/// Measures actual frame rate against the target one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FramePacer {
+	/// Smoothed frame time in seconds.
+	frame_time: c_float,
+	/// Weight of the newest frame in the average, `0..=1`.
+	smoothing: c_float,
+	frames: u32,
+	late: u32,
+}
+
+impl FramePacer {
+	pub const fn new() -> Self {
+		Self { frame_time: 0.0,
+		       smoothing: 0.1,
+		       frames: 0,
+		       late: 0 }
+	}
+
+	/// Sets weight of the newest frame in the running average, clamped to `0.01..=1`.
+	pub fn set_smoothing(&mut self, smoothing: c_float) { self.smoothing = smoothing.clamp(0.01, 1.0) }
+
+	/// Registers a frame that took `dt` seconds, counting it as late if it exceeds the `target` rate by 10%.
+	pub fn tick(&mut self, dt: c_float, target: c_float) {
+		if dt <= 0.0 {
+			return;
+		}
+		self.frame_time = if self.frames == 0 {
+			dt
+		} else {
+			self.frame_time + (dt - self.frame_time) * self.smoothing
+		};
+		self.frames = self.frames.saturating_add(1);
+		if target > 0.0 && dt > 1.1 / target {
+			self.late = self.late.saturating_add(1);
+		}
+	}
+
+	/// Smoothed actual frames per second.
+	pub fn frame_rate(&self) -> c_float {
+		if self.frame_time > 0.0 {
+			1.0 / self.frame_time
+		} else {
+			0.0
+		}
+	}
+
+	/// Smoothed frame time in seconds.
+	pub fn frame_time(&self) -> c_float { self.frame_time }
+	pub fn frames(&self) -> u32 { self.frames }
+	/// Number of frames that missed the target rate.
+	pub fn late_frames(&self) -> u32 { self.late }
+
+	pub fn reset(&mut self) {
+		*self = Self { smoothing: self.smoothing,
+		               ..Self::new() }
+	}
+}
+
+impl Default for FramePacer {
+	fn default() -> Self { Self::new() }
+}
+
+
+/// Linear offset animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tween {
+	from: (c_float, c_float),
+	to: (c_float, c_float),
+	duration: c_float,
+	elapsed: c_float,
+}
+
+impl Tween {
+	fn value(&self) -> (c_float, c_float) {
+		let t = if self.duration > 0.0 {
+			(self.elapsed / self.duration).min(1.0)
+		} else {
+			1.0
+		};
+		(self.from.0 + (self.to.0 - self.from.0) * t, self.from.1 + (self.to.1 - self.from.1) * t)
+	}
+
+	fn is_done(&self) -> bool { self.elapsed >= self.duration }
+}
+
+
+/// Decaying random offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Shake {
+	magnitude: c_float,
+	duration: c_float,
+	elapsed: c_float,
+}
+
+impl Shake {
+	/// Current amplitude, decaying linearly to zero.
+	fn power(&self) -> c_float {
+		if self.duration > 0.0 {
+			self.magnitude * (1.0 - self.elapsed / self.duration).max(0.0)
+		} else {
+			0.0
+		}
+	}
+}
+
+
+/// Display wrapper remembering current settings.
+///
+/// All changes must go through this type for the remembered values to stay correct.
+#[derive(Debug, Clone)]
+pub struct DisplayState<Api: api::Api = api::Default> {
+	display: Display<Api>,
+	current: DisplaySettings,
+	stack: Vec<DisplaySettings>,
+	tween: Option<Tween>,
+	shake: Option<Shake>,
+	/// Offset actually set to the display, including animations.
+	applied_offset: (c_int, c_int),
+	seed: u32,
+	pacer: FramePacer,
+}
+
+impl<Api: api::Api + Default> DisplayState<Api> {
+	/// Creates state assuming the display has [default settings](DisplaySettings::DEFAULT).
+	pub fn new() -> Self { Self::new_with(Display::new_with(Api::default()), DisplaySettings::DEFAULT) }
+}
+
+impl<Api: api::Api + Default> Default for DisplayState<Api> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<Api: api::Api> DisplayState<Api> {
+	/// Creates state for the `display`, assuming it currently has the given `settings`.
+	///
+	/// Nothing is applied to the display.
+	pub fn new_with(display: Display<Api>, settings: DisplaySettings) -> Self {
+		Self { display,
+		       current: settings,
+		       stack: Vec::new(),
+		       tween: None,
+		       shake: None,
+		       applied_offset: settings.offset,
+		       seed: 0x2545_F491,
+		       pacer: FramePacer::new() }
+	}
+
+	pub fn display(&self) -> &Display<Api> { &self.display }
+
+	/// Currently set configuration.
+	///
+	/// The offset is the base one, without shake and animation.
+	pub fn settings(&self) -> &DisplaySettings { &self.current }
+
+	pub fn refresh_rate(&self) -> c_float { self.current.refresh_rate }
+	pub fn scale(&self) -> DisplayScale { self.current.scale }
+	pub fn inverted(&self) -> bool { self.current.inverted }
+	pub fn mosaic(&self) -> (c_uint, c_uint) { self.current.mosaic }
+	pub fn flipped(&self) -> (bool, bool) { self.current.flipped }
+	pub fn offset(&self) -> (c_int, c_int) { self.current.offset }
+
+
+	pub fn set_refresh_rate(&mut self, rate: c_float) {
+		self.current.refresh_rate = rate;
+		self.display.set_refresh_rate(rate);
+	}
+
+	pub fn set_scale(&mut self, scale: DisplayScale) {
+		self.current.scale = scale;
+		self.display.set_scale(scale);
+	}
+
+	pub fn set_inverted(&mut self, value: bool) {
+		self.current.inverted = value;
+		self.display.set_inverted(value);
+	}
+
+	pub fn set_mosaic(&mut self, x: c_uint, y: c_uint) {
+		self.current.mosaic = (x, y);
+		self.display.set_mosaic(x, y);
+	}
+
+	pub fn set_flipped(&mut self, x: bool, y: bool) {
+		self.current.flipped = (x, y);
+		self.display.set_flipped(x, y);
+	}
+
+	/// Sets the base offset, cancelling offset animation.
+	pub fn set_offset(&mut self, x: c_int, y: c_int) {
+		self.current.offset = (x, y);
+		self.tween = None;
+		self.apply_offset();
+	}
+
+
+	/// Applies the whole configuration to the display.
+	pub fn apply(&mut self, settings: DisplaySettings) {
+		self.current = settings;
+		self.tween = None;
+		self.display.set_refresh_rate(settings.refresh_rate);
+		self.display.set_scale(settings.scale);
+		self.display.set_inverted(settings.inverted);
+		self.display.set_mosaic(settings.mosaic.0, settings.mosaic.1);
+		self.display.set_flipped(settings.flipped.0, settings.flipped.1);
+		self.apply_offset();
+	}
+
+	/// Saves current configuration on the stack.
+	pub fn push(&mut self) { self.stack.push(self.current) }
+
+	/// Saves current configuration on the stack and applies the given one.
+	pub fn push_with(&mut self, settings: DisplaySettings) {
+		self.push();
+		self.apply(settings);
+	}
+
+	/// Restores the last pushed configuration.
+	///
+	/// Returns the configuration that was active before, or `None` if the stack is empty.
+	pub fn pop(&mut self) -> Option<DisplaySettings> {
+		let settings = self.stack.pop()?;
+		let previous = self.current;
+		self.apply(settings);
+		Some(previous)
+	}
+
+	/// Number of saved configurations.
+	pub fn depth(&self) -> usize { self.stack.len() }
+
+
+	/// Moves the base offset to `x, y` over `duration` seconds.
+	pub fn animate_offset(&mut self, x: c_int, y: c_int, duration: c_float) {
+		let from = match self.tween {
+			Some(tween) => tween.value(),
+			None => (self.current.offset.0 as c_float, self.current.offset.1 as c_float),
+		};
+		self.current.offset = (x, y);
+		self.tween = Some(Tween { from,
+		                          to: (x as c_float, y as c_float),
+		                          duration,
+		                          elapsed: 0.0 });
+	}
+
+	/// Shakes the screen by up to `magnitude` pixels, decaying over `duration` seconds.
+	///
+	/// Stronger shake replaces the running one.
+	pub fn shake(&mut self, magnitude: c_float, duration: c_float) {
+		let remaining = self.shake.map_or(0.0, |s| s.power());
+		if magnitude >= remaining {
+			self.shake = Some(Shake { magnitude,
+			                          duration,
+			                          elapsed: 0.0 });
+		}
+	}
+
+	pub fn stop_shake(&mut self) {
+		self.shake = None;
+		self.apply_offset();
+	}
+
+	pub fn is_animating(&self) -> bool { self.tween.is_some() || self.shake.is_some() }
+
+
+	/// Advances animations and frame pacing by `dt` seconds.
+	///
+	/// Should be called once per frame.
+	pub fn update(&mut self, dt: c_float) {
+		self.pacer.tick(dt, self.current.refresh_rate);
+
+		if let Some(tween) = self.tween.as_mut() {
+			tween.elapsed += dt;
+			if tween.is_done() {
+				self.tween = None;
+			}
+		}
+		if let Some(shake) = self.shake.as_mut() {
+			shake.elapsed += dt;
+			if shake.elapsed >= shake.duration {
+				self.shake = None;
+			}
+		}
+
+		self.apply_offset();
+	}
+
+	/// Frame pacing statistics.
+	pub fn pacer(&self) -> &FramePacer { &self.pacer }
+	pub fn pacer_mut(&mut self) -> &mut FramePacer { &mut self.pacer }
+
+	/// Smoothed actual frame rate.
+	pub fn actual_frame_rate(&self) -> c_float { self.pacer.frame_rate() }
+
+	/// Target frame rate, same as [`refresh_rate`](DisplayState::refresh_rate).
+	pub fn target_frame_rate(&self) -> c_float { self.current.refresh_rate }
+
+
+	fn apply_offset(&mut self) {
+		let (mut x, mut y) = match self.tween {
+			Some(tween) => tween.value(),
+			None => (self.current.offset.0 as c_float, self.current.offset.1 as c_float),
+		};
+
+		if let Some(shake) = self.shake {
+			let power = shake.power();
+			x += self.random() * power;
+			y += self.random() * power;
+		}
+
+		let offset = (libm::roundf(x) as c_int, libm::roundf(y) as c_int);
+		if offset != self.applied_offset {
+			self.applied_offset = offset;
+			self.display.set_offset(offset.0, offset.1);
+		}
+	}
+
+	/// Returns pseudo-random value in `-1..1`.
+	fn random(&mut self) -> c_float {
+		// xorshift32
+		self.seed ^= self.seed << 13;
+		self.seed ^= self.seed >> 17;
+		self.seed ^= self.seed << 5;
+		(self.seed as c_float / u32::MAX as c_float) * 2.0 - 1.0
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Display end-point ignoring all calls.
+	struct Fake;
+
+	impl api::Api for Fake {
+		fn set_offset(&self) -> unsafe extern "C" fn(x: c_int, y: c_int) {
+			unsafe extern "C" fn set_offset(_: c_int, _: c_int) {}
+			set_offset
+		}
+	}
+
+	fn state() -> DisplayState<Fake> { DisplayState::new_with(Display::new_with(Fake), DisplaySettings::DEFAULT) }
+
+	#[test]
+	fn pacer_rate() {
+		let mut pacer = FramePacer::new();
+		pacer.set_smoothing(1.0);
+		pacer.tick(0.0, 50.0);
+		assert_eq!(0, pacer.frames());
+		assert_eq!(0.0, pacer.frame_rate());
+
+		pacer.tick(0.02, 50.0);
+		assert_eq!(50.0, pacer.frame_rate());
+		assert_eq!(0, pacer.late_frames());
+
+		pacer.tick(0.04, 50.0);
+		assert_eq!(25.0, pacer.frame_rate());
+		assert_eq!(1, pacer.late_frames());
+
+		pacer.set_smoothing(0.5);
+		pacer.tick(0.02, 50.0);
+		assert_eq!(0.03, pacer.frame_time());
+		assert_eq!(3, pacer.frames());
+	}
+
+	#[test]
+	fn shake_decay() {
+		let mut state = state();
+		state.shake(8.0, 1.0);
+		state.update(0.25);
+		assert_eq!(6.0, state.shake.unwrap().power());
+
+		state.shake(4.0, 1.0);
+		assert_eq!(8.0, state.shake.unwrap().magnitude);
+		state.shake(7.0, 1.0);
+		assert_eq!(7.0, state.shake.unwrap().magnitude);
+
+		state.update(1.0);
+		assert!(state.shake.is_none());
+		assert_eq!((0, 0), state.applied_offset);
+	}
+
+	#[test]
+	fn shake_without_duration() {
+		let mut state = state();
+		state.shake(8.0, 0.0);
+		assert_eq!(0.0, state.shake.unwrap().power());
+		state.shake(2.0, 1.0);
+		assert_eq!(2.0, state.shake.unwrap().magnitude);
+	}
+
+	#[test]
+	fn offset_tween() {
+		let mut state = state();
+		state.animate_offset(10, -4, 1.0);
+		state.update(0.5);
+		assert_eq!((5, -2), state.applied_offset);
+		state.update(0.5);
+		assert!(!state.is_animating());
+		assert_eq!((10, -4), state.applied_offset);
+	}
+}
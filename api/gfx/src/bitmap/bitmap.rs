@@ -538,8 +538,8 @@ pub struct BitmapData<'bitmap> {
 	pub width: c_int,
 	pub height: c_int,
 	pub row_bytes: c_int,
-	mask: Option<&'bitmap mut [u8]>,
-	data: &'bitmap mut [u8],
+	pub(crate) mask: Option<&'bitmap mut [u8]>,
+	pub(crate) data: &'bitmap mut [u8],
 }
 
 impl<'bitmap> BitmapData<'bitmap> {
@@ -0,0 +1,289 @@
+//! Slicing sprite-sheets into frames at runtime.
+//!
+//! Cells are described by a regular [`Grid`] or by a list of [`Cell`]s,
+//! e.g. parsed from Aseprite or TexturePacker data files.
+//! Pixels and masks are copied by drawing the sheet into new bitmaps.
+
+use alloc::vec::Vec;
+use core::ffi::c_int;
+
+use crate::Graphics;
+use crate::error::Error;
+use super::api;
+use super::AnyBitmap;
+use super::Bitmap;
+use super::api::Api as BitmapApi;
+use super::BitmapDrawMode;
+use super::BitmapDrawModeExt;
+use super::BitmapFlip;
+use super::BitmapFlipExt;
+use super::Color;
+
+
+/// Regular grid of equally sized cells, read left to right, top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grid {
+	pub width: c_int,
+	pub height: c_int,
+	/// Empty border around the whole sheet.
+	pub margin: c_int,
+	/// Empty space between cells.
+	pub spacing: c_int,
+	/// Maximum number of cells, e.g. when the last row is not full.
+	pub count: Option<c_int>,
+}
+
+impl Grid {
+	pub const fn new(width: c_int, height: c_int) -> Self {
+		Self { width,
+		       height,
+		       margin: 0,
+		       spacing: 0,
+		       count: None }
+	}
+
+	pub const fn with_margin(mut self, margin: c_int) -> Self {
+		self.margin = margin;
+		self
+	}
+
+	pub const fn with_spacing(mut self, spacing: c_int) -> Self {
+		self.spacing = spacing;
+		self
+	}
+
+	pub const fn with_count(mut self, count: c_int) -> Self {
+		self.count = Some(count);
+		self
+	}
+
+	/// Returns number of `(columns, rows)` that fit into a sheet of the given size.
+	pub fn dimensions(&self, sheet_width: c_int, sheet_height: c_int) -> (c_int, c_int) {
+		let fit = |size: c_int, cell: c_int| {
+			if cell <= 0 {
+				0
+			} else {
+				((size - self.margin * 2 + self.spacing) / (cell + self.spacing)).max(0)
+			}
+		};
+		(fit(sheet_width, self.width), fit(sheet_height, self.height))
+	}
+
+	/// Returns cells of a sheet of the given size.
+	pub fn cells(&self, sheet_width: c_int, sheet_height: c_int) -> impl Iterator<Item = Cell> + '_ {
+		let (columns, rows) = self.dimensions(sheet_width, sheet_height);
+		let total = columns * rows;
+		let count = self.count.map_or(total, |count| count.min(total));
+		(0..count).map(move |i| {
+			          let x = self.margin + (i % columns) * (self.width + self.spacing);
+			          let y = self.margin + (i / columns) * (self.height + self.spacing);
+			          Cell::new(x, y, self.width, self.height)
+		          })
+	}
+}
+
+
+/// Trimming metadata of a cell.
+///
+/// Describes where the trimmed cell is placed inside the original untrimmed frame,
+/// so `x, y` are the offsets to apply when drawing the trimmed image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Trim {
+	pub x: c_int,
+	pub y: c_int,
+	/// Width of the untrimmed frame.
+	pub width: c_int,
+	/// Height of the untrimmed frame.
+	pub height: c_int,
+}
+
+
+/// Rectangle of the sheet with optional trimming metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+	pub x: c_int,
+	pub y: c_int,
+	pub width: c_int,
+	pub height: c_int,
+	pub trim: Option<Trim>,
+}
+
+impl Cell {
+	pub const fn new(x: c_int, y: c_int, width: c_int, height: c_int) -> Self {
+		Self { x,
+		       y,
+		       width,
+		       height,
+		       trim: None }
+	}
+
+	pub const fn with_trim(mut self, trim: Trim) -> Self {
+		self.trim = Some(trim);
+		self
+	}
+
+	/// Returns `(width, height)` of the untrimmed frame.
+	pub const fn frame_size(&self) -> (c_int, c_int) {
+		match self.trim {
+			Some(trim) => (trim.width, trim.height),
+			None => (self.width, self.height),
+		}
+	}
+
+	/// Returns offset of the cell inside the untrimmed frame.
+	pub const fn offset(&self) -> (c_int, c_int) {
+		match self.trim {
+			Some(trim) => (trim.x, trim.y),
+			None => (0, 0),
+		}
+	}
+}
+
+
+/// Bitmap cut out of a sheet with its trimming metadata kept alongside.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Frame<Api: api::Api = api::Default> {
+	pub bitmap: Bitmap<Api, true>,
+	pub trim: Option<Trim>,
+}
+
+impl<Api: api::Api> Frame<Api> {
+	/// Returns `(width, height)` of the untrimmed frame.
+	pub fn size(&self) -> (c_int, c_int) {
+		match self.trim {
+			Some(trim) => (trim.width, trim.height),
+			None => self.bitmap.size(),
+		}
+	}
+
+	/// Draws the frame as if it was untrimmed, with its upper-left corner at `x, y`.
+	///
+	/// Flipping takes the trimmed area into account.
+	pub fn draw(&self, x: c_int, y: c_int, flip: BitmapFlip) {
+		let (dx, dy) = match self.trim {
+			Some(trim) => {
+				let (width, height) = self.bitmap.size();
+				let flip_x = matches!(flip, BitmapFlip::kBitmapFlippedX | BitmapFlip::kBitmapFlippedXY);
+				let flip_y = matches!(flip, BitmapFlip::kBitmapFlippedY | BitmapFlip::kBitmapFlippedXY);
+				let dx = if flip_x {
+					trim.width - width - trim.x
+				} else {
+					trim.x
+				};
+				let dy = if flip_y {
+					trim.height - height - trim.y
+				} else {
+					trim.y
+				};
+				(dx, dy)
+			},
+			None => (0, 0),
+		};
+		self.bitmap.draw(x + dx, y + dy, flip)
+	}
+}
+
+
+/// Returns `(width, height)` of any bitmap.
+pub(crate) fn size_of(bitmap: &impl AnyBitmap) -> (c_int, c_int) {
+	let (mut width, mut height, mut row_bytes) = (0, 0, 0);
+	let f = bitmap.api_ref().get_bitmap_data();
+	unsafe {
+		f(
+		  bitmap.as_raw(),
+		  &mut width,
+		  &mut height,
+		  &mut row_bytes,
+		  core::ptr::null_mut(),
+		  core::ptr::null_mut(),
+		)
+	};
+	(width, height)
+}
+
+/// Draws the `cell` of the `sheet` into `target` at `x, y` without touching anything outside of the cell,
+/// using the given `gfx` end-point.
+pub(crate) fn blit<GApi: crate::api::Api>(gfx: &Graphics<GApi>,
+                                          target: &impl AnyBitmap,
+                                          sheet: &impl AnyBitmap,
+                                          cell: &Cell,
+                                          x: c_int,
+                                          y: c_int) {
+	gfx.push_context(target);
+	let _ = gfx.set_draw_mode(BitmapDrawMode::Copy);
+	gfx.set_clip_rect(x, y, cell.width, cell.height);
+	gfx.draw(sheet, x - cell.x, y - cell.y, BitmapFlip::Unflipped);
+	gfx.pop_context();
+}
+
+
+impl<Api: api::Api, const FOD: bool> Bitmap<Api, FOD> {
+	/// Copies the `cell` of this bitmap into a new bitmap of the cell size,
+	/// including the mask.
+	///
+	/// Clones inner api-access, also used as the graphics end-point for copying.
+	#[inline(always)]
+	pub fn slice(&self, cell: &Cell) -> Result<Bitmap<Api, true>, Error>
+		where Api: crate::api::Api + Clone {
+		self.slice_with(&Graphics::new_with(self.1.clone()), self.1.clone(), cell)
+	}
+
+	/// Copies the `cell` of this bitmap into a new bitmap of the cell size,
+	/// including the mask, drawing with the given `gfx` end-point.
+	///
+	/// Produced `Bitmap` uses passed `api` api-access.
+	pub fn slice_with<GApi: crate::api::Api, NewApi: api::Api>(&self,
+	                                                           gfx: &Graphics<GApi>,
+	                                                           api: NewApi,
+	                                                           cell: &Cell)
+	                                                           -> Result<Bitmap<NewApi, true>, Error> {
+		let bitmap = Bitmap::new_with(api, cell.width, cell.height, Color::CLEAR)?;
+		blit(gfx, &bitmap, self, cell, 0, 0);
+		Ok(bitmap)
+	}
+
+	/// Cuts this bitmap into frames by the given `grid`.
+	///
+	/// Clones inner api-access.
+	pub fn slice_grid(&self, grid: &Grid) -> Result<Vec<Frame<Api>>, Error>
+		where Api: crate::api::Api + Clone {
+		let (width, height) = self.size();
+		grid.cells(width, height)
+		    .map(|cell| self.slice(&cell).map(|bitmap| Frame { bitmap, trim: None }))
+		    .collect()
+	}
+
+	/// Cuts this bitmap into frames by the given `cells`,
+	/// keeping their trimming metadata alongside.
+	///
+	/// Clones inner api-access.
+	pub fn slice_cells(&self, cells: &[Cell]) -> Result<Vec<Frame<Api>>, Error>
+		where Api: crate::api::Api + Clone {
+		cells.iter()
+		     .map(|cell| {
+			     self.slice(cell).map(|bitmap| {
+				                     Frame { bitmap,
+				                             trim: cell.trim }
+			                     })
+		     })
+		     .collect()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn grid_cells() {
+		let grid = Grid::new(16, 8).with_margin(1).with_spacing(2);
+		assert_eq!((3, 2), grid.dimensions(56, 20));
+
+		let cells: Vec<_> = grid.with_count(4).cells(56, 20).collect();
+		assert_eq!(4, cells.len());
+		assert_eq!(Cell::new(1, 1, 16, 8), cells[0]);
+		assert_eq!(Cell::new(37, 1, 16, 8), cells[2]);
+		assert_eq!(Cell::new(1, 11, 16, 8), cells[3]);
+	}
+}
@@ -1,6 +1,7 @@
 //! Playdate bitmap-table API
 
 use alloc::boxed::Box;
+use alloc::vec::Vec;
 use core::ffi::c_char;
 use core::ffi::c_int;
 
@@ -10,8 +11,16 @@ use fs::Path;
 
 use crate::error::ApiError;
 use crate::error::Error;
+use crate::Graphics;
+use super::AnyBitmap;
 use super::Bitmap;
+use super::BitmapRef;
+use super::Color;
 use super::api::Api as BitmapApi;
+use super::sheet::blit;
+use super::sheet::size_of;
+use super::sheet::Cell;
+use super::sheet::Grid;
 
 
 #[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
@@ -94,6 +103,81 @@ impl<Api: api::Api> BitmapTable<Api, true> {
 	}
 }
 
+impl<Api: api::Api> BitmapTable<Api, true> {
+	/// Creates a new [`BitmapTable`] from the `sheet` cut by the given `grid`.
+	///
+	/// Pixels and masks are copied, so the `sheet` can be dropped afterwards.
+	///
+	/// Uses default ZST graphics end-point.
+	pub fn from_grid(sheet: &impl AnyBitmap, grid: &Grid) -> Result<Self, Error>
+		where Api: Default {
+		let api = Api::default();
+		Self::from_grid_with(&Graphics::Default(), api, sheet, grid)
+	}
+
+	/// Creates a new [`BitmapTable`] from the `sheet` cut by the given `grid`,
+	/// using the given `api` and `gfx` end-point.
+	///
+	/// Pixels and masks are copied, so the `sheet` can be dropped afterwards.
+	pub fn from_grid_with<GApi: crate::api::Api>(gfx: &Graphics<GApi>,
+	                                             api: Api,
+	                                             sheet: &impl AnyBitmap,
+	                                             grid: &Grid)
+	                                             -> Result<Self, Error> {
+		let (width, height) = size_of(sheet);
+		let cells: Vec<Cell> = grid.cells(width, height).collect();
+		Self::from_cells_with(gfx, api, sheet, &cells)
+	}
+
+	/// Creates a new [`BitmapTable`] from the `sheet` cut by the given `cells`.
+	///
+	/// All bitmaps in a table have the same size,
+	/// so every cell is placed into the largest untrimmed frame at its trim offset
+	/// and the rest of the frame stays transparent.
+	///
+	/// Uses default ZST graphics end-point.
+	pub fn from_cells(sheet: &impl AnyBitmap, cells: &[Cell]) -> Result<Self, Error>
+		where Api: Default {
+		let api = Api::default();
+		Self::from_cells_with(&Graphics::Default(), api, sheet, cells)
+	}
+
+	/// Creates a new [`BitmapTable`] from the `sheet` cut by the given `cells`,
+	/// using the given `api` and `gfx` end-point.
+	///
+	/// See [`BitmapTable::from_cells`].
+	pub fn from_cells_with<GApi: crate::api::Api>(gfx: &Graphics<GApi>,
+	                                              api: Api,
+	                                              sheet: &impl AnyBitmap,
+	                                              cells: &[Cell])
+	                                              -> Result<Self, Error> {
+		let (width, height) = cells.iter()
+		                           .map(Cell::frame_size)
+		                           .fold((0, 0), |(w, h), (cw, ch)| (w.max(cw), h.max(ch)));
+		let table = Self::new_with(api, cells.len() as c_int, width, height)?;
+
+		for (i, cell) in cells.iter().enumerate() {
+			let f = table.1.get_table_bitmap();
+			let ptr = unsafe { f(table.0, i as c_int) };
+			if ptr.is_null() {
+				return Err(Error::Alloc);
+			}
+			let target = Bitmap::<crate::api::Default, false>::from(ptr);
+
+			// render the untrimmed frame with transparent padding:
+			let frame = Bitmap::<crate::api::Default, true>::new(width, height, Color::CLEAR)?;
+			let (x, y) = cell.offset();
+			blit(gfx, &frame, sheet, cell, x, y);
+			blit(gfx, &target, &frame, &Cell::new(0, 0, width, height), 0, 0);
+
+			let mut mask = frame.mask().ok_or(Error::InvalidMask)?;
+			target.set_mask(&mut mask)?;
+		}
+
+		Ok(table)
+	}
+}
+
 impl<Api: api::Api, const FOD: bool> BitmapTable<Api, FOD> {
 	/// Loads the image-table at `path` into the previously allocated this table.
 	///
@@ -151,6 +235,21 @@ impl<Api: api::Api, const FOD: bool> BitmapTable<Api, FOD> {
 		}
 	}
 
+	/// Returns the `index` bitmap in this table borrowed from it,
+	/// if `index` is out of bounds, the function returns `None`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_graphics::getTableBitmap`].
+	#[doc(alias = "sys::ffi::playdate_graphics::getTableBitmap")]
+	pub fn get_ref(&self, index: c_int) -> Option<BitmapRef<'_>> {
+		let f = self.1.get_table_bitmap();
+		let ptr = unsafe { f(self.0, index) };
+		if ptr.is_null() {
+			None
+		} else {
+			Some(BitmapRef::from(ptr))
+		}
+	}
+
 	/// Returns the bitmap table’s image count in the `count` if not `None`
 	/// and number of cells across in the `width` (ditto) if not `None` .
 	///
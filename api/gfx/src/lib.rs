@@ -11,9 +11,11 @@ pub mod bitmap {
 	mod bitmap;
 	pub mod api;
 	pub mod table;
+	pub mod sheet;
 	pub use bitmap::*;
 }
 pub mod video;
+pub mod mode7;
 pub mod api;
 
 use core::ffi::c_float;
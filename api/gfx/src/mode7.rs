@@ -0,0 +1,508 @@
+//! Mode7-style floor rasterizer.
+//!
+//! Samples a source [`Bitmap`] through an affine or per-scanline perspective transform
+//! and writes the result directly into the frame buffer, whole bytes at a time.
+
+use core::ffi::c_int;
+use core::ops::Range;
+
+use sys::ffi::LCDSolidColor;
+use sys::ffi::LCD_COLUMNS;
+use sys::ffi::LCD_ROWS;
+use sys::ffi::LCD_ROWSIZE;
+
+use crate::Graphics;
+use crate::bitmap::Bitmap;
+use crate::bitmap::BitmapData;
+use crate::bitmap::api as bitmap_api;
+use crate::error::ApiError;
+
+
+/// Fixed-point precision used by the inner loop.
+const FRAC_BITS: u32 = 16;
+
+/// Ordered dither matrix used for fog.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+
+/// What to sample outside of the source bitmap bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+	/// Tile the source infinitely.
+	Repeat,
+	/// Stretch the edge pixels of the source.
+	Clamp,
+	/// Fill with the given color.
+	///
+	/// [`kColorClear`][LCDSolidColor::kColorClear] and [`kColorXOR`][LCDSolidColor::kColorXOR]
+	/// leave the destination untouched.
+	Fill(LCDSolidColor),
+}
+
+
+/// Dithered distance fog.
+///
+/// Density grows linearly from `0` at `start` to fully fogged at `end`, in source pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+	pub start: f32,
+	pub end: f32,
+	/// [`kColorBlack`][LCDSolidColor::kColorBlack] or [`kColorWhite`][LCDSolidColor::kColorWhite].
+	pub color: LCDSolidColor,
+}
+
+impl Fog {
+	pub const fn new(start: f32, end: f32, color: LCDSolidColor) -> Self { Self { start, end, color } }
+
+	/// Returns fog density for the given `distance`, in `0.0..=1.0`.
+	pub fn density(&self, distance: f32) -> f32 {
+		if distance <= self.start {
+			0.0
+		} else if distance >= self.end || self.end <= self.start {
+			1.0
+		} else {
+			(distance - self.start) / (self.end - self.start)
+		}
+	}
+}
+
+
+/// Camera looking at the floor plane.
+///
+/// Position and `height` are in source pixels,
+/// `angle` is in radians where `0` looks along the source's `+x` axis and grows clockwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+	pub x: f32,
+	pub y: f32,
+	/// Altitude above the plane.
+	pub height: f32,
+	pub angle: f32,
+	/// Distance from the eye to the projection plane in screen pixels,
+	/// e.g. half of the screen width gives 90° of horizontal field of view.
+	pub focal: f32,
+}
+
+impl Camera {
+	pub const fn new(x: f32, y: f32, height: f32, angle: f32, focal: f32) -> Self {
+		Self { x,
+		       y,
+		       height,
+		       angle,
+		       focal }
+	}
+
+	/// Returns unit vector of the view direction.
+	pub fn direction(&self) -> (f32, f32) { (libm::cosf(self.angle), libm::sinf(self.angle)) }
+
+	/// Moves the camera by `forward` along the view direction and by `right` to the side.
+	pub fn advance(&mut self, forward: f32, right: f32) {
+		let (cos, sin) = self.direction();
+		self.x += cos * forward - sin * right;
+		self.y += sin * forward + cos * right;
+	}
+}
+
+
+/// Affine screen-to-source transform.
+///
+/// Screen point `(origin_x, origin_y)` maps to source point `(u, v)`,
+/// moving one pixel right on the screen moves `(a, c)` in the source,
+/// moving one pixel down moves `(b, d)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+	pub a: f32,
+	pub b: f32,
+	pub c: f32,
+	pub d: f32,
+	pub u: f32,
+	pub v: f32,
+	pub origin_x: f32,
+	pub origin_y: f32,
+}
+
+impl Affine {
+	/// Transform showing source point `(u, v)` at screen point `(origin_x, origin_y)`,
+	/// rotated by `angle` radians and magnified by `zoom`.
+	pub fn new(u: f32, v: f32, angle: f32, zoom: f32, origin_x: f32, origin_y: f32) -> Self {
+		let cos = libm::cosf(angle) / zoom;
+		let sin = libm::sinf(angle) / zoom;
+		Self { a: cos,
+		       b: -sin,
+		       c: sin,
+		       d: cos,
+		       u,
+		       v,
+		       origin_x,
+		       origin_y }
+	}
+}
+
+
+/// How screen rows are mapped onto the source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+	/// Same transform for every row, no depth so fog is not applied.
+	Affine(Affine),
+	/// Perspective floor seen from the camera, recomputed for every row.
+	Perspective(Camera),
+}
+
+
+/// Source walk for a single screen row, in source pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scanline {
+	/// Source point of the left-most pixel of the row.
+	pub u: f32,
+	pub v: f32,
+	/// Source step per screen pixel.
+	pub du: f32,
+	pub dv: f32,
+	/// Fog density, `0.0` means no fog.
+	pub fog: f32,
+}
+
+
+/// Mode7-style floor renderer.
+///
+/// Rows above the `horizon` are left untouched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mode7 {
+	pub projection: Projection,
+	/// First screen row of the plane.
+	pub horizon: c_int,
+	pub wrap: Wrap,
+	pub fog: Option<Fog>,
+}
+
+impl Mode7 {
+	pub const fn new(projection: Projection, horizon: c_int) -> Self {
+		Self { projection,
+		       horizon,
+		       wrap: Wrap::Repeat,
+		       fog: None }
+	}
+
+	pub const fn affine(transform: Affine) -> Self { Self::new(Projection::Affine(transform), 0) }
+
+	pub const fn perspective(camera: Camera, horizon: c_int) -> Self {
+		Self::new(Projection::Perspective(camera), horizon)
+	}
+
+	pub const fn with_wrap(mut self, wrap: Wrap) -> Self {
+		self.wrap = wrap;
+		self
+	}
+
+	pub const fn with_fog(mut self, fog: Fog) -> Self {
+		self.fog = Some(fog);
+		self
+	}
+
+
+	/// Computes the source walk for screen row `y` of a `width` pixels wide target.
+	///
+	/// Returns `None` for rows above the horizon.
+	pub fn scanline(&self, y: c_int, width: c_int) -> Option<Scanline> {
+		if y < self.horizon {
+			return None;
+		}
+
+		match &self.projection {
+			Projection::Affine(m) => {
+				let dx = -m.origin_x;
+				let dy = y as f32 - m.origin_y;
+				Some(Scanline { u: m.u + m.a * dx + m.b * dy,
+				                v: m.v + m.c * dx + m.d * dy,
+				                du: m.a,
+				                dv: m.c,
+				                fog: 0.0 })
+			},
+			Projection::Perspective(cam) => {
+				// sample the pixel center so the horizon row itself stays finite:
+				let p = (y - self.horizon) as f32 + 0.5;
+				let distance = cam.height * cam.focal / p;
+				let scale = distance / cam.focal;
+				let (cos, sin) = cam.direction();
+				let half = width as f32 * 0.5;
+				Some(Scanline { u: cam.x + cos * distance + sin * half * scale,
+				                v: cam.y + sin * distance - cos * half * scale,
+				                du: -sin * scale,
+				                dv: cos * scale,
+				                fog: self.fog.map_or(0.0, |fog| fog.density(distance)) })
+			},
+		}
+	}
+
+
+	/// Projects source point `x, y` onto the screen for a `width` pixels wide target.
+	///
+	/// Returns screen `(x, y)` and the scale at that depth,
+	/// useful to place sprites on the floor.
+	/// Returns `None` for points behind the camera and for the affine projection.
+	pub fn project(&self, x: f32, y: f32, width: c_int) -> Option<(f32, f32, f32)> {
+		let Projection::Perspective(cam) = &self.projection else {
+			return None;
+		};
+		let (cos, sin) = cam.direction();
+		let (dx, dy) = (x - cam.x, y - cam.y);
+		let depth = dx * cos + dy * sin;
+		if depth <= 0.0 {
+			return None;
+		}
+		let side = dy * cos - dx * sin;
+		let scale = cam.focal / depth;
+		let sx = width as f32 * 0.5 + side * scale;
+		let sy = self.horizon as f32 + cam.height * scale - 0.5;
+		Some((sx, sy, scale))
+	}
+
+
+	/// Renders the plane into the frame buffer and marks updated rows.
+	///
+	/// Uses default ZST graphics end-point.
+	pub fn draw<Api: bitmap_api::Api, const FOD: bool>(&self, src: &mut Bitmap<Api, FOD>) -> Result<(), ApiError> {
+		self.draw_with(&Graphics::Default(), src)
+	}
+
+	/// Renders the plane into the frame buffer and marks updated rows,
+	/// using the given `gfx` end-point.
+	pub fn draw_with<GApi: crate::api::Api, Api: bitmap_api::Api, const FOD: bool>(&self,
+	                                                                               gfx: &Graphics<GApi>,
+	                                                                               src: &mut Bitmap<Api, FOD>)
+	                                                                               -> Result<(), ApiError> {
+		let frame = gfx.get_frame()?;
+		let src = src.bitmap_data();
+		let rows = self.render(
+		                       &src,
+		                       frame,
+		                       None,
+		                       LCD_ROWSIZE as _,
+		                       LCD_COLUMNS as _,
+		                       LCD_ROWS as _,
+		);
+		if !rows.is_empty() {
+			gfx.mark_updated_rows(rows.start as _, rows.end as c_int - 1);
+		}
+		Ok(())
+	}
+
+	/// Renders the plane into the `target` bitmap.
+	///
+	/// If the target has a mask, written pixels become opaque.
+	pub fn draw_into(&self, src: &BitmapData, target: &mut BitmapData) {
+		let (stride, width, height) = (target.row_bytes as _, target.width as _, target.height as _);
+		self.render(
+		            src,
+		            target.data,
+		            target.mask.as_deref_mut(),
+		            stride,
+		            width,
+		            height,
+		);
+	}
+
+	/// Renders the plane into the raw 1-bit MSB-ordered buffer `dst`.
+	///
+	/// If `mask` is given, written pixels are set in it.
+	/// Returns range of rows that were touched.
+	pub fn render(&self,
+	              src: &BitmapData,
+	              dst: &mut [u8],
+	              mut mask: Option<&mut [u8]>,
+	              stride: usize,
+	              width: usize,
+	              height: usize)
+	              -> Range<usize> {
+		let start = self.horizon.clamp(0, height as c_int) as usize;
+		if src.width <= 0 || src.height <= 0 {
+			return start..start;
+		}
+
+		let fog_color = self.fog.map(|fog| fog.color == LCDSolidColor::kColorWhite);
+		let sampler = Sampler::new(src, self.wrap);
+
+		for y in start..height {
+			let Some(line) = self.scanline(y as _, width as _) else {
+				continue;
+			};
+
+			let fog = match fog_color {
+				Some(_) if !matches!(self.projection, Projection::Affine(_)) => fog_pattern(y, line.fog),
+				_ => 0,
+			};
+
+			let mut u = to_fixed(line.u);
+			let mut v = to_fixed(line.v);
+			let du = to_fixed(line.du);
+			let dv = to_fixed(line.dv);
+
+			let row = &mut dst[y * stride..];
+			let mut x = 0;
+			for (byte, out) in row.iter_mut().enumerate().take(width.div_ceil(8)) {
+				let mut bits = 0u8;
+				let mut opaque = 0u8;
+				let count = (width - x).min(8);
+				for i in 0..count {
+					if let Some(white) = sampler.texel((u >> FRAC_BITS) as i32, (v >> FRAC_BITS) as i32) {
+						let bit = 0x80 >> i;
+						opaque |= bit;
+						if white {
+							bits |= bit;
+						}
+					}
+					u += du;
+					v += dv;
+				}
+				x += count;
+
+				let mut value = (*out & !opaque) | (bits & opaque);
+				let fogged = fog & opaque;
+				match fog_color {
+					Some(true) => value |= fogged,
+					Some(false) => value &= !fogged,
+					None => {},
+				}
+				*out = value;
+
+				if let Some(mask) = mask.as_deref_mut() {
+					mask[y * stride + byte] |= opaque;
+				}
+			}
+		}
+
+		start..height
+	}
+}
+
+
+struct Sampler<'a> {
+	data: &'a [u8],
+	mask: Option<&'a [u8]>,
+	row_bytes: usize,
+	width: i32,
+	height: i32,
+	wrap: Wrap,
+}
+
+impl<'a> Sampler<'a> {
+	fn new(src: &'a BitmapData, wrap: Wrap) -> Self {
+		Self { data: src.data(),
+		       mask: src.mask(),
+		       row_bytes: src.row_bytes as _,
+		       width: src.width,
+		       height: src.height,
+		       wrap }
+	}
+
+	/// Returns `Some(white)` for opaque texels, `None` for transparent ones.
+	#[inline(always)]
+	fn texel(&self, x: i32, y: i32) -> Option<bool> {
+		let (x, y) = match self.wrap {
+			Wrap::Repeat => (wrap(x, self.width), wrap(y, self.height)),
+			Wrap::Clamp => (x.clamp(0, self.width - 1), y.clamp(0, self.height - 1)),
+			Wrap::Fill(color) => {
+				if x < 0 || y < 0 || x >= self.width || y >= self.height {
+					return match color {
+						LCDSolidColor::kColorWhite => Some(true),
+						LCDSolidColor::kColorBlack => Some(false),
+						_ => None,
+					};
+				}
+				(x, y)
+			},
+		};
+
+		let index = y as usize * self.row_bytes + (x as usize >> 3);
+		let bit = 0x80 >> (x & 7);
+		if let Some(mask) = self.mask {
+			if mask[index] & bit == 0 {
+				return None;
+			}
+		}
+		Some(self.data[index] & bit != 0)
+	}
+}
+
+
+#[inline(always)]
+fn wrap(value: i32, size: i32) -> i32 {
+	if size & (size - 1) == 0 {
+		value & (size - 1)
+	} else {
+		value.rem_euclid(size)
+	}
+}
+
+#[inline(always)]
+fn to_fixed(value: f32) -> i64 { (value * (1 << FRAC_BITS) as f32) as i64 }
+
+/// Returns dithered fog byte for screen row `y` at the given `density`.
+fn fog_pattern(y: usize, density: f32) -> u8 {
+	let level = (density.clamp(0.0, 1.0) * 16.0 + 0.5) as u8;
+	let row = &BAYER_4X4[y & 3];
+	let nibble = row.iter()
+	                .enumerate()
+	                .fold(0u8, |acc, (i, t)| if *t < level { acc | (0x8 >> i) } else { acc });
+	nibble << 4 | nibble
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// 16x2 source: checker of bytes, left half white on the first row.
+	fn source(data: &mut [u8]) -> BitmapData<'_> {
+		BitmapData { width: 16,
+		             height: 2,
+		             row_bytes: 2,
+		             mask: None,
+		             data }
+	}
+
+	#[test]
+	fn affine_identity_copies_source() {
+		let mut data = [0xFF, 0x00, 0x0F, 0xF0];
+		let src = source(&mut data);
+		let m7 = Mode7::affine(Affine::new(0.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+
+		let mut dst = [0u8; 4];
+		let rows = m7.render(&src, &mut dst, None, 2, 16, 2);
+		assert_eq!(0..2, rows);
+		assert_eq!([0xFF, 0x00, 0x0F, 0xF0], dst);
+	}
+
+	#[test]
+	fn horizon_keeps_rows_above() {
+		let mut data = [0xFF; 4];
+		let src = source(&mut data);
+		let m7 = Mode7::affine(Affine::new(0.0, 0.0, 0.0, 1.0, 0.0, 0.0)).with_wrap(Wrap::Repeat);
+		let m7 = Mode7 { horizon: 1, ..m7 };
+
+		let mut dst = [0u8; 4];
+		let rows = m7.render(&src, &mut dst, None, 2, 16, 2);
+		assert_eq!(1..2, rows);
+		assert_eq!([0x00, 0x00, 0xFF, 0xFF], dst);
+	}
+
+	#[test]
+	fn fill_clear_is_transparent() {
+		let mut data = [0x00; 4];
+		let src = source(&mut data);
+		let m7 = Mode7::affine(Affine::new(-8.0, 0.0, 0.0, 1.0, 0.0, 0.0)).with_wrap(Wrap::Fill(LCDSolidColor::kColorClear));
+
+		let mut dst = [0xFFu8; 4];
+		let mut mask = [0u8; 4];
+		m7.render(&src, &mut dst, Some(&mut mask), 2, 16, 2);
+		assert_eq!([0xFF, 0x00, 0xFF, 0x00], dst);
+		assert_eq!([0x00, 0xFF, 0x00, 0xFF], mask);
+	}
+
+	#[test]
+	fn fog_density_levels() {
+		assert_eq!(0x00, fog_pattern(0, 0.0));
+		assert_eq!(0xFF, fog_pattern(0, 1.0));
+		assert_eq!(4, fog_pattern(1, 0.5).count_ones());
+	}
+}
@@ -11,6 +11,8 @@ use crate::bitmap::{AnyBitmap, BitmapRef};
 use crate::error::ApiError;
 use crate::error::Error;
 
+pub mod playback;
+
 
 #[derive(Debug, Clone, Copy)]
 pub struct Video<Api: api::Api = api::Default>(Api);
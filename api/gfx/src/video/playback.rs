@@ -0,0 +1,438 @@
+//! Video playback controller.
+//!
+//! Drives frame selection of a [`VideoPlayer`] from elapsed time,
+//! optionally synchronised to a companion [`AudioTrack`].
+
+use core::ffi::c_float;
+use core::ffi::c_int;
+use alloc::boxed::Box;
+
+use super::api;
+use super::VideoPlayer;
+use crate::error::Error;
+
+
+/// Audio played alongside the video and used as the playback clock while it is playing.
+///
+/// Implemented for [`sound::player::fp::Player`] with the `sound` feature.
+pub trait AudioTrack {
+	/// Starts playing from the current offset.
+	fn play(&mut self);
+	fn stop(&mut self);
+	fn is_playing(&self) -> bool;
+	/// Current position in seconds.
+	fn offset(&self) -> c_float;
+	fn set_offset(&mut self, offset: c_float);
+}
+
+
+/// Placeholder for playback without audio, can not be constructed.
+pub enum NoAudio {}
+
+impl AudioTrack for NoAudio {
+	fn play(&mut self) { match *self {} }
+	fn stop(&mut self) { match *self {} }
+	fn is_playing(&self) -> bool { match *self {} }
+	fn offset(&self) -> c_float { match *self {} }
+	fn set_offset(&mut self, _: c_float) { match *self {} }
+}
+
+
+#[cfg(feature = "sound")]
+impl<Api: sound::player::fp::api::Api> AudioTrack for sound::player::fp::Player<Api> {
+	fn play(&mut self) { sound::player::fp::Player::play(self, sound::player::Repeat::Loops(1)); }
+	fn stop(&mut self) { sound::player::fp::Player::stop(self) }
+	fn is_playing(&self) -> bool { sound::player::fp::Player::is_playing(self) }
+	fn offset(&self) -> c_float { sound::player::fp::Player::offset(self) }
+	fn set_offset(&mut self, offset: c_float) { sound::player::fp::Player::set_offset(self, offset) }
+}
+
+
+/// State of the [`VideoPlayback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackState {
+	Stopped,
+	Playing,
+	Paused,
+	/// Reached the end of a non-looping video.
+	Finished,
+}
+
+
+/// Plays a [`VideoPlayer`] in real time.
+///
+/// Call [`update`](VideoPlayback::update) once per frame with the time passed since the previous call.
+/// While the audio track is playing, its offset is the source of time so video never drifts from sound.
+pub struct VideoPlayback<Api: api::Api = api::Default, Audio: AudioTrack = NoAudio> {
+	player: VideoPlayer<Api, true>,
+	audio: Option<Audio>,
+	state: PlaybackState,
+	looping: bool,
+	frame_rate: c_float,
+	frame_count: c_int,
+	/// Current position in seconds.
+	position: c_float,
+	/// Last rendered frame.
+	rendered: Option<c_int>,
+	on_end: Option<Box<dyn FnMut()>>,
+}
+
+impl<Api: api::Api> VideoPlayback<Api, NoAudio> {
+	/// Creates stopped playback of the given `player`.
+	///
+	/// Render target of the `player` is kept as is.
+	pub fn new(player: VideoPlayer<Api, true>) -> Self {
+		let info = player.info();
+		Self { player,
+		       audio: None,
+		       state: PlaybackState::Stopped,
+		       looping: false,
+		       frame_rate: info.frame_rate,
+		       frame_count: info.frame_count,
+		       position: 0.0,
+		       rendered: None,
+		       on_end: None }
+	}
+}
+
+impl<Api: api::Api, Audio: AudioTrack> VideoPlayback<Api, Audio> {
+	/// Attaches companion `audio` track, which is started and stopped together with the video.
+	pub fn with_audio<NewAudio: AudioTrack>(self, audio: NewAudio) -> VideoPlayback<Api, NewAudio> {
+		VideoPlayback { player: self.player,
+		                audio: Some(audio),
+		                state: self.state,
+		                looping: self.looping,
+		                frame_rate: self.frame_rate,
+		                frame_count: self.frame_count,
+		                position: self.position,
+		                rendered: self.rendered,
+		                on_end: self.on_end }
+	}
+
+	pub fn player(&self) -> &VideoPlayer<Api, true> { &self.player }
+	pub fn audio(&self) -> Option<&Audio> { self.audio.as_ref() }
+	pub fn audio_mut(&mut self) -> Option<&mut Audio> { self.audio.as_mut() }
+
+	pub fn state(&self) -> PlaybackState { self.state }
+	pub fn is_playing(&self) -> bool { self.state == PlaybackState::Playing }
+	pub fn is_finished(&self) -> bool { self.state == PlaybackState::Finished }
+
+	pub fn frame_rate(&self) -> c_float { self.frame_rate }
+	pub fn frame_count(&self) -> c_int { self.frame_count }
+
+	/// Length of the video in seconds.
+	pub fn duration(&self) -> c_float {
+		if self.frame_rate > 0.0 {
+			self.frame_count as c_float / self.frame_rate
+		} else {
+			0.0
+		}
+	}
+
+	/// Current position in seconds.
+	pub fn position(&self) -> c_float { self.position }
+
+	/// Frame at the current position.
+	pub fn current_frame(&self) -> c_int {
+		let frame = (self.position * self.frame_rate) as c_int;
+		frame.clamp(0, (self.frame_count - 1).max(0))
+	}
+
+	pub fn looping(&self) -> bool { self.looping }
+	pub fn set_looping(&mut self, looping: bool) { self.looping = looping }
+
+	/// Sets `callback` called when a non-looping video reaches its end.
+	pub fn set_end_callback<F: FnMut() + 'static>(&mut self, callback: F) {
+		self.on_end = Some(Box::new(callback))
+	}
+	pub fn clear_end_callback(&mut self) { self.on_end = None }
+
+
+	/// Starts playing from the current position,
+	/// or from the beginning if the video is finished.
+	pub fn play(&mut self) {
+		if self.state == PlaybackState::Finished {
+			self.position = 0.0;
+		}
+		self.state = PlaybackState::Playing;
+		self.sync_audio();
+	}
+
+	pub fn pause(&mut self) {
+		if self.state == PlaybackState::Playing {
+			self.state = PlaybackState::Paused;
+			self.stop_audio();
+		}
+	}
+
+	pub fn resume(&mut self) {
+		if self.state == PlaybackState::Paused {
+			self.play();
+		}
+	}
+
+	/// Stops playing and rewinds to the beginning.
+	pub fn stop(&mut self) {
+		self.state = PlaybackState::Stopped;
+		self.position = 0.0;
+		self.stop_audio();
+	}
+
+	/// Moves to `position` in seconds, clamped to the video length.
+	pub fn seek(&mut self, position: c_float) {
+		self.position = position.clamp(0.0, self.duration());
+		if self.state == PlaybackState::Finished {
+			self.state = PlaybackState::Paused;
+		}
+		if let Some(audio) = self.audio.as_mut() {
+			audio.set_offset(self.position);
+		}
+	}
+
+	/// Moves to the `frame`.
+	pub fn seek_frame(&mut self, frame: c_int) {
+		if self.frame_rate > 0.0 {
+			self.seek(frame as c_float / self.frame_rate)
+		}
+	}
+
+
+	/// Advances playback by `dt` seconds and renders the current frame if it changed.
+	///
+	/// Returns `true` if a new frame was rendered.
+	pub fn update(&mut self, dt: c_float) -> Result<bool, Error> {
+		if self.state == PlaybackState::Playing {
+			self.advance(dt);
+		}
+		self.render()
+	}
+
+	/// Renders the frame at the current position if it was not rendered yet.
+	///
+	/// Returns `true` if a new frame was rendered.
+	pub fn render(&mut self) -> Result<bool, Error> {
+		let frame = self.current_frame();
+		if self.rendered == Some(frame) || self.frame_count == 0 {
+			return Ok(false);
+		}
+		self.player.render_frame(frame)?;
+		self.rendered = Some(frame);
+		Ok(true)
+	}
+
+	/// Forces the next [`render`](VideoPlayback::render) to draw even if the frame did not change,
+	/// e.g. after the render target was cleared.
+	pub fn invalidate(&mut self) { self.rendered = None }
+
+
+	fn advance(&mut self, dt: c_float) {
+		let duration = self.duration();
+
+		self.position = match self.audio.as_ref() {
+			Some(audio) if audio.is_playing() => audio.offset(),
+			_ => self.position + dt,
+		};
+
+		if self.position < duration {
+			return;
+		}
+
+		if self.looping && duration > 0.0 {
+			self.position %= duration;
+			self.sync_audio();
+		} else {
+			self.position = duration;
+			self.state = PlaybackState::Finished;
+			self.stop_audio();
+			if let Some(callback) = self.on_end.as_mut() {
+				callback();
+			}
+		}
+	}
+
+	fn sync_audio(&mut self) {
+		if let Some(audio) = self.audio.as_mut() {
+			audio.set_offset(self.position);
+			if !audio.is_playing() {
+				audio.play();
+			}
+		}
+	}
+
+	fn stop_audio(&mut self) {
+		if let Some(audio) = self.audio.as_mut() {
+			audio.stop();
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use core::cell::Cell;
+	use alloc::rc::Rc;
+	use sys::ffi::LCDVideoPlayer;
+
+	use super::*;
+
+	/// Video end-point of a 2 seconds video with 20 frames at 10 fps.
+	#[derive(Clone, Copy)]
+	struct Fake;
+
+	impl api::Api for Fake {
+		fn free_player(&self) -> unsafe extern "C" fn(p: *mut LCDVideoPlayer) {
+			unsafe extern "C" fn free(_: *mut LCDVideoPlayer) {}
+			free
+		}
+
+		fn render_frame(&self) -> unsafe extern "C" fn(p: *mut LCDVideoPlayer, n: c_int) -> c_int {
+			unsafe extern "C" fn render(_: *mut LCDVideoPlayer, n: c_int) -> c_int { (0..20).contains(&n) as _ }
+			render
+		}
+
+		fn get_info(
+			&self)
+			-> unsafe extern "C" fn(p: *mut LCDVideoPlayer,
+			                        outWidth: *mut c_int,
+			                        outHeight: *mut c_int,
+			                        outFrameRate: *mut c_float,
+			                        outFrameCount: *mut c_int,
+			                        outCurrentFrame: *mut c_int) {
+			unsafe extern "C" fn info(_: *mut LCDVideoPlayer,
+			                          width: *mut c_int,
+			                          height: *mut c_int,
+			                          frame_rate: *mut c_float,
+			                          frame_count: *mut c_int,
+			                          current_frame: *mut c_int) {
+				*width = 400;
+				*height = 240;
+				*frame_rate = 10.0;
+				*frame_count = 20;
+				*current_frame = 0;
+			}
+			info
+		}
+	}
+
+	#[derive(Default)]
+	struct FakeAudio {
+		playing: bool,
+		offset: c_float,
+		plays: u32,
+	}
+
+	impl AudioTrack for FakeAudio {
+		fn play(&mut self) {
+			self.playing = true;
+			self.plays += 1;
+		}
+		fn stop(&mut self) { self.playing = false }
+		fn is_playing(&self) -> bool { self.playing }
+		fn offset(&self) -> c_float { self.offset }
+		fn set_offset(&mut self, offset: c_float) { self.offset = offset }
+	}
+
+	fn playback() -> VideoPlayback<Fake> {
+		VideoPlayback::new(VideoPlayer(core::ptr::NonNull::dangling().as_ptr(), Fake))
+	}
+
+	#[test]
+	fn frame_selection() {
+		let mut playback = playback();
+		assert_eq!(2.0, playback.duration());
+		assert!(playback.update(0.05).unwrap());
+		assert!(!playback.update(0.05).unwrap());
+		assert_eq!(PlaybackState::Stopped, playback.state());
+
+		playback.play();
+		assert!(!playback.update(0.05).unwrap());
+		assert!(playback.update(0.1).unwrap());
+		assert_eq!(1, playback.current_frame());
+
+		playback.pause();
+		assert!(!playback.update(1.0).unwrap());
+		assert_eq!(1, playback.current_frame());
+		playback.invalidate();
+		assert!(playback.render().unwrap());
+	}
+
+	#[test]
+	fn seek() {
+		let mut playback = playback();
+		playback.seek(5.0);
+		assert_eq!(2.0, playback.position());
+		assert_eq!(19, playback.current_frame());
+		playback.seek(-1.0);
+		assert_eq!(0, playback.current_frame());
+		playback.seek_frame(7);
+		assert_eq!(7, playback.current_frame());
+	}
+
+	#[test]
+	fn end_callback() {
+		let ended = Rc::new(Cell::new(0));
+		let mut playback = playback();
+		playback.set_end_callback({
+			        let ended = ended.clone();
+			        move || ended.set(ended.get() + 1)
+		        });
+		playback.play();
+		playback.update(1.5).unwrap();
+		assert_eq!(0, ended.get());
+		playback.update(1.0).unwrap();
+		assert_eq!(1, ended.get());
+		assert!(playback.is_finished());
+		assert_eq!(19, playback.current_frame());
+
+		playback.update(1.0).unwrap();
+		assert_eq!(1, ended.get());
+
+		playback.seek(1.0);
+		assert_eq!(PlaybackState::Paused, playback.state());
+		playback.play();
+		assert_eq!(1.0, playback.position());
+	}
+
+	#[test]
+	fn looping() {
+		let ended = Rc::new(Cell::new(false));
+		let mut playback = playback();
+		playback.set_end_callback({
+			        let ended = ended.clone();
+			        move || ended.set(true)
+		        });
+		playback.set_looping(true);
+		playback.play();
+		playback.update(2.5).unwrap();
+		assert!(playback.is_playing());
+		assert!(!ended.get());
+		assert_eq!(5, playback.current_frame());
+	}
+
+	#[test]
+	fn audio_clock() {
+		let mut playback = playback().with_audio(FakeAudio::default());
+		playback.seek(0.5);
+		playback.play();
+		let audio = playback.audio().unwrap();
+		assert!(audio.is_playing());
+		assert_eq!(0.5, audio.offset());
+
+		// video follows the audio, not the passed time:
+		playback.audio_mut().unwrap().offset = 1.25;
+		playback.update(0.01).unwrap();
+		assert_eq!(12, playback.current_frame());
+
+		playback.pause();
+		assert!(!playback.audio().unwrap().is_playing());
+		playback.update(0.5).unwrap();
+		assert_eq!(1.25, playback.position());
+
+		playback.resume();
+		playback.audio_mut().unwrap().offset = 2.0;
+		playback.update(0.0).unwrap();
+		assert!(playback.is_finished());
+		assert!(!playback.audio().unwrap().is_playing());
+		assert_eq!(2, playback.audio().unwrap().plays);
+	}
+}
@@ -0,0 +1,197 @@
+//! IMA ADPCM codec, in the block layout used by WAV files.
+//!
+//! Each block starts with a 4-byte header per channel (initial sample and step index),
+//! followed by rounds of 4 bytes per channel, 8 samples each, low nibble first.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+
+const INDEX_TABLE: [i32; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+const STEP_TABLE: [i32; 89] = [7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50,
+                               55, 60, 66, 73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253,
+                               279, 307, 337, 371, 408, 449, 494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166,
+                               1282, 1411, 1552, 1707, 1878, 2066, 2272, 2499, 2749, 3024, 3327, 3660, 4026,
+                               4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493, 10442, 11487, 12635, 13899,
+                               15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767];
+
+
+/// Returns number of samples per channel in a block of `block_align` bytes.
+///
+/// Returns `0` if there are no `channels` or the block is too small for their headers.
+pub const fn samples_per_block(block_align: usize, channels: usize) -> usize {
+	if channels == 0 || block_align < 4 * channels {
+		return 0;
+	}
+	(block_align - 4 * channels) * 2 / channels + 1
+}
+
+
+/// Codec state of one channel.
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+	predictor: i32,
+	index: i32,
+}
+
+impl State {
+	fn decode(&mut self, nibble: u8) -> i16 {
+		let step = STEP_TABLE[self.index as usize];
+		let mut diff = step >> 3;
+		if nibble & 4 != 0 {
+			diff += step;
+		}
+		if nibble & 2 != 0 {
+			diff += step >> 1;
+		}
+		if nibble & 1 != 0 {
+			diff += step >> 2;
+		}
+		if nibble & 8 != 0 {
+			self.predictor -= diff;
+		} else {
+			self.predictor += diff;
+		}
+		self.predictor = self.predictor.clamp(i16::MIN as _, i16::MAX as _);
+		self.index = (self.index + INDEX_TABLE[nibble as usize]).clamp(0, 88);
+		self.predictor as i16
+	}
+
+	fn encode(&mut self, sample: i16) -> u8 {
+		let mut diff = sample as i32 - self.predictor;
+		let mut nibble = 0;
+		if diff < 0 {
+			nibble = 8;
+			diff = -diff;
+		}
+		let mut step = STEP_TABLE[self.index as usize];
+		let mut mask = 4;
+		while mask != 0 {
+			if diff >= step {
+				nibble |= mask;
+				diff -= step;
+			}
+			step >>= 1;
+			mask >>= 1;
+		}
+		self.decode(nibble);
+		nibble
+	}
+}
+
+
+/// Decodes ADPCM `data` into interleaved 16-bit PCM.
+///
+/// Incomplete rounds at the end of the data are ignored.
+pub fn decode(data: &[u8], channels: usize, block_align: usize) -> Vec<i16> {
+	let mut out = Vec::with_capacity(data.len() * 2);
+	if channels == 0 || block_align <= 4 * channels {
+		return out;
+	}
+
+	let mut states = vec![State::default(); channels];
+	let mut round = vec![0; 8 * channels];
+	for block in data.chunks(block_align) {
+		let Some((header, body)) = block.split_at_checked(4 * channels) else {
+			break;
+		};
+		for (state, header) in states.iter_mut().zip(header.chunks_exact(4)) {
+			state.predictor = i16::from_le_bytes([header[0], header[1]]) as i32;
+			state.index = (header[2] as i32).clamp(0, 88);
+			out.push(state.predictor as i16);
+		}
+
+		for bytes in body.chunks_exact(4 * channels) {
+			for (ch, (state, bytes)) in states.iter_mut().zip(bytes.chunks_exact(4)).enumerate() {
+				for (i, byte) in bytes.iter().enumerate() {
+					round[(i * 2) * channels + ch] = state.decode(byte & 0x0F);
+					round[(i * 2 + 1) * channels + ch] = state.decode(byte >> 4);
+				}
+			}
+			out.extend_from_slice(&round);
+		}
+	}
+	out
+}
+
+
+/// Encodes interleaved 16-bit `pcm` into ADPCM blocks of `block_align` bytes.
+///
+/// The last block is padded with the last frame, so decoded data can be longer than `pcm`.
+pub fn encode(pcm: &[i16], channels: usize, block_align: usize) -> Vec<u8> {
+	let mut out = Vec::new();
+	if channels == 0 || block_align <= 4 * channels || block_align % (4 * channels) != 0 {
+		return out;
+	}
+
+	let frames = pcm.len() / channels;
+	if frames == 0 {
+		return out;
+	}
+	let block_frames = samples_per_block(block_align, channels);
+	let frame = |index: usize, ch: usize| pcm[index.min(frames - 1) * channels + ch];
+
+	// start with the step matching the first difference, so the first block doesn't lag behind
+	let mut states = vec![State::default(); channels];
+	for (ch, state) in states.iter_mut().enumerate() {
+		let diff = (frame(1, ch) as i32 - frame(0, ch) as i32).abs();
+		state.index = STEP_TABLE.iter().position(|step| *step >= diff).unwrap_or(88) as i32;
+	}
+	let mut start = 0;
+	while start < frames {
+		for (ch, state) in states.iter_mut().enumerate() {
+			state.predictor = frame(start, ch) as i32;
+			out.extend_from_slice(&(state.predictor as i16).to_le_bytes());
+			out.push(state.index as u8);
+			out.push(0);
+		}
+
+		let end = (start + block_frames).min(frames);
+		let mut index = start + 1;
+		while index < end {
+			for (ch, state) in states.iter_mut().enumerate() {
+				for i in 0..4 {
+					let low = state.encode(frame(index + i * 2, ch));
+					let high = state.encode(frame(index + i * 2 + 1, ch));
+					out.push(low | (high << 4));
+				}
+			}
+			index += 8;
+		}
+		start += block_frames;
+	}
+	out
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+
+	#[test]
+	fn roundtrip() {
+		let pcm: Vec<i16> = (0..1000).map(|i| (libm::sinf(i as f32 * 0.05) * 12000.0) as i16)
+		                             .collect();
+		let block_align = 256;
+		let encoded = encode(&pcm, 1, block_align);
+		let decoded = decode(&encoded, 1, block_align);
+		assert!(decoded.len() >= pcm.len());
+		assert_eq!(pcm[0], decoded[0]);
+		let error = pcm.iter()
+		               .zip(&decoded)
+		               .map(|(a, b)| (*a as i32 - *b as i32).abs())
+		               .max()
+		               .unwrap();
+		assert!(error < 1000, "max error {error}");
+	}
+
+	#[test]
+	fn block_size() {
+		assert_eq!(505, samples_per_block(256, 1));
+		assert_eq!(505, samples_per_block(512, 2));
+		assert_eq!(0, samples_per_block(256, 0));
+		assert_eq!(0, samples_per_block(4, 2));
+	}
+}
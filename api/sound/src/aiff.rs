@@ -0,0 +1,117 @@
+//! AIFF and AIFF-C decoding of uncompressed PCM audio.
+
+use crate::error::Error;
+use crate::pcm::PcmBuffer;
+use crate::pcm::PcmData;
+
+
+/// Decodes an AIFF or uncompressed AIFF-C file with 8 or 16-bit data, mono or stereo.
+pub fn decode(bytes: &[u8]) -> Result<PcmBuffer, Error> {
+	if bytes.len() < 12 || &bytes[..4] != b"FORM" || !matches!(&bytes[8..12], b"AIFF" | b"AIFC") {
+		return Err(Error::InvalidData);
+	}
+
+	let mut comm = None;
+	let mut ssnd = None;
+	let mut rest = &bytes[12..];
+	while rest.len() >= 8 {
+		let id = &rest[..4];
+		let len = u32_be(&rest[4..8]) as usize;
+		let end = len.checked_add(8).ok_or(Error::InvalidData)?;
+		let body = rest.get(8..end).unwrap_or(&rest[8..]);
+		match id {
+			b"COMM" => comm = Some(body),
+			b"SSND" => ssnd = Some(body),
+			_ => {},
+		}
+		let next = end.checked_add(len % 2).ok_or(Error::InvalidData)?;
+		rest = rest.get(next..).unwrap_or_default();
+	}
+
+	let (Some(comm), Some(ssnd)) = (comm, ssnd) else {
+		return Err(Error::InvalidData);
+	};
+	if comm.len() < 18 || ssnd.len() < 8 {
+		return Err(Error::InvalidData);
+	}
+	let channels = u16_be(&comm[0..2]) as usize;
+	let frames = u32_be(&comm[2..6]) as usize;
+	let bits = u16_be(&comm[6..8]);
+	let sample_rate = extended_to_u32(&comm[8..18]);
+	let little_endian = match comm.get(18..22) {
+		None | Some(b"NONE") | Some(b"twos") => false,
+		Some(b"sowt") => true,
+		_ => return Err(Error::InvalidData),
+	};
+	let stereo = match channels {
+		1 => false,
+		2 => true,
+		_ => return Err(Error::InvalidData),
+	};
+
+	let offset = u32_be(&ssnd[0..4]) as usize;
+	let data = ssnd.get(8 + offset..).ok_or(Error::InvalidData)?;
+	let len = frames * channels;
+	let data = match bits {
+		8 => PcmData::I8(data.iter().take(len).map(|v| *v as i8).collect()),
+		16 => {
+			let sample = |v: &[u8]| {
+				if little_endian {
+					i16::from_le_bytes([v[0], v[1]])
+				} else {
+					i16::from_be_bytes([v[0], v[1]])
+				}
+			};
+			PcmData::I16(data.chunks_exact(2).take(len).map(sample).collect())
+		},
+		_ => return Err(Error::InvalidData),
+	};
+	Ok(PcmBuffer::new(data, stereo, sample_rate))
+}
+
+
+/// Converts 80-bit IEEE 754 extended precision float to integer.
+fn extended_to_u32(bytes: &[u8]) -> u32 {
+	let exponent = (u16_be(&bytes[0..2]) & 0x7FFF) as i32 - 16383;
+	let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+	if !(0..32).contains(&exponent) {
+		return 0;
+	}
+	(mantissa >> (63 - exponent)) as u32
+}
+
+fn u16_be(bytes: &[u8]) -> u16 { u16::from_be_bytes([bytes[0], bytes[1]]) }
+fn u32_be(bytes: &[u8]) -> u32 { u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) }
+
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+	use super::*;
+
+
+	#[test]
+	fn decode_mono16() {
+		let mut comm = Vec::new();
+		comm.extend_from_slice(&1u16.to_be_bytes());
+		comm.extend_from_slice(&2u32.to_be_bytes());
+		comm.extend_from_slice(&16u16.to_be_bytes());
+		// 44100 as 80-bit extended
+		comm.extend_from_slice(&[0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]);
+		let mut ssnd = alloc::vec![0; 8];
+		ssnd.extend_from_slice(&[0x01, 0x00, 0xFF, 0xFF]);
+
+		let mut aiff = Vec::new();
+		aiff.extend_from_slice(b"FORM");
+		aiff.extend_from_slice(&((4 + 8 + comm.len() + 8 + ssnd.len()) as u32).to_be_bytes());
+		aiff.extend_from_slice(b"AIFF");
+		for (id, body) in [(b"COMM", &comm), (b"SSND", &ssnd)] {
+			aiff.extend_from_slice(id);
+			aiff.extend_from_slice(&(body.len() as u32).to_be_bytes());
+			aiff.extend_from_slice(body);
+		}
+
+		let pcm = decode(&aiff).unwrap();
+		assert_eq!(PcmBuffer::mono16(alloc::vec![256, -1], 44100), pcm);
+	}
+}
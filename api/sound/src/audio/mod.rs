@@ -0,0 +1,147 @@
+//! Custom audio sources written in Rust
+//!
+//! Implement [`AudioSource`] and register it with [`Sound::add_audio_source`](crate::Sound::add_audio_source)
+//! to play on the default channel, or with [`Channel::add_audio_source`](crate::channel::Channel::add_audio_source).
+//!
+//! The source is rendered in the audio callback, so it should only share state with the game loop
+//! via lock-free types, such as [`Param`] and [`ring`] buffers.
+
+use alloc::boxed::Box;
+use core::ffi::c_int;
+use core::ffi::c_void;
+
+use sys::ffi::AudioSourceFunction;
+use sys::ffi::SoundSource as OpaqueSoundSource;
+
+use crate::error::Error;
+use crate::source::AsSoundSource;
+use crate::source::SoundSource;
+
+pub mod param;
+pub mod ring;
+
+pub use param::Param;
+
+
+/// Audio source rendered by the sound engine every audio cycle.
+///
+/// The source is moved to the audio callback, so it must be [`Send`] and `'static` to be registered.
+pub trait AudioSource {
+	/// Fills `left` buffer (and `right` if the source is stereo) with samples.
+	///
+	/// Returns `false` if the source is silent through the cycle.
+	///
+	/// Called from the audio callback, so it must not block or allocate.
+	fn render(&mut self, left: &mut [i16], right: Option<&mut [i16]>) -> bool;
+}
+
+impl<F> AudioSource for F where F: FnMut(&mut [i16], Option<&mut [i16]>) -> bool {
+	fn render(&mut self, left: &mut [i16], right: Option<&mut [i16]>) -> bool { self(left, right) }
+}
+
+
+/// Context of the render callback.
+struct Context<S> {
+	stereo: bool,
+	source: S,
+}
+
+
+/// Render callback proxy, calls [`AudioSource::render`].
+unsafe extern "C" fn proxy_render<S: AudioSource>(context: *mut c_void,
+                                                  left: *mut i16,
+                                                  right: *mut i16,
+                                                  len: c_int)
+                                                  -> c_int {
+	let Some(context) = (context as *mut Context<S>).as_mut() else {
+		return 0;
+	};
+	let len = len.max(0) as usize;
+	let left = core::slice::from_raw_parts_mut(left, len);
+	let right = (context.stereo && !right.is_null()).then(|| core::slice::from_raw_parts_mut(right, len));
+	context.source.render(left, right) as _
+}
+
+
+/// Registered [`AudioSource`].
+///
+/// The source is removed from its channel and freed on drop,
+/// so it's rendered as long as this handle is alive.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct CallbackSource<S: AudioSource> {
+	source: SoundSource,
+	context: *mut Context<S>,
+}
+
+
+// ctor //
+
+impl<S: AudioSource + Send + 'static> CallbackSource<S> {
+	/// Registers the `source` with `add`, which gets the render callback with its context
+	/// and returns the created sound source.
+	pub(crate) fn register(source: S,
+	                       stereo: bool,
+	                       add: impl FnOnce(AudioSourceFunction, *mut c_void) -> *mut OpaqueSoundSource)
+	                       -> Result<Self, Error> {
+		let context = Box::into_raw(Box::new(Context { stereo, source }));
+		let ptr = add(Some(proxy_render::<S>), context as _);
+		if ptr.is_null() {
+			drop(unsafe { Box::from_raw(context) });
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { source: ptr.into(),
+			          context })
+		}
+	}
+}
+
+
+impl<S: AudioSource> Drop for CallbackSource<S> {
+	fn drop(&mut self) {
+		if !self.context.is_null() {
+			self.unregister();
+			drop(unsafe { Box::from_raw(self.context) });
+			self.context = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<S: AudioSource> AsSoundSource for CallbackSource<S> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.source.as_sound_source() }
+}
+
+
+// impl //
+
+impl<S: AudioSource> CallbackSource<S> {
+	/// Returns the sound source to control volume and playback state.
+	pub fn source(&self) -> &SoundSource { &self.source }
+
+	/// Returns `true` if the source is stereo.
+	pub fn is_stereo(&self) -> bool { unsafe { (*self.context).stereo } }
+
+	/// Stops rendering, removes the source from its channel and returns the [`AudioSource`].
+	pub fn remove(mut self) -> S {
+		self.unregister();
+		let context = unsafe { Box::from_raw(self.context) };
+		self.context = core::ptr::null_mut();
+		context.source
+	}
+
+	/// Removes the sound source from its channel and frees it.
+	///
+	/// Freed with the system allocator, as the caller owns the callback source.
+	fn unregister(&self) {
+		use crate::api::Api;
+
+		self.source.clear_finish_callback();
+		let ptr = self.source.as_sound_source();
+		let f = crate::api::Default.remove_source();
+		unsafe { f(ptr) };
+		let f = *sys::api!(system.realloc);
+		unsafe { f(ptr as _, 0) };
+	}
+}
@@ -0,0 +1,27 @@
+//! Lock-free parameters shared between the game loop and audio sources.
+
+use alloc::sync::Arc;
+use core::ffi::c_float;
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::Ordering;
+
+
+/// Float parameter that can be set from the game loop and read in the audio callback.
+///
+/// Clones share the same value.
+#[derive(Debug, Clone, Default)]
+pub struct Param(Arc<AtomicU32>);
+
+impl Param {
+	pub fn new(value: c_float) -> Self { Self(Arc::new(AtomicU32::new(value.to_bits()))) }
+
+	#[inline(always)]
+	pub fn get(&self) -> c_float { c_float::from_bits(self.0.load(Ordering::Relaxed)) }
+
+	#[inline(always)]
+	pub fn set(&self, value: c_float) { self.0.store(value.to_bits(), Ordering::Relaxed) }
+}
+
+impl From<c_float> for Param {
+	fn from(value: c_float) -> Self { Self::new(value) }
+}
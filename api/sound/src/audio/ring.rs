@@ -0,0 +1,140 @@
+//! Lock-free single-producer single-consumer ring buffer,
+//! e.g. to send events from the game loop to an audio source.
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+
+
+/// Creates a ring buffer holding up to `capacity` items,
+/// returns its producer and consumer halves.
+pub fn buffer<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+	let slots = (0..=capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+	                          .collect();
+	let inner = Arc::new(Inner { slots,
+	                             head: AtomicUsize::new(0),
+	                             tail: AtomicUsize::new(0) });
+	(Producer(inner.clone()), Consumer(inner))
+}
+
+
+struct Inner<T> {
+	/// One slot more than the capacity, to tell full from empty.
+	slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+	/// Index of the next item to pop, written by the consumer.
+	head: AtomicUsize,
+	/// Index of the next slot to push to, written by the producer.
+	tail: AtomicUsize,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+	#[inline(always)]
+	fn next(&self, index: usize) -> usize { (index + 1) % self.slots.len() }
+
+	fn len(&self) -> usize {
+		let head = self.head.load(Ordering::Acquire);
+		let tail = self.tail.load(Ordering::Acquire);
+		(tail + self.slots.len() - head) % self.slots.len()
+	}
+}
+
+impl<T> Drop for Inner<T> {
+	fn drop(&mut self) {
+		let mut head = *self.head.get_mut();
+		let tail = *self.tail.get_mut();
+		while head != tail {
+			unsafe { self.slots[head].get_mut().assume_init_drop() };
+			head = self.next(head);
+		}
+	}
+}
+
+
+/// Pushing half of a ring [`buffer`].
+pub struct Producer<T>(Arc<Inner<T>>);
+
+impl<T> Producer<T> {
+	/// Pushes the `value`, or returns it back if the buffer is full.
+	pub fn push(&mut self, value: T) -> Result<(), T> {
+		let tail = self.0.tail.load(Ordering::Relaxed);
+		let next = self.0.next(tail);
+		if next == self.0.head.load(Ordering::Acquire) {
+			return Err(value);
+		}
+		unsafe { (*self.0.slots[tail].get()).write(value) };
+		self.0.tail.store(next, Ordering::Release);
+		Ok(())
+	}
+
+	/// Returns the number of items in the buffer.
+	pub fn len(&self) -> usize { self.0.len() }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	/// Returns the maximum number of items in the buffer.
+	pub fn capacity(&self) -> usize { self.0.slots.len() - 1 }
+}
+
+
+/// Popping half of a ring [`buffer`].
+pub struct Consumer<T>(Arc<Inner<T>>);
+
+impl<T> Consumer<T> {
+	/// Pops the oldest value, if any.
+	pub fn pop(&mut self) -> Option<T> {
+		let head = self.0.head.load(Ordering::Relaxed);
+		if head == self.0.tail.load(Ordering::Acquire) {
+			return None;
+		}
+		let value = unsafe { (*self.0.slots[head].get()).assume_init_read() };
+		self.0.head.store(self.0.next(head), Ordering::Release);
+		Some(value)
+	}
+
+	/// Returns an iterator popping all available values.
+	pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ { core::iter::from_fn(|| self.pop()) }
+
+	/// Returns the number of items in the buffer.
+	pub fn len(&self) -> usize { self.0.len() }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	/// Returns the maximum number of items in the buffer.
+	pub fn capacity(&self) -> usize { self.0.slots.len() - 1 }
+}
+
+
+#[cfg(test)]
+mod tests {
+	use alloc::rc::Rc;
+	use super::*;
+
+
+	#[test]
+	fn push_pop() {
+		let (mut tx, mut rx) = buffer(2);
+		assert_eq!(Ok(()), tx.push(1));
+		assert_eq!(Ok(()), tx.push(2));
+		assert_eq!(Err(3), tx.push(3));
+		assert_eq!(Some(1), rx.pop());
+		assert_eq!(Ok(()), tx.push(3));
+		assert_eq!(alloc::vec![2, 3], rx.drain().collect::<alloc::vec::Vec<_>>());
+		assert_eq!(None, rx.pop());
+	}
+
+	#[test]
+	fn drops_remaining() {
+		let value = Rc::new(());
+		let (mut tx, rx) = buffer(4);
+		tx.push(value.clone()).unwrap();
+		tx.push(value.clone()).unwrap();
+		drop((tx, rx));
+		assert_eq!(1, Rc::strong_count(&value));
+	}
+}
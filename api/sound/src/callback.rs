@@ -0,0 +1,154 @@
+//! Closure callbacks called by the sound engine.
+//!
+//! Closures are boxed and passed to the engine as userdata of a proxy function,
+//! owners keep them alive and unregister them before they're dropped.
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::ffi::c_int;
+use core::ffi::c_void;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::Ordering;
+
+use sys::ffi::sndCallbackProc;
+use sys::ffi::SoundSource;
+
+
+type Shared<F> = Rc<RefCell<Box<F>>>;
+
+
+/// Calls the shared closure at `ptr`, keeping it alive during the call even if its owner drops it.
+///
+/// Reentrant calls are ignored.
+unsafe fn call_shared<F: ?Sized>(ptr: *const RefCell<Box<F>>, call: impl FnOnce(&mut F)) {
+	if ptr.is_null() {
+		return;
+	}
+	Rc::increment_strong_count(ptr);
+	let shared = Rc::from_raw(ptr);
+	if let Ok(mut f) = shared.try_borrow_mut() {
+		call(&mut **f)
+	};
+}
+
+
+/// Closure called by the sound engine, e.g. when a source finishes playing.
+pub(crate) struct Callback(Shared<dyn FnMut()>);
+
+impl Callback {
+	/// Proxy function to pass to the engine with [`userdata`](Self::userdata).
+	pub const PROC: sndCallbackProc = Some(proxy);
+
+	pub fn new<F: FnMut() + 'static>(f: F) -> Self { Self(Rc::new(RefCell::new(Box::new(f)))) }
+
+	/// Returns userdata for the [`PROC`](Self::PROC), valid while `self` is alive.
+	pub fn userdata(&self) -> *mut c_void { Rc::as_ptr(&self.0) as _ }
+}
+
+impl core::fmt::Debug for Callback {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("Callback").field(&self.userdata()).finish()
+	}
+}
+
+
+/// Callback proxy, calls the [`Callback`] passed as `userdata`.
+unsafe extern "C" fn proxy(_: *mut SoundSource, userdata: *mut c_void) {
+	call_shared(userdata as *const RefCell<Box<dyn FnMut()>>, |f| f())
+}
+
+
+/// Headphone state change callback, only one can be registered at a time.
+static HEADPHONE: AtomicPtr<c_void> = AtomicPtr::new(core::ptr::null_mut());
+
+
+/// Headphone change proxy, calls the registered [`HeadphoneCallback`].
+unsafe extern "C" fn proxy_headphone(headphone: c_int, mic: c_int) {
+	let ptr = HEADPHONE.load(Ordering::Acquire);
+	call_shared(ptr as *const RefCell<Box<dyn FnMut(bool, bool)>>, |f| {
+		f(headphone == 1, mic == 1)
+	})
+}
+
+
+/// Registered headphone state change callback,
+/// see [`Sound::set_headphone_callback`](crate::Sound::set_headphone_callback).
+///
+/// The callback is removed on drop, then audio output switches between speaker and headphones automatically again.
+pub struct HeadphoneCallback(Shared<dyn FnMut(bool, bool)>);
+
+impl core::fmt::Debug for HeadphoneCallback {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("HeadphoneCallback")
+		 .field(&self.is_active())
+		 .finish()
+	}
+}
+
+
+// ctor //
+
+impl HeadphoneCallback {
+	/// Registers `f` with `set` getting the proxy function.
+	pub(crate) fn register<F>(f: F, set: impl FnOnce(unsafe extern "C" fn(c_int, c_int))) -> Self
+		where F: FnMut(bool, bool) + 'static {
+		let shared: Shared<dyn FnMut(bool, bool)> = Rc::new(RefCell::new(Box::new(f)));
+		HEADPHONE.store(Rc::as_ptr(&shared) as _, Ordering::Release);
+		set(proxy_headphone);
+		Self(shared)
+	}
+}
+
+
+impl Drop for HeadphoneCallback {
+	fn drop(&mut self) {
+		use crate::api::Api;
+
+		let ours = Rc::as_ptr(&self.0) as *mut c_void;
+		if HEADPHONE.compare_exchange(ours, core::ptr::null_mut(), Ordering::AcqRel, Ordering::Acquire)
+		            .is_ok()
+		{
+			let f = crate::api::Default.get_headphone_state();
+			let null = core::ptr::null_mut();
+			unsafe { f(null, null, None) };
+		}
+	}
+}
+
+
+// impl //
+
+impl HeadphoneCallback {
+	/// Returns `true` if this callback wasn't replaced by another one.
+	pub fn is_active(&self) -> bool { HEADPHONE.load(Ordering::Acquire) == Rc::as_ptr(&self.0) as *mut c_void }
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::cell::Cell;
+
+
+	#[test]
+	fn proxy_survives_replace() {
+		let calls = Rc::new(Cell::new(0));
+		let slot = Rc::new(RefCell::new(None::<Callback>));
+		let callback = {
+			let calls = calls.clone();
+			let slot = slot.clone();
+			Callback::new(move || {
+				calls.set(calls.get() + 1);
+				// drops the running closure
+				slot.borrow_mut().take();
+			})
+		};
+		let userdata = callback.userdata();
+		slot.borrow_mut().replace(callback);
+
+		unsafe { proxy(core::ptr::null_mut(), userdata) };
+		assert_eq!(1, calls.get());
+		assert!(slot.borrow().is_none());
+	}
+}
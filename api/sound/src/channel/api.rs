@@ -0,0 +1,145 @@
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+use sys::ffi::AudioSourceFunction;
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::SoundChannel;
+use sys::ffi::SoundEffect;
+use sys::ffi::SoundSource;
+use sys::ffi::playdate_sound_channel;
+
+
+/// Default sound channel api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached sound channel api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_channel);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.channel)) }
+}
+
+impl From<*const playdate_sound_channel> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_channel) -> Self { Self(unsafe { ptr.as_ref() }.expect("channel")) }
+}
+
+impl From<&'static playdate_sound_channel> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_channel) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_channel>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_channel>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_channel>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_channel>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_channel(&self) -> FnNewChannel { self.0.newChannel.expect("newChannel") }
+	fn free_channel(&self) -> FnFreeChannel { self.0.freeChannel.expect("freeChannel") }
+	fn add_source(&self) -> FnSource { self.0.addSource.expect("addSource") }
+	fn remove_source(&self) -> FnSource { self.0.removeSource.expect("removeSource") }
+	fn add_callback_source(&self) -> FnAddCallbackSource { self.0.addCallbackSource.expect("addCallbackSource") }
+	fn add_effect(&self) -> FnEffect { self.0.addEffect.expect("addEffect") }
+	fn remove_effect(&self) -> FnEffect { self.0.removeEffect.expect("removeEffect") }
+	fn set_volume(&self) -> FnSetFloat { self.0.setVolume.expect("setVolume") }
+	fn get_volume(&self) -> FnGetFloat { self.0.getVolume.expect("getVolume") }
+	fn set_volume_modulator(&self) -> FnSetModulator { self.0.setVolumeModulator.expect("setVolumeModulator") }
+	fn set_pan(&self) -> FnSetFloat { self.0.setPan.expect("setPan") }
+	fn set_pan_modulator(&self) -> FnSetModulator { self.0.setPanModulator.expect("setPanModulator") }
+	fn get_dry_level_signal(&self) -> FnGetSignal { self.0.getDryLevelSignal.expect("getDryLevelSignal") }
+	fn get_wet_level_signal(&self) -> FnGetSignal { self.0.getWetLevelSignal.expect("getWetLevelSignal") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_channel::newChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::newChannel")]
+	fn new_channel(&self) -> FnNewChannel { *sys::api!(sound.channel.newChannel) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::freeChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::freeChannel")]
+	fn free_channel(&self) -> FnFreeChannel { *sys::api!(sound.channel.freeChannel) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::addSource`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::addSource")]
+	fn add_source(&self) -> FnSource { *sys::api!(sound.channel.addSource) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::removeSource`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::removeSource")]
+	fn remove_source(&self) -> FnSource { *sys::api!(sound.channel.removeSource) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::addCallbackSource`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::addCallbackSource")]
+	fn add_callback_source(&self) -> FnAddCallbackSource { *sys::api!(sound.channel.addCallbackSource) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::addEffect`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::addEffect")]
+	fn add_effect(&self) -> FnEffect { *sys::api!(sound.channel.addEffect) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::removeEffect`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::removeEffect")]
+	fn remove_effect(&self) -> FnEffect { *sys::api!(sound.channel.removeEffect) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::setVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setVolume")]
+	fn set_volume(&self) -> FnSetFloat { *sys::api!(sound.channel.setVolume) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::getVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::getVolume")]
+	fn get_volume(&self) -> FnGetFloat { *sys::api!(sound.channel.getVolume) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::setVolumeModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setVolumeModulator")]
+	fn set_volume_modulator(&self) -> FnSetModulator { *sys::api!(sound.channel.setVolumeModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::setPan`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setPan")]
+	fn set_pan(&self) -> FnSetFloat { *sys::api!(sound.channel.setPan) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::setPanModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setPanModulator")]
+	fn set_pan_modulator(&self) -> FnSetModulator { *sys::api!(sound.channel.setPanModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::getDryLevelSignal`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::getDryLevelSignal")]
+	fn get_dry_level_signal(&self) -> FnGetSignal { *sys::api!(sound.channel.getDryLevelSignal) }
+
+	/// Returns [`sys::ffi::playdate_sound_channel::getWetLevelSignal`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::getWetLevelSignal")]
+	fn get_wet_level_signal(&self) -> FnGetSignal { *sys::api!(sound.channel.getWetLevelSignal) }
+}
+
+
+type FnNewChannel = unsafe extern "C" fn() -> *mut SoundChannel;
+type FnFreeChannel = unsafe extern "C" fn(channel: *mut SoundChannel);
+type FnSource = unsafe extern "C" fn(channel: *mut SoundChannel, source: *mut SoundSource) -> c_int;
+type FnAddCallbackSource = unsafe extern "C" fn(channel: *mut SoundChannel,
+                                                callback: AudioSourceFunction,
+                                                context: *mut c_void,
+                                                stereo: c_int)
+                                                -> *mut SoundSource;
+type FnEffect = unsafe extern "C" fn(channel: *mut SoundChannel, effect: *mut SoundEffect);
+type FnSetFloat = unsafe extern "C" fn(channel: *mut SoundChannel, value: c_float);
+type FnGetFloat = unsafe extern "C" fn(channel: *mut SoundChannel) -> c_float;
+type FnSetModulator = unsafe extern "C" fn(channel: *mut SoundChannel, modulator: *mut PDSynthSignalValue);
+type FnGetSignal = unsafe extern "C" fn(channel: *mut SoundChannel) -> *mut PDSynthSignalValue;
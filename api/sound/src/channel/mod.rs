@@ -0,0 +1,278 @@
+//! Playdate sound channel API
+//!
+//! Channels are buses mixing their sources and processing them with effects.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::Cell;
+use core::ffi::c_float;
+use core::ffi::c_int;
+
+use sys::ffi::SoundChannel as OpaqueSoundChannel;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+use sys::ffi::SoundSource as OpaqueSoundSource;
+
+use crate::audio::AudioSource;
+use crate::audio::CallbackSource;
+use crate::effect::AsSoundEffect;
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use crate::source::AsSoundSource;
+
+pub mod api;
+
+
+/// Sound channel with its sources and effects.
+///
+/// Sources and effects added to the channel are kept alive while they are in the channel,
+/// and removed from the channel when it's dropped.
+///
+/// New channel should be added to the sound engine with [`Sound::add_channel`](crate::Sound::add_channel)
+/// to be heard.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Channel<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueSoundChannel,
+	api: Api,
+	owned: bool,
+	/// `removeChannel` of the sound api that added this channel to the engine.
+	engine: Cell<Option<unsafe extern "C" fn(*mut OpaqueSoundChannel) -> c_int>>,
+	sources: Vec<(*mut OpaqueSoundSource, Rc<dyn Any>)>,
+	effects: Vec<(*mut OpaqueSoundEffect, Rc<dyn Any>)>,
+	volume: Slot,
+	pan: Slot,
+}
+
+impl<Api: api::Api> Drop for Channel<Api> {
+	fn drop(&mut self) {
+		if self.ptr.is_null() {
+			return;
+		}
+
+		let f = self.api.remove_source();
+		for (source, _) in self.sources.drain(..) {
+			unsafe { f(self.ptr, source) };
+		}
+		let f = self.api.remove_effect();
+		for (effect, _) in self.effects.drain(..) {
+			unsafe { f(self.ptr, effect) };
+		}
+
+		if self.owned {
+			if let Some(f) = self.engine.take() {
+				unsafe { f(self.ptr) };
+			}
+
+			let f = self.api.free_channel();
+			unsafe { f(self.ptr) };
+		}
+		self.ptr = core::ptr::null_mut();
+	}
+}
+
+
+// ctor //
+
+impl<Api> Channel<Api> where Api: api::Api {
+	/// Creates a new channel.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::newChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::newChannel")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new channel, using given `api`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::newChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::newChannel")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_channel();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self::from_with(api, ptr, true))
+		}
+	}
+
+	/// Wraps the given channel, freeing it on drop if `owned`.
+	pub(crate) fn from_with(api: Api, ptr: *mut OpaqueSoundChannel, owned: bool) -> Self {
+		Self { ptr,
+		       api,
+		       owned,
+		       engine: Cell::new(None),
+		       sources: Vec::new(),
+		       effects: Vec::new(),
+		       volume: Default::default(),
+		       pan: Default::default() }
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Channel<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+
+	#[inline(always)]
+	pub fn as_raw(&self) -> *mut OpaqueSoundChannel { self.ptr }
+
+	/// Returns `true` if this is the channel created by the user, not the default one.
+	#[inline(always)]
+	pub fn is_owned(&self) -> bool { self.owned }
+
+	/// Remembers `remove` as the way to take this channel out of the engine on drop.
+	pub(crate) fn set_engine(&self, remove: Option<unsafe extern "C" fn(*mut OpaqueSoundChannel) -> c_int>) {
+		self.engine.set(remove);
+	}
+}
+
+
+// impl //
+
+impl<Api: api::Api> Channel<Api> {
+	/// Adds the `source` to the channel, removing it from its previous channel.
+	///
+	/// The source is kept alive while it's in the channel.
+	/// Wrap it into [`Rc`] to keep access to it.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::addSource`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::addSource")]
+	pub fn add_source<S: AsSoundSource + 'static>(&mut self, source: S) -> Result<(), Error> {
+		let ptr = source.as_sound_source();
+		let f = self.api.add_source();
+		if unsafe { f(self.ptr, ptr) } == 0 {
+			return Err(Error::InvalidArgument);
+		}
+		self.sources.retain(|(p, _)| *p != ptr);
+		self.sources.push((ptr, Rc::new(source)));
+		Ok(())
+	}
+
+	/// Registers the [`AudioSource`] to play on the channel.
+	///
+	/// The source is rendered while it's in the channel, share its parameters with [`Param`](crate::audio::Param)
+	/// or a [ring buffer](crate::audio::ring) to control it.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::addCallbackSource`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::addCallbackSource")]
+	pub fn add_audio_source<S: AudioSource + Send + 'static>(&mut self,
+	                                                         source: S,
+	                                                         stereo: bool)
+	                                                         -> Result<(), Error> {
+		let f = self.api.add_callback_source();
+		let channel = self.ptr;
+		let source = CallbackSource::register(source, stereo, |callback, context| unsafe {
+			f(channel, callback, context, stereo as _)
+		})?;
+		self.sources.push((source.as_sound_source(), Rc::new(source)));
+		Ok(())
+	}
+
+	/// Removes the `source` from the channel.
+	///
+	/// Returns `true` if the source was found in the channel and removed.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::removeSource`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::removeSource")]
+	pub fn remove_source(&mut self, source: &impl AsSoundSource) -> bool {
+		let ptr = source.as_sound_source();
+		let f = self.api.remove_source();
+		let removed = unsafe { f(self.ptr, ptr) } == 1;
+		self.sources.retain(|(p, _)| *p != ptr);
+		removed
+	}
+
+	/// Adds the `effect` to the end of the channel’s effects chain.
+	///
+	/// The effect is kept alive while it's in the channel.
+	/// Wrap it into [`Rc`] to keep access to it.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::addEffect`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::addEffect")]
+	pub fn add_effect<E: AsSoundEffect + 'static>(&mut self, effect: E) {
+		let ptr = effect.as_sound_effect();
+		if self.effects.iter().any(|(p, _)| *p == ptr) {
+			return;
+		}
+		let f = self.api.add_effect();
+		unsafe { f(self.ptr, ptr) };
+		self.effects.push((ptr, Rc::new(effect)));
+	}
+
+	/// Removes the `effect` from the channel.
+	///
+	/// Returns `true` if the effect was added to this channel before.
+	/// `removeEffect` doesn't report the result in this SDK version,
+	/// so it's taken from the effects kept by the channel.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::removeEffect`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::removeEffect")]
+	pub fn remove_effect(&mut self, effect: &impl AsSoundEffect) -> bool {
+		let ptr = effect.as_sound_effect();
+		let f = self.api.remove_effect();
+		unsafe { f(self.ptr, ptr) };
+		let len = self.effects.len();
+		self.effects.retain(|(p, _)| *p != ptr);
+		len != self.effects.len()
+	}
+
+	/// Gets the volume for the channel, in range `0.0 - 1.0`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::getVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::getVolume")]
+	pub fn volume(&self) -> c_float {
+		let f = self.api.get_volume();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Sets the volume for the channel, in range `0.0 - 1.0`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::setVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setVolume")]
+	pub fn set_volume(&self, volume: c_float) {
+		let f = self.api.set_volume();
+		unsafe { f(self.ptr, volume) }
+	}
+
+	/// Sets a signal to modulate the channel volume. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::setVolumeModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setVolumeModulator")]
+	pub fn set_volume_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api.set_volume_modulator();
+		self.volume
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current volume modulator.
+	pub fn volume_modulator(&self) -> Option<Modulator> { self.volume.get() }
+
+	/// Sets the pan parameter for the channel.
+	///
+	/// Valid values are in the range `-1.0` (left) to `1.0` (right), `0.0` is center.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::setPan`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setPan")]
+	pub fn set_pan(&self, pan: c_float) {
+		let f = self.api.set_pan();
+		unsafe { f(self.ptr, pan) }
+	}
+
+	/// Sets a signal to modulate the channel pan. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_channel::setPanModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_channel::setPanModulator")]
+	pub fn set_pan_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api.set_pan_modulator();
+		self.pan
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current pan modulator.
+	pub fn pan_modulator(&self) -> Option<Modulator> { self.pan.get() }
+}
@@ -0,0 +1,69 @@
+use core::ffi::c_float;
+use core::ptr::NonNull;
+
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::SoundEffect;
+use sys::ffi::playdate_sound_effect;
+
+
+/// Default sound effect api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached sound effect api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect)) }
+}
+
+impl From<*const playdate_sound_effect> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect) -> Self { Self(unsafe { ptr.as_ref() }.expect("effect")) }
+}
+
+impl From<&'static playdate_sound_effect> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn set_mix(&self) -> FnSetMix { self.0.setMix.expect("setMix") }
+	fn set_mix_modulator(&self) -> FnSetMixModulator { self.0.setMixModulator.expect("setMixModulator") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	fn set_mix(&self) -> FnSetMix { *sys::api!(sound.effect.setMix) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	fn set_mix_modulator(&self) -> FnSetMixModulator { *sys::api!(sound.effect.setMixModulator) }
+}
+
+
+type FnSetMix = unsafe extern "C" fn(effect: *mut SoundEffect, level: c_float);
+type FnSetMixModulator = unsafe extern "C" fn(effect: *mut SoundEffect, signal: *mut PDSynthSignalValue);
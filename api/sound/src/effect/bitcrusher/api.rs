@@ -0,0 +1,97 @@
+use core::ffi::c_float;
+use core::ptr::NonNull;
+
+use sys::ffi::BitCrusher;
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::playdate_sound_effect_bitcrusher;
+
+
+/// Default bitcrusher api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached bitcrusher api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect_bitcrusher);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect.bitcrusher)) }
+}
+
+impl From<*const playdate_sound_effect_bitcrusher> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect_bitcrusher) -> Self {
+		Self(unsafe { ptr.as_ref() }.expect("bitcrusher"))
+	}
+}
+
+impl From<&'static playdate_sound_effect_bitcrusher> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect_bitcrusher) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect_bitcrusher>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect_bitcrusher>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect_bitcrusher>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect_bitcrusher>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_bit_crusher(&self) -> FnNew { self.0.newBitCrusher.expect("newBitCrusher") }
+	fn free_bit_crusher(&self) -> FnFree { self.0.freeBitCrusher.expect("freeBitCrusher") }
+	fn set_amount(&self) -> FnSetFloat { self.0.setAmount.expect("setAmount") }
+	fn set_amount_modulator(&self) -> FnSetModulator { self.0.setAmountModulator.expect("setAmountModulator") }
+	fn set_undersampling(&self) -> FnSetFloat { self.0.setUndersampling.expect("setUndersampling") }
+	fn set_undersample_modulator(&self) -> FnSetModulator {
+		self.0.setUndersampleModulator.expect("setUndersampleModulator")
+	}
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect_bitcrusher::newBitCrusher`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::newBitCrusher")]
+	fn new_bit_crusher(&self) -> FnNew { *sys::api!(sound.effect.bitcrusher.newBitCrusher) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_bitcrusher::freeBitCrusher`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::freeBitCrusher")]
+	fn free_bit_crusher(&self) -> FnFree { *sys::api!(sound.effect.bitcrusher.freeBitCrusher) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_bitcrusher::setAmount`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setAmount")]
+	fn set_amount(&self) -> FnSetFloat { *sys::api!(sound.effect.bitcrusher.setAmount) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_bitcrusher::setAmountModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setAmountModulator")]
+	fn set_amount_modulator(&self) -> FnSetModulator { *sys::api!(sound.effect.bitcrusher.setAmountModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_bitcrusher::setUndersampling`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setUndersampling")]
+	fn set_undersampling(&self) -> FnSetFloat { *sys::api!(sound.effect.bitcrusher.setUndersampling) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_bitcrusher::setUndersampleModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setUndersampleModulator")]
+	fn set_undersample_modulator(&self) -> FnSetModulator {
+		*sys::api!(sound.effect.bitcrusher.setUndersampleModulator)
+	}
+}
+
+
+type FnNew = unsafe extern "C" fn() -> *mut BitCrusher;
+type FnFree = unsafe extern "C" fn(filter: *mut BitCrusher);
+type FnSetFloat = unsafe extern "C" fn(filter: *mut BitCrusher, value: c_float);
+type FnSetModulator = unsafe extern "C" fn(filter: *mut BitCrusher, signal: *mut PDSynthSignalValue);
@@ -0,0 +1,149 @@
+//! Bitcrusher sound effect
+
+use core::ffi::c_float;
+
+use sys::ffi::BitCrusher as OpaqueBitCrusher;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use super::AsSoundEffect;
+use super::Mix;
+
+pub mod api;
+
+
+/// Bitcrusher effect, reduces bit depth and sample rate of the signal.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct BitCrusher<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueBitCrusher,
+	api: Api,
+	mix: Mix,
+	amount: Slot,
+	undersampling: Slot,
+}
+
+
+// ctor //
+
+impl<Api> BitCrusher<Api> where Api: api::Api {
+	/// Creates a new bitcrusher effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_bitcrusher::newBitCrusher`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::newBitCrusher")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new bitcrusher effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_bitcrusher::newBitCrusher`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::newBitCrusher")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_bit_crusher();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          mix: Default::default(),
+			          amount: Default::default(),
+			          undersampling: Default::default() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for BitCrusher<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_bit_crusher();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> BitCrusher<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsSoundEffect for BitCrusher<Api> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> BitCrusher<Api> {
+	/// Sets the wet/dry mix for the effect, `0` is dry and `1` is wet.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	pub fn set_mix(&self, level: c_float) { self.mix.set_level(self.as_sound_effect(), level) }
+
+	/// Sets a signal to modulate the effect’s mix level. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	pub fn set_mix_modulator(&self, modulator: Option<Modulator>) {
+		self.mix.set_modulator(self.as_sound_effect(), modulator)
+	}
+
+	/// Returns the current mix modulator.
+	pub fn mix_modulator(&self) -> Option<Modulator> { self.mix.modulator() }
+
+	/// Sets the amount of crushing, `0..=1`.
+	///
+	/// The default amount is `0` (no effect).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_bitcrusher::setAmount`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setAmount")]
+	pub fn set_amount(&self, amount: c_float) {
+		let f = self.api().set_amount();
+		unsafe { f(self.ptr, amount) }
+	}
+
+	/// Sets a signal to modulate the amount. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_bitcrusher::setAmountModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setAmountModulator")]
+	pub fn set_amount_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_amount_modulator();
+		self.amount
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current amount modulator.
+	pub fn amount_modulator(&self) -> Option<Modulator> { self.amount.get() }
+
+	/// Sets the number of samples to repeat, `0..=1`,
+	/// from `0` (no undersampling) to `1` (one sample every 32 repeated).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_bitcrusher::setUndersampling`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setUndersampling")]
+	pub fn set_undersampling(&self, undersampling: c_float) {
+		let f = self.api().set_undersampling();
+		unsafe { f(self.ptr, undersampling) }
+	}
+
+	/// Sets a signal to modulate the undersampling. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_bitcrusher::setUndersampleModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_bitcrusher::setUndersampleModulator")]
+	pub fn set_undersampling_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_undersample_modulator();
+		self.undersampling
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current undersampling modulator.
+	pub fn undersampling_modulator(&self) -> Option<Modulator> { self.undersampling.get() }
+}
@@ -0,0 +1,123 @@
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ptr::NonNull;
+
+use sys::ffi::DelayLine;
+use sys::ffi::DelayLineTap;
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::playdate_sound_effect_delayline;
+
+
+/// Default delay line api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached delay line api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect_delayline);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect.delayline)) }
+}
+
+impl From<*const playdate_sound_effect_delayline> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect_delayline) -> Self {
+		Self(unsafe { ptr.as_ref() }.expect("delayline"))
+	}
+}
+
+impl From<&'static playdate_sound_effect_delayline> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect_delayline) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect_delayline>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect_delayline>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect_delayline>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect_delayline>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_delay_line(&self) -> FnNew { self.0.newDelayLine.expect("newDelayLine") }
+	fn free_delay_line(&self) -> FnFree { self.0.freeDelayLine.expect("freeDelayLine") }
+	fn set_length(&self) -> FnSetLength { self.0.setLength.expect("setLength") }
+	fn set_feedback(&self) -> FnSetFeedback { self.0.setFeedback.expect("setFeedback") }
+	fn add_tap(&self) -> FnAddTap { self.0.addTap.expect("addTap") }
+	fn free_tap(&self) -> FnFreeTap { self.0.freeTap.expect("freeTap") }
+	fn set_tap_delay(&self) -> FnSetTapDelay { self.0.setTapDelay.expect("setTapDelay") }
+	fn set_tap_delay_modulator(&self) -> FnSetTapDelayModulator {
+		self.0.setTapDelayModulator.expect("setTapDelayModulator")
+	}
+	fn set_tap_channels_flipped(&self) -> FnSetTapChannelsFlipped {
+		self.0.setTapChannelsFlipped.expect("setTapChannelsFlipped")
+	}
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::newDelayLine`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::newDelayLine")]
+	fn new_delay_line(&self) -> FnNew { *sys::api!(sound.effect.delayline.newDelayLine) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::freeDelayLine`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::freeDelayLine")]
+	fn free_delay_line(&self) -> FnFree { *sys::api!(sound.effect.delayline.freeDelayLine) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::setLength`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setLength")]
+	fn set_length(&self) -> FnSetLength { *sys::api!(sound.effect.delayline.setLength) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::setFeedback`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setFeedback")]
+	fn set_feedback(&self) -> FnSetFeedback { *sys::api!(sound.effect.delayline.setFeedback) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::addTap`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::addTap")]
+	fn add_tap(&self) -> FnAddTap { *sys::api!(sound.effect.delayline.addTap) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::freeTap`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::freeTap")]
+	fn free_tap(&self) -> FnFreeTap { *sys::api!(sound.effect.delayline.freeTap) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::setTapDelay`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setTapDelay")]
+	fn set_tap_delay(&self) -> FnSetTapDelay { *sys::api!(sound.effect.delayline.setTapDelay) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::setTapDelayModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setTapDelayModulator")]
+	fn set_tap_delay_modulator(&self) -> FnSetTapDelayModulator {
+		*sys::api!(sound.effect.delayline.setTapDelayModulator)
+	}
+
+	/// Returns [`sys::ffi::playdate_sound_effect_delayline::setTapChannelsFlipped`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setTapChannelsFlipped")]
+	fn set_tap_channels_flipped(&self) -> FnSetTapChannelsFlipped {
+		*sys::api!(sound.effect.delayline.setTapChannelsFlipped)
+	}
+}
+
+
+type FnNew = unsafe extern "C" fn(length: c_int, stereo: c_int) -> *mut DelayLine;
+type FnFree = unsafe extern "C" fn(filter: *mut DelayLine);
+type FnSetLength = unsafe extern "C" fn(d: *mut DelayLine, frames: c_int);
+type FnSetFeedback = unsafe extern "C" fn(d: *mut DelayLine, feedback: c_float);
+type FnAddTap = unsafe extern "C" fn(d: *mut DelayLine, delay: c_int) -> *mut DelayLineTap;
+type FnFreeTap = unsafe extern "C" fn(tap: *mut DelayLineTap);
+type FnSetTapDelay = unsafe extern "C" fn(tap: *mut DelayLineTap, frames: c_int);
+type FnSetTapDelayModulator = unsafe extern "C" fn(tap: *mut DelayLineTap, signal: *mut PDSynthSignalValue);
+type FnSetTapChannelsFlipped = unsafe extern "C" fn(tap: *mut DelayLineTap, flip: c_int);
@@ -0,0 +1,208 @@
+//! Delay line sound effect
+
+use alloc::rc::Rc;
+use core::ffi::c_float;
+use core::ffi::c_int;
+
+use sys::ffi::DelayLine as OpaqueDelayLine;
+use sys::ffi::DelayLineTap as OpaqueDelayLineTap;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+use sys::ffi::SoundSource as OpaqueSoundSource;
+
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use crate::source::AsSoundSource;
+use super::AsSoundEffect;
+use super::Mix;
+
+pub mod api;
+
+
+/// Owned delay line, shared with its taps.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+struct Line<Api: api::Api>(*mut OpaqueDelayLine, Api);
+
+impl<Api: api::Api> Drop for Line<Api> {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			let f = self.1.free_delay_line();
+			unsafe { f(self.0) }
+			self.0 = core::ptr::null_mut();
+		}
+	}
+}
+
+
+/// Delay line effect.
+///
+/// Delayed signal can also be read by [taps](DelayLine::add_tap) at other positions,
+/// which are sound sources and should be added to a channel to be heard.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct DelayLine<Api: api::Api = api::Default> {
+	line: Rc<Line<Api>>,
+	mix: Mix,
+}
+
+
+// ctor //
+
+impl<Api> DelayLine<Api> where Api: api::Api {
+	/// Creates a new delay line effect with the given `length` in frames, `44100` per second.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::newDelayLine`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::newDelayLine")]
+	pub fn new(length: c_int, stereo: bool) -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default(), length, stereo)
+	}
+
+	/// Creates a new delay line effect with the given `length` in frames, `44100` per second.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::newDelayLine`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::newDelayLine")]
+	pub fn new_with(api: Api, length: c_int, stereo: bool) -> Result<Self, Error> {
+		let f = api.new_delay_line();
+		let ptr = unsafe { f(length, stereo as _) };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { line: Rc::new(Line(ptr, api)),
+			          mix: Default::default() })
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> DelayLine<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.line.1 }
+}
+
+impl<Api: api::Api> AsSoundEffect for DelayLine<Api> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { self.line.0 as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> DelayLine<Api> {
+	/// Sets the wet/dry mix for the effect, `0` is dry and `1` is wet.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	pub fn set_mix(&self, level: c_float) { self.mix.set_level(self.as_sound_effect(), level) }
+
+	/// Sets a signal to modulate the effect’s mix level. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	pub fn set_mix_modulator(&self, modulator: Option<Modulator>) {
+		self.mix.set_modulator(self.as_sound_effect(), modulator)
+	}
+
+	/// Returns the current mix modulator.
+	pub fn mix_modulator(&self) -> Option<Modulator> { self.mix.modulator() }
+
+	/// Changes the length of the delay line, clearing its contents.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::setLength`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setLength")]
+	pub fn set_length(&self, frames: c_int) {
+		let f = self.api().set_length();
+		unsafe { f(self.line.0, frames) }
+	}
+
+	/// Sets the feedback level of the delay line.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::setFeedback`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setFeedback")]
+	pub fn set_feedback(&self, feedback: c_float) {
+		let f = self.api().set_feedback();
+		unsafe { f(self.line.0, feedback) }
+	}
+
+	/// Returns a new tap on the delay line, at the given `delay` in frames.
+	///
+	/// `delay` must be less than or equal to the length of the delay line.
+	/// The tap keeps the delay line alive.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::addTap`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::addTap")]
+	pub fn add_tap(&self, delay: c_int) -> Result<DelayLineTap<Api>, Error> {
+		let f = self.api().add_tap();
+		let ptr = unsafe { f(self.line.0, delay) };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(DelayLineTap { ptr,
+			                  line: self.line.clone(),
+			                  delay: Default::default() })
+		}
+	}
+}
+
+
+/// Tap on a [`DelayLine`], a sound source playing the delayed signal.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct DelayLineTap<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueDelayLineTap,
+	line: Rc<Line<Api>>,
+	delay: Slot,
+}
+
+impl<Api: api::Api> Drop for DelayLineTap<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_tap();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+impl<Api: api::Api> AsSoundSource for DelayLineTap<Api> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.ptr as _ }
+}
+
+impl<Api: api::Api> DelayLineTap<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.line.1 }
+
+	/// Sets the position of the tap on the delay line, up to the delay line’s length.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::setTapDelay`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setTapDelay")]
+	pub fn set_delay(&self, frames: c_int) {
+		let f = self.api().set_tap_delay();
+		unsafe { f(self.ptr, frames) }
+	}
+
+	/// Sets a signal to modulate the tap delay. `None` clears the modulator.
+	///
+	/// If the signal is continuous (e.g. an envelope or a triangle LFO, but not a square LFO)
+	/// playback is sped up or slowed down to compress or expand time.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::setTapDelayModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setTapDelayModulator")]
+	pub fn set_delay_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_tap_delay_modulator();
+		self.delay
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current delay modulator.
+	pub fn delay_modulator(&self) -> Option<Modulator> { self.delay.get() }
+
+	/// If the delay line is stereo and `flipped` is `true`, the input of the left channel
+	/// is sent to the right channel of the tap and vice versa.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_delayline::setTapChannelsFlipped`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_delayline::setTapChannelsFlipped")]
+	pub fn set_channels_flipped(&self, flipped: bool) {
+		let f = self.api().set_tap_channels_flipped();
+		unsafe { f(self.ptr, flipped as _) }
+	}
+}
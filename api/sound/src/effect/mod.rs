@@ -0,0 +1,64 @@
+//! Playdate sound effects API
+//!
+//! Effects are inserted into a [`Channel`](crate::channel::Channel) and process everything playing on it.
+
+use alloc::rc::Rc;
+use core::ffi::c_float;
+
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+
+use crate::signal::Modulator;
+use crate::signal::Slot;
+
+pub mod api;
+pub mod bitcrusher;
+pub mod delay_line;
+pub mod one_pole_filter;
+pub mod overdrive;
+pub mod ring_modulator;
+pub mod two_pole_filter;
+
+pub use bitcrusher::BitCrusher;
+pub use delay_line::DelayLine;
+pub use delay_line::DelayLineTap;
+pub use one_pole_filter::OnePoleFilter;
+pub use overdrive::Overdrive;
+pub use ring_modulator::RingModulator;
+pub use two_pole_filter::TwoPoleFilter;
+
+
+/// Anything that can be inserted into a channel as an effect.
+pub trait AsSoundEffect {
+	/// Returns pointer to the underlying sound effect.
+	///
+	/// The pointer must remain valid while `self` is alive.
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect;
+}
+
+impl<T: AsSoundEffect + ?Sized> AsSoundEffect for Rc<T> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { (**self).as_sound_effect() }
+}
+
+
+/// Wet/dry mix of an effect, common for all effect types.
+#[derive(Default)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub(crate) struct Mix<Api: api::Api = api::Default> {
+	api: Api,
+	modulator: Slot,
+}
+
+impl<Api: api::Api> Mix<Api> {
+	pub fn set_level(&self, effect: *mut OpaqueSoundEffect, level: c_float) {
+		let f = self.api.set_mix();
+		unsafe { f(effect, level) }
+	}
+
+	pub fn set_modulator(&self, effect: *mut OpaqueSoundEffect, modulator: Option<Modulator>) {
+		let f = self.api.set_mix_modulator();
+		self.modulator
+		    .set(modulator, |modulator| unsafe { f(effect, modulator) })
+	}
+
+	pub fn modulator(&self) -> Option<Modulator> { self.modulator.get() }
+}
@@ -0,0 +1,87 @@
+use core::ffi::c_float;
+use core::ptr::NonNull;
+
+use sys::ffi::OnePoleFilter;
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::playdate_sound_effect_onepolefilter;
+
+
+/// Default one-pole filter api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached one-pole filter api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect_onepolefilter);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect.onepolefilter)) }
+}
+
+impl From<*const playdate_sound_effect_onepolefilter> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect_onepolefilter) -> Self {
+		Self(unsafe { ptr.as_ref() }.expect("onepolefilter"))
+	}
+}
+
+impl From<&'static playdate_sound_effect_onepolefilter> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect_onepolefilter) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect_onepolefilter>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect_onepolefilter>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect_onepolefilter>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect_onepolefilter>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_filter(&self) -> FnNew { self.0.newFilter.expect("newFilter") }
+	fn free_filter(&self) -> FnFree { self.0.freeFilter.expect("freeFilter") }
+	fn set_parameter(&self) -> FnSetFloat { self.0.setParameter.expect("setParameter") }
+	fn set_parameter_modulator(&self) -> FnSetModulator {
+		self.0.setParameterModulator.expect("setParameterModulator")
+	}
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect_onepolefilter::newFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::newFilter")]
+	fn new_filter(&self) -> FnNew { *sys::api!(sound.effect.onepolefilter.newFilter) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_onepolefilter::freeFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::freeFilter")]
+	fn free_filter(&self) -> FnFree { *sys::api!(sound.effect.onepolefilter.freeFilter) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_onepolefilter::setParameter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::setParameter")]
+	fn set_parameter(&self) -> FnSetFloat { *sys::api!(sound.effect.onepolefilter.setParameter) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_onepolefilter::setParameterModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::setParameterModulator")]
+	fn set_parameter_modulator(&self) -> FnSetModulator {
+		*sys::api!(sound.effect.onepolefilter.setParameterModulator)
+	}
+}
+
+
+type FnNew = unsafe extern "C" fn() -> *mut OnePoleFilter;
+type FnFree = unsafe extern "C" fn(filter: *mut OnePoleFilter);
+type FnSetFloat = unsafe extern "C" fn(filter: *mut OnePoleFilter, value: c_float);
+type FnSetModulator = unsafe extern "C" fn(filter: *mut OnePoleFilter, signal: *mut PDSynthSignalValue);
@@ -0,0 +1,124 @@
+//! One-pole filter sound effect
+
+use core::ffi::c_float;
+
+use sys::ffi::OnePoleFilter as OpaqueOnePoleFilter;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use super::AsSoundEffect;
+use super::Mix;
+
+pub mod api;
+
+
+/// Simple one-pole filter.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct OnePoleFilter<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueOnePoleFilter,
+	api: Api,
+	mix: Mix,
+	parameter: Slot,
+}
+
+
+// ctor //
+
+impl<Api> OnePoleFilter<Api> where Api: api::Api {
+	/// Creates a new one-pole filter.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_onepolefilter::newFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::newFilter")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new one-pole filter.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_onepolefilter::newFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::newFilter")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_filter();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          mix: Default::default(),
+			          parameter: Default::default() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for OnePoleFilter<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_filter();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> OnePoleFilter<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsSoundEffect for OnePoleFilter<Api> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> OnePoleFilter<Api> {
+	/// Sets the wet/dry mix for the effect, `0` is dry and `1` is wet.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	pub fn set_mix(&self, level: c_float) { self.mix.set_level(self.as_sound_effect(), level) }
+
+	/// Sets a signal to modulate the effect’s mix level. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	pub fn set_mix_modulator(&self, modulator: Option<Modulator>) {
+		self.mix.set_modulator(self.as_sound_effect(), modulator)
+	}
+
+	/// Returns the current mix modulator.
+	pub fn mix_modulator(&self) -> Option<Modulator> { self.mix.modulator() }
+
+	/// Sets the filter’s single parameter (cutoff frequency) to `parameter`, `-1..=1`.
+	///
+	/// Values above `0` (up to `1`) are high-pass, values below `0` (down to `-1`) are low-pass.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_onepolefilter::setParameter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::setParameter")]
+	pub fn set_parameter(&self, parameter: c_float) {
+		let f = self.api().set_parameter();
+		unsafe { f(self.ptr, parameter) }
+	}
+
+	/// Sets a signal to modulate the parameter. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_onepolefilter::setParameterModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_onepolefilter::setParameterModulator")]
+	pub fn set_parameter_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_parameter_modulator();
+		self.parameter
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current parameter modulator.
+	pub fn parameter_modulator(&self) -> Option<Modulator> { self.parameter.get() }
+}
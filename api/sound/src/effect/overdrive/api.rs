@@ -0,0 +1,98 @@
+use core::ffi::c_float;
+use core::ptr::NonNull;
+
+use sys::ffi::Overdrive;
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::playdate_sound_effect_overdrive;
+
+
+/// Default overdrive api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached overdrive api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect_overdrive);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect.overdrive)) }
+}
+
+impl From<*const playdate_sound_effect_overdrive> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect_overdrive) -> Self {
+		Self(unsafe { ptr.as_ref() }.expect("overdrive"))
+	}
+}
+
+impl From<&'static playdate_sound_effect_overdrive> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect_overdrive) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect_overdrive>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect_overdrive>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect_overdrive>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect_overdrive>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_overdrive(&self) -> FnNew { self.0.newOverdrive.expect("newOverdrive") }
+	fn free_overdrive(&self) -> FnFree { self.0.freeOverdrive.expect("freeOverdrive") }
+	fn set_gain(&self) -> FnSetFloat { self.0.setGain.expect("setGain") }
+	fn set_limit(&self) -> FnSetFloat { self.0.setLimit.expect("setLimit") }
+	fn set_limit_modulator(&self) -> FnSetModulator { self.0.setLimitModulator.expect("setLimitModulator") }
+	fn set_offset(&self) -> FnSetFloat { self.0.setOffset.expect("setOffset") }
+	fn set_offset_modulator(&self) -> FnSetModulator { self.0.setOffsetModulator.expect("setOffsetModulator") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::newOverdrive`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::newOverdrive")]
+	fn new_overdrive(&self) -> FnNew { *sys::api!(sound.effect.overdrive.newOverdrive) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::freeOverdrive`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::freeOverdrive")]
+	fn free_overdrive(&self) -> FnFree { *sys::api!(sound.effect.overdrive.freeOverdrive) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::setGain`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setGain")]
+	fn set_gain(&self) -> FnSetFloat { *sys::api!(sound.effect.overdrive.setGain) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::setLimit`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setLimit")]
+	fn set_limit(&self) -> FnSetFloat { *sys::api!(sound.effect.overdrive.setLimit) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::setLimitModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setLimitModulator")]
+	fn set_limit_modulator(&self) -> FnSetModulator { *sys::api!(sound.effect.overdrive.setLimitModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::setOffset`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setOffset")]
+	fn set_offset(&self) -> FnSetFloat { *sys::api!(sound.effect.overdrive.setOffset) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_overdrive::setOffsetModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setOffsetModulator")]
+	fn set_offset_modulator(&self) -> FnSetModulator { *sys::api!(sound.effect.overdrive.setOffsetModulator) }
+}
+
+
+type FnNew = unsafe extern "C" fn() -> *mut Overdrive;
+type FnFree = unsafe extern "C" fn(filter: *mut Overdrive);
+type FnSetFloat = unsafe extern "C" fn(filter: *mut Overdrive, value: c_float);
+type FnSetModulator = unsafe extern "C" fn(filter: *mut Overdrive, signal: *mut PDSynthSignalValue);
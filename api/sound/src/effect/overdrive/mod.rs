@@ -0,0 +1,155 @@
+//! Overdrive sound effect
+
+use core::ffi::c_float;
+
+use sys::ffi::Overdrive as OpaqueOverdrive;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use super::AsSoundEffect;
+use super::Mix;
+
+pub mod api;
+
+
+/// Overdrive effect, amplifies and clips the signal.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Overdrive<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueOverdrive,
+	api: Api,
+	mix: Mix,
+	limit: Slot,
+	offset: Slot,
+}
+
+
+// ctor //
+
+impl<Api> Overdrive<Api> where Api: api::Api {
+	/// Creates a new overdrive effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::newOverdrive`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::newOverdrive")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new overdrive effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::newOverdrive`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::newOverdrive")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_overdrive();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          mix: Default::default(),
+			          limit: Default::default(),
+			          offset: Default::default() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for Overdrive<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_overdrive();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Overdrive<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsSoundEffect for Overdrive<Api> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Overdrive<Api> {
+	/// Sets the wet/dry mix for the effect, `0` is dry and `1` is wet.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	pub fn set_mix(&self, level: c_float) { self.mix.set_level(self.as_sound_effect(), level) }
+
+	/// Sets a signal to modulate the effect’s mix level. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	pub fn set_mix_modulator(&self, modulator: Option<Modulator>) {
+		self.mix.set_modulator(self.as_sound_effect(), modulator)
+	}
+
+	/// Returns the current mix modulator.
+	pub fn mix_modulator(&self) -> Option<Modulator> { self.mix.modulator() }
+
+	/// Sets the gain of the overdrive effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::setGain`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setGain")]
+	pub fn set_gain(&self, gain: c_float) {
+		let f = self.api().set_gain();
+		unsafe { f(self.ptr, gain) }
+	}
+
+	/// Sets the level where the amplified input clips.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::setLimit`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setLimit")]
+	pub fn set_limit(&self, limit: c_float) {
+		let f = self.api().set_limit();
+		unsafe { f(self.ptr, limit) }
+	}
+
+	/// Sets a signal to modulate the limit. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::setLimitModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setLimitModulator")]
+	pub fn set_limit_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_limit_modulator();
+		self.limit
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current limit modulator.
+	pub fn limit_modulator(&self) -> Option<Modulator> { self.limit.get() }
+
+	/// Adds an offset to the upper and lower limits to create an asymmetric clipping.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::setOffset`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setOffset")]
+	pub fn set_offset(&self, offset: c_float) {
+		let f = self.api().set_offset();
+		unsafe { f(self.ptr, offset) }
+	}
+
+	/// Sets a signal to modulate the offset. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_overdrive::setOffsetModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_overdrive::setOffsetModulator")]
+	pub fn set_offset_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_offset_modulator();
+		self.offset
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current offset modulator.
+	pub fn offset_modulator(&self) -> Option<Modulator> { self.offset.get() }
+}
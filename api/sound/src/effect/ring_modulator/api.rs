@@ -0,0 +1,87 @@
+use core::ffi::c_float;
+use core::ptr::NonNull;
+
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::RingModulator;
+use sys::ffi::playdate_sound_effect_ringmodulator;
+
+
+/// Default ring modulator api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached ring modulator api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect_ringmodulator);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect.ringmodulator)) }
+}
+
+impl From<*const playdate_sound_effect_ringmodulator> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect_ringmodulator) -> Self {
+		Self(unsafe { ptr.as_ref() }.expect("ringmodulator"))
+	}
+}
+
+impl From<&'static playdate_sound_effect_ringmodulator> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect_ringmodulator) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect_ringmodulator>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect_ringmodulator>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect_ringmodulator>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect_ringmodulator>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_ringmod(&self) -> FnNew { self.0.newRingmod.expect("newRingmod") }
+	fn free_ringmod(&self) -> FnFree { self.0.freeRingmod.expect("freeRingmod") }
+	fn set_frequency(&self) -> FnSetFloat { self.0.setFrequency.expect("setFrequency") }
+	fn set_frequency_modulator(&self) -> FnSetModulator {
+		self.0.setFrequencyModulator.expect("setFrequencyModulator")
+	}
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect_ringmodulator::newRingmod`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::newRingmod")]
+	fn new_ringmod(&self) -> FnNew { *sys::api!(sound.effect.ringmodulator.newRingmod) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_ringmodulator::freeRingmod`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::freeRingmod")]
+	fn free_ringmod(&self) -> FnFree { *sys::api!(sound.effect.ringmodulator.freeRingmod) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_ringmodulator::setFrequency`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::setFrequency")]
+	fn set_frequency(&self) -> FnSetFloat { *sys::api!(sound.effect.ringmodulator.setFrequency) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_ringmodulator::setFrequencyModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::setFrequencyModulator")]
+	fn set_frequency_modulator(&self) -> FnSetModulator {
+		*sys::api!(sound.effect.ringmodulator.setFrequencyModulator)
+	}
+}
+
+
+type FnNew = unsafe extern "C" fn() -> *mut RingModulator;
+type FnFree = unsafe extern "C" fn(filter: *mut RingModulator);
+type FnSetFloat = unsafe extern "C" fn(filter: *mut RingModulator, value: c_float);
+type FnSetModulator = unsafe extern "C" fn(filter: *mut RingModulator, signal: *mut PDSynthSignalValue);
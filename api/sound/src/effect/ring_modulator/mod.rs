@@ -0,0 +1,122 @@
+//! Ring modulator sound effect
+
+use core::ffi::c_float;
+
+use sys::ffi::RingModulator as OpaqueRingModulator;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use super::AsSoundEffect;
+use super::Mix;
+
+pub mod api;
+
+
+/// Ring modulator effect, multiplies the signal by a sine wave.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct RingModulator<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueRingModulator,
+	api: Api,
+	mix: Mix,
+	frequency: Slot,
+}
+
+
+// ctor //
+
+impl<Api> RingModulator<Api> where Api: api::Api {
+	/// Creates a new ring modulator effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_ringmodulator::newRingmod`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::newRingmod")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new ring modulator effect.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_ringmodulator::newRingmod`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::newRingmod")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_ringmod();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          mix: Default::default(),
+			          frequency: Default::default() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for RingModulator<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_ringmod();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> RingModulator<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsSoundEffect for RingModulator<Api> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> RingModulator<Api> {
+	/// Sets the wet/dry mix for the effect, `0` is dry and `1` is wet.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	pub fn set_mix(&self, level: c_float) { self.mix.set_level(self.as_sound_effect(), level) }
+
+	/// Sets a signal to modulate the effect’s mix level. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	pub fn set_mix_modulator(&self, modulator: Option<Modulator>) {
+		self.mix.set_modulator(self.as_sound_effect(), modulator)
+	}
+
+	/// Returns the current mix modulator.
+	pub fn mix_modulator(&self) -> Option<Modulator> { self.mix.modulator() }
+
+	/// Sets the frequency of the modulation signal, in Hz.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_ringmodulator::setFrequency`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::setFrequency")]
+	pub fn set_frequency(&self, frequency: c_float) {
+		let f = self.api().set_frequency();
+		unsafe { f(self.ptr, frequency) }
+	}
+
+	/// Sets a signal to modulate the frequency. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_ringmodulator::setFrequencyModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_ringmodulator::setFrequencyModulator")]
+	pub fn set_frequency_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_frequency_modulator();
+		self.frequency
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current frequency modulator.
+	pub fn frequency_modulator(&self) -> Option<Modulator> { self.frequency.get() }
+}
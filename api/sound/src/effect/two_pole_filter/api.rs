@@ -0,0 +1,113 @@
+use core::ffi::c_float;
+use core::ptr::NonNull;
+
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::TwoPoleFilter;
+use sys::ffi::TwoPoleFilterType;
+use sys::ffi::playdate_sound_effect_twopolefilter;
+
+
+/// Default two-pole filter api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached two-pole filter api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_effect_twopolefilter);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.effect.twopolefilter)) }
+}
+
+impl From<*const playdate_sound_effect_twopolefilter> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_effect_twopolefilter) -> Self {
+		Self(unsafe { ptr.as_ref() }.expect("twopolefilter"))
+	}
+}
+
+impl From<&'static playdate_sound_effect_twopolefilter> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_effect_twopolefilter) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_effect_twopolefilter>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_effect_twopolefilter>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_effect_twopolefilter>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_effect_twopolefilter>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_filter(&self) -> FnNew { self.0.newFilter.expect("newFilter") }
+	fn free_filter(&self) -> FnFree { self.0.freeFilter.expect("freeFilter") }
+	fn set_type(&self) -> FnSetType { self.0.setType.expect("setType") }
+	fn set_frequency(&self) -> FnSetFloat { self.0.setFrequency.expect("setFrequency") }
+	fn set_frequency_modulator(&self) -> FnSetModulator {
+		self.0.setFrequencyModulator.expect("setFrequencyModulator")
+	}
+	fn set_gain(&self) -> FnSetFloat { self.0.setGain.expect("setGain") }
+	fn set_resonance(&self) -> FnSetFloat { self.0.setResonance.expect("setResonance") }
+	fn set_resonance_modulator(&self) -> FnSetModulator {
+		self.0.setResonanceModulator.expect("setResonanceModulator")
+	}
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::newFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::newFilter")]
+	fn new_filter(&self) -> FnNew { *sys::api!(sound.effect.twopolefilter.newFilter) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::freeFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::freeFilter")]
+	fn free_filter(&self) -> FnFree { *sys::api!(sound.effect.twopolefilter.freeFilter) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::setType`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setType")]
+	fn set_type(&self) -> FnSetType { *sys::api!(sound.effect.twopolefilter.setType) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::setFrequency`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setFrequency")]
+	fn set_frequency(&self) -> FnSetFloat { *sys::api!(sound.effect.twopolefilter.setFrequency) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::setFrequencyModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setFrequencyModulator")]
+	fn set_frequency_modulator(&self) -> FnSetModulator {
+		*sys::api!(sound.effect.twopolefilter.setFrequencyModulator)
+	}
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::setGain`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setGain")]
+	fn set_gain(&self) -> FnSetFloat { *sys::api!(sound.effect.twopolefilter.setGain) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::setResonance`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setResonance")]
+	fn set_resonance(&self) -> FnSetFloat { *sys::api!(sound.effect.twopolefilter.setResonance) }
+
+	/// Returns [`sys::ffi::playdate_sound_effect_twopolefilter::setResonanceModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setResonanceModulator")]
+	fn set_resonance_modulator(&self) -> FnSetModulator {
+		*sys::api!(sound.effect.twopolefilter.setResonanceModulator)
+	}
+}
+
+
+type FnNew = unsafe extern "C" fn() -> *mut TwoPoleFilter;
+type FnFree = unsafe extern "C" fn(filter: *mut TwoPoleFilter);
+type FnSetType = unsafe extern "C" fn(filter: *mut TwoPoleFilter, kind: TwoPoleFilterType);
+type FnSetFloat = unsafe extern "C" fn(filter: *mut TwoPoleFilter, value: c_float);
+type FnSetModulator = unsafe extern "C" fn(filter: *mut TwoPoleFilter, signal: *mut PDSynthSignalValue);
@@ -0,0 +1,167 @@
+//! Two-pole filter sound effect
+
+use core::ffi::c_float;
+
+use sys::ffi::TwoPoleFilter as OpaqueTwoPoleFilter;
+use sys::ffi::SoundEffect as OpaqueSoundEffect;
+
+use crate::error::Error;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use super::AsSoundEffect;
+use super::Mix;
+
+pub use sys::ffi::TwoPoleFilterType as FilterType;
+
+pub mod api;
+
+
+/// Two-pole filter of one of the [`FilterType`] kinds.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct TwoPoleFilter<Api: api::Api = api::Default> {
+	ptr: *mut OpaqueTwoPoleFilter,
+	api: Api,
+	mix: Mix,
+	frequency: Slot,
+	resonance: Slot,
+}
+
+
+// ctor //
+
+impl<Api> TwoPoleFilter<Api> where Api: api::Api {
+	/// Creates a new two-pole filter.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::newFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::newFilter")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new two-pole filter.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::newFilter`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::newFilter")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_filter();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          mix: Default::default(),
+			          frequency: Default::default(),
+			          resonance: Default::default() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for TwoPoleFilter<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_filter();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> TwoPoleFilter<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsSoundEffect for TwoPoleFilter<Api> {
+	fn as_sound_effect(&self) -> *mut OpaqueSoundEffect { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> TwoPoleFilter<Api> {
+	/// Sets the wet/dry mix for the effect, `0` is dry and `1` is wet.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMix`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMix")]
+	pub fn set_mix(&self, level: c_float) { self.mix.set_level(self.as_sound_effect(), level) }
+
+	/// Sets a signal to modulate the effect’s mix level. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect::setMixModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect::setMixModulator")]
+	pub fn set_mix_modulator(&self, modulator: Option<Modulator>) {
+		self.mix.set_modulator(self.as_sound_effect(), modulator)
+	}
+
+	/// Returns the current mix modulator.
+	pub fn mix_modulator(&self) -> Option<Modulator> { self.mix.modulator() }
+
+	/// Sets the type of the filter.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::setType`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setType")]
+	pub fn set_type(&self, kind: FilterType) {
+		let f = self.api().set_type();
+		unsafe { f(self.ptr, kind) }
+	}
+
+	/// Sets the center/corner frequency of the filter, in Hz.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::setFrequency`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setFrequency")]
+	pub fn set_frequency(&self, frequency: c_float) {
+		let f = self.api().set_frequency();
+		unsafe { f(self.ptr, frequency) }
+	}
+
+	/// Sets a signal to modulate the frequency. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::setFrequencyModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setFrequencyModulator")]
+	pub fn set_frequency_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_frequency_modulator();
+		self.frequency
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current frequency modulator.
+	pub fn frequency_modulator(&self) -> Option<Modulator> { self.frequency.get() }
+
+	/// Sets the filter gain, used by [`FilterType::kFilterTypePEQ`],
+	/// [`FilterType::kFilterTypeLowShelf`] and [`FilterType::kFilterTypeHighShelf`] filters.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::setGain`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setGain")]
+	pub fn set_gain(&self, gain: c_float) {
+		let f = self.api().set_gain();
+		unsafe { f(self.ptr, gain) }
+	}
+
+	/// Sets the filter resonance, `0..=1`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::setResonance`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setResonance")]
+	pub fn set_resonance(&self, resonance: c_float) {
+		let f = self.api().set_resonance();
+		unsafe { f(self.ptr, resonance) }
+	}
+
+	/// Sets a signal to modulate the resonance. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_effect_twopolefilter::setResonanceModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_effect_twopolefilter::setResonanceModulator")]
+	pub fn set_resonance_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_resonance_modulator();
+		self.resonance
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current resonance modulator.
+	pub fn resonance_modulator(&self) -> Option<Modulator> { self.resonance.get() }
+}
@@ -0,0 +1,131 @@
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ptr::NonNull;
+
+use sys::ffi::MIDINote;
+use sys::ffi::PDSynthEnvelope;
+use sys::ffi::playdate_sound_envelope;
+
+
+/// Default envelope api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached envelope api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_envelope);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.envelope)) }
+}
+
+impl From<*const playdate_sound_envelope> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_envelope) -> Self { Self(unsafe { ptr.as_ref() }.expect("envelope")) }
+}
+
+impl From<&'static playdate_sound_envelope> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_envelope) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_envelope>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_envelope>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_envelope>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_envelope>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_envelope(&self) -> FnNewEnvelope { self.0.newEnvelope.expect("newEnvelope") }
+	fn free_envelope(&self) -> FnFreeEnvelope { self.0.freeEnvelope.expect("freeEnvelope") }
+	fn set_attack(&self) -> FnSetFloat { self.0.setAttack.expect("setAttack") }
+	fn set_decay(&self) -> FnSetFloat { self.0.setDecay.expect("setDecay") }
+	fn set_sustain(&self) -> FnSetFloat { self.0.setSustain.expect("setSustain") }
+	fn set_release(&self) -> FnSetFloat { self.0.setRelease.expect("setRelease") }
+	fn set_legato(&self) -> FnSetFlag { self.0.setLegato.expect("setLegato") }
+	fn set_retrigger(&self) -> FnSetFlag { self.0.setRetrigger.expect("setRetrigger") }
+	fn get_value(&self) -> FnGetValue { self.0.getValue.expect("getValue") }
+	fn set_curvature(&self) -> FnSetFloat { self.0.setCurvature.expect("setCurvature") }
+	fn set_velocity_sensitivity(&self) -> FnSetFloat {
+		self.0.setVelocitySensitivity.expect("setVelocitySensitivity")
+	}
+	fn set_rate_scaling(&self) -> FnSetRateScaling { self.0.setRateScaling.expect("setRateScaling") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_envelope::newEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::newEnvelope")]
+	fn new_envelope(&self) -> FnNewEnvelope { *sys::api!(sound.envelope.newEnvelope) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::freeEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::freeEnvelope")]
+	fn free_envelope(&self) -> FnFreeEnvelope { *sys::api!(sound.envelope.freeEnvelope) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setAttack`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setAttack")]
+	fn set_attack(&self) -> FnSetFloat { *sys::api!(sound.envelope.setAttack) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setDecay`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setDecay")]
+	fn set_decay(&self) -> FnSetFloat { *sys::api!(sound.envelope.setDecay) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setSustain`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setSustain")]
+	fn set_sustain(&self) -> FnSetFloat { *sys::api!(sound.envelope.setSustain) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setRelease`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setRelease")]
+	fn set_release(&self) -> FnSetFloat { *sys::api!(sound.envelope.setRelease) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setLegato`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setLegato")]
+	fn set_legato(&self) -> FnSetFlag { *sys::api!(sound.envelope.setLegato) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setRetrigger`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setRetrigger")]
+	fn set_retrigger(&self) -> FnSetFlag { *sys::api!(sound.envelope.setRetrigger) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::getValue`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::getValue")]
+	fn get_value(&self) -> FnGetValue { *sys::api!(sound.envelope.getValue) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setCurvature`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setCurvature")]
+	fn set_curvature(&self) -> FnSetFloat { *sys::api!(sound.envelope.setCurvature) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setVelocitySensitivity`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setVelocitySensitivity")]
+	fn set_velocity_sensitivity(&self) -> FnSetFloat { *sys::api!(sound.envelope.setVelocitySensitivity) }
+
+	/// Returns [`sys::ffi::playdate_sound_envelope::setRateScaling`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setRateScaling")]
+	fn set_rate_scaling(&self) -> FnSetRateScaling { *sys::api!(sound.envelope.setRateScaling) }
+}
+
+
+type FnNewEnvelope = unsafe extern "C" fn(attack: c_float,
+                                          decay: c_float,
+                                          sustain: c_float,
+                                          release: c_float)
+                                          -> *mut PDSynthEnvelope;
+type FnFreeEnvelope = unsafe extern "C" fn(env: *mut PDSynthEnvelope);
+type FnSetFloat = unsafe extern "C" fn(env: *mut PDSynthEnvelope, value: c_float);
+type FnSetFlag = unsafe extern "C" fn(env: *mut PDSynthEnvelope, flag: c_int);
+type FnGetValue = unsafe extern "C" fn(env: *mut PDSynthEnvelope) -> c_float;
+type FnSetRateScaling =
+	unsafe extern "C" fn(env: *mut PDSynthEnvelope, scaling: c_float, start: MIDINote, end: MIDINote);
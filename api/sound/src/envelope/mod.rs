@@ -0,0 +1,201 @@
+//! Playdate sound envelope API
+
+use core::ffi::c_float;
+use core::mem::ManuallyDrop;
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use sys::ffi::MIDINote;
+use sys::ffi::PDSynthEnvelope;
+use sys::ffi::PDSynthSignalValue;
+
+use crate::error::Error;
+use crate::signal::SignalValue;
+
+pub mod api;
+
+
+/// ADSR envelope, can be used as a modulation source.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Envelope<Api: api::Api = api::Default>(pub(crate) *mut PDSynthEnvelope, Api);
+
+
+// ctor //
+
+impl<Api> Envelope<Api> where Api: api::Api {
+	/// Creates a new envelope with the given parameters.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::newEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::newEnvelope")]
+	pub fn new(attack: c_float, decay: c_float, sustain: c_float, release: c_float) -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default(), attack, decay, sustain, release)
+	}
+
+	/// Creates a new envelope with the given parameters.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::newEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::newEnvelope")]
+	pub fn new_with(api: Api,
+	                attack: c_float,
+	                decay: c_float,
+	                sustain: c_float,
+	                release: c_float)
+	                -> Result<Self, Error> {
+		let f = api.new_envelope();
+		let ptr = unsafe { f(attack, decay, sustain, release) };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self(ptr, api))
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for Envelope<Api> {
+	fn drop(&mut self) {
+		if !self.0.is_null() {
+			let f = self.api().free_envelope();
+			unsafe { f(self.0) }
+			self.0 = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Envelope<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.1 }
+}
+
+impl<Api: api::Api> SignalValue for Envelope<Api> {
+	fn signal_value(&self) -> *mut PDSynthSignalValue { self.0 as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Envelope<Api> {
+	/// Sets the envelope attack time to `attack`, in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setAttack`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setAttack")]
+	pub fn set_attack(&self, attack: c_float) {
+		let f = self.api().set_attack();
+		unsafe { f(self.0, attack) }
+	}
+
+	/// Sets the envelope decay time to `decay`, in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setDecay`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setDecay")]
+	pub fn set_decay(&self, decay: c_float) {
+		let f = self.api().set_decay();
+		unsafe { f(self.0, decay) }
+	}
+
+	/// Sets the envelope sustain level to `sustain`, as a proportion of the maximum.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setSustain`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setSustain")]
+	pub fn set_sustain(&self, sustain: c_float) {
+		let f = self.api().set_sustain();
+		unsafe { f(self.0, sustain) }
+	}
+
+	/// Sets the envelope release time to `release`, in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setRelease`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setRelease")]
+	pub fn set_release(&self, release: c_float) {
+		let f = self.api().set_release();
+		unsafe { f(self.0, release) }
+	}
+
+	/// Sets whether to use legato phrasing for the envelope.
+	///
+	/// If set, when the envelope is re-triggered before it’s released,
+	/// it remains in the sustain phase instead of jumping back to the attack phase.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setLegato`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setLegato")]
+	pub fn set_legato(&self, value: bool) {
+		let f = self.api().set_legato();
+		unsafe { f(self.0, value as _) }
+	}
+
+	/// If retrigger is on, the envelope always starts from 0 when a note starts playing,
+	/// instead of the current value if it’s active.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setRetrigger`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setRetrigger")]
+	pub fn set_retrigger(&self, value: bool) {
+		let f = self.api().set_retrigger();
+		unsafe { f(self.0, value as _) }
+	}
+
+	/// Returns the current output value of the envelope.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::getValue`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::getValue")]
+	pub fn value(&self) -> c_float {
+		let f = self.api().get_value();
+		unsafe { f(self.0) }
+	}
+
+	/// Smoothly changes the envelope’s shape from linear (`amount = 0`) to exponential (`amount = 1`).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setCurvature`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setCurvature")]
+	pub fn set_curvature(&self, amount: c_float) {
+		let f = self.api().set_curvature();
+		unsafe { f(self.0, amount) }
+	}
+
+	/// Changes the amount by which note velocity scales output level.
+	///
+	/// At the default value of `1`, output is proportional to velocity;
+	/// at `0` velocity has no effect on output level.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setVelocitySensitivity`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setVelocitySensitivity")]
+	pub fn set_velocity_sensitivity(&self, sensitivity: c_float) {
+		let f = self.api().set_velocity_sensitivity();
+		unsafe { f(self.0, sensitivity) }
+	}
+
+	/// Scales the envelope rate according to the played note.
+	///
+	/// For notes below `start`, the envelope’s set rate is used;
+	/// for notes above `end` envelope rates are scaled by the `scaling` parameter.
+	/// Between the two notes the scaling factor is interpolated from `1.0` to `scaling`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_envelope::setRateScaling`]
+	#[doc(alias = "sys::ffi::playdate_sound_envelope::setRateScaling")]
+	pub fn set_rate_scaling(&self, scaling: c_float, start: MIDINote, end: MIDINote) {
+		let f = self.api().set_rate_scaling();
+		unsafe { f(self.0, scaling, start, end) }
+	}
+}
+
+
+/// Envelope owned by another object, e.g. the amplitude envelope of a [`Synth`](crate::synth::Synth).
+///
+/// Is not freed on drop.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct EnvelopeRef<'owner, Api: api::Api = api::Default>(ManuallyDrop<Envelope<Api>>,
+                                                             PhantomData<&'owner ()>);
+
+impl<Api: api::Api> EnvelopeRef<'_, Api> {
+	pub(crate) fn from_with(api: Api, ptr: *mut PDSynthEnvelope) -> Self {
+		Self(ManuallyDrop::new(Envelope(ptr, api)), PhantomData)
+	}
+}
+
+impl<Api: api::Api> Deref for EnvelopeRef<'_, Api> {
+	type Target = Envelope<Api>;
+	fn deref(&self) -> &Self::Target { &self.0 }
+}
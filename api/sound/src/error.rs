@@ -14,6 +14,12 @@ pub enum Error {
 
 	/// Error caused by the file system.
 	Fs(fs::error::Error),
+
+	/// The sound engine rejected the given arguments.
+	InvalidArgument,
+
+	/// Audio data is malformed or in unsupported format.
+	InvalidData,
 }
 
 impl fmt::Display for Error {
@@ -22,6 +28,8 @@ impl fmt::Display for Error {
 			Error::Alloc => write!(f, "Snd: Allocation failed"),
 			Error::FileNotExist => write!(f, "Snd: File doesn't exist"),
 			Error::Fs(err) => err.fmt(f),
+			Error::InvalidArgument => write!(f, "Snd: Invalid argument"),
+			Error::InvalidData => write!(f, "Snd: Invalid or unsupported audio data"),
 		}
 	}
 }
@@ -0,0 +1,147 @@
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ptr::NonNull;
+
+use sys::ffi::MIDINote;
+use sys::ffi::PDSynth;
+use sys::ffi::PDSynthInstrument;
+use sys::ffi::playdate_sound_instrument;
+
+
+/// Default sound instrument api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached sound instrument api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_instrument);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.instrument)) }
+}
+
+impl From<*const playdate_sound_instrument> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_instrument) -> Self { Self(unsafe { ptr.as_ref() }.expect("instrument")) }
+}
+
+impl From<&'static playdate_sound_instrument> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_instrument) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_instrument>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_instrument>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_instrument>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_instrument>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_instrument(&self) -> FnNewInstrument { self.0.newInstrument.expect("newInstrument") }
+	fn free_instrument(&self) -> FnFreeInstrument { self.0.freeInstrument.expect("freeInstrument") }
+	fn add_voice(&self) -> FnAddVoice { self.0.addVoice.expect("addVoice") }
+	fn play_note(&self) -> FnPlayNote { self.0.playNote.expect("playNote") }
+	fn play_midi_note(&self) -> FnPlayMidiNote { self.0.playMIDINote.expect("playMIDINote") }
+	fn set_pitch_bend(&self) -> FnSetFloat { self.0.setPitchBend.expect("setPitchBend") }
+	fn set_pitch_bend_range(&self) -> FnSetFloat { self.0.setPitchBendRange.expect("setPitchBendRange") }
+	fn set_transpose(&self) -> FnSetFloat { self.0.setTranspose.expect("setTranspose") }
+	fn note_off(&self) -> FnNoteOff { self.0.noteOff.expect("noteOff") }
+	fn all_notes_off(&self) -> FnAllNotesOff { self.0.allNotesOff.expect("allNotesOff") }
+	fn set_volume(&self) -> FnSetVolume { self.0.setVolume.expect("setVolume") }
+	fn get_volume(&self) -> FnGetVolume { self.0.getVolume.expect("getVolume") }
+	fn active_voice_count(&self) -> FnActiveVoiceCount { self.0.activeVoiceCount.expect("activeVoiceCount") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_instrument::newInstrument`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::newInstrument")]
+	fn new_instrument(&self) -> FnNewInstrument { *sys::api!(sound.instrument.newInstrument) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::freeInstrument`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::freeInstrument")]
+	fn free_instrument(&self) -> FnFreeInstrument { *sys::api!(sound.instrument.freeInstrument) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::addVoice`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::addVoice")]
+	fn add_voice(&self) -> FnAddVoice { *sys::api!(sound.instrument.addVoice) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::playNote`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::playNote")]
+	fn play_note(&self) -> FnPlayNote { *sys::api!(sound.instrument.playNote) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::playMIDINote`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::playMIDINote")]
+	fn play_midi_note(&self) -> FnPlayMidiNote { *sys::api!(sound.instrument.playMIDINote) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::setPitchBend`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setPitchBend")]
+	fn set_pitch_bend(&self) -> FnSetFloat { *sys::api!(sound.instrument.setPitchBend) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::setPitchBendRange`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setPitchBendRange")]
+	fn set_pitch_bend_range(&self) -> FnSetFloat { *sys::api!(sound.instrument.setPitchBendRange) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::setTranspose`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setTranspose")]
+	fn set_transpose(&self) -> FnSetFloat { *sys::api!(sound.instrument.setTranspose) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::noteOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::noteOff")]
+	fn note_off(&self) -> FnNoteOff { *sys::api!(sound.instrument.noteOff) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::allNotesOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::allNotesOff")]
+	fn all_notes_off(&self) -> FnAllNotesOff { *sys::api!(sound.instrument.allNotesOff) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::setVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setVolume")]
+	fn set_volume(&self) -> FnSetVolume { *sys::api!(sound.instrument.setVolume) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::getVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::getVolume")]
+	fn get_volume(&self) -> FnGetVolume { *sys::api!(sound.instrument.getVolume) }
+
+	/// Returns [`sys::ffi::playdate_sound_instrument::activeVoiceCount`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::activeVoiceCount")]
+	fn active_voice_count(&self) -> FnActiveVoiceCount { *sys::api!(sound.instrument.activeVoiceCount) }
+}
+
+
+type FnNewInstrument = unsafe extern "C" fn() -> *mut PDSynthInstrument;
+type FnFreeInstrument = unsafe extern "C" fn(inst: *mut PDSynthInstrument);
+type FnAddVoice = unsafe extern "C" fn(inst: *mut PDSynthInstrument,
+                                       synth: *mut PDSynth,
+                                       range_start: MIDINote,
+                                       range_end: MIDINote,
+                                       transpose: c_float) -> c_int;
+type FnPlayNote = unsafe extern "C" fn(inst: *mut PDSynthInstrument,
+                                       frequency: c_float,
+                                       vel: c_float,
+                                       len: c_float,
+                                       when: u32) -> *mut PDSynth;
+type FnPlayMidiNote = unsafe extern "C" fn(inst: *mut PDSynthInstrument,
+                                           note: MIDINote,
+                                           vel: c_float,
+                                           len: c_float,
+                                           when: u32) -> *mut PDSynth;
+type FnSetFloat = unsafe extern "C" fn(inst: *mut PDSynthInstrument, value: c_float);
+type FnNoteOff = unsafe extern "C" fn(inst: *mut PDSynthInstrument, note: MIDINote, when: u32);
+type FnAllNotesOff = unsafe extern "C" fn(inst: *mut PDSynthInstrument, when: u32);
+type FnSetVolume = unsafe extern "C" fn(inst: *mut PDSynthInstrument, left: c_float, right: c_float);
+type FnGetVolume = unsafe extern "C" fn(inst: *mut PDSynthInstrument, left: *mut c_float, right: *mut c_float);
+type FnActiveVoiceCount = unsafe extern "C" fn(inst: *mut PDSynthInstrument) -> c_int;
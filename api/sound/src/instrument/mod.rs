@@ -0,0 +1,250 @@
+//! Playdate sound instrument API
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::RefCell;
+use core::ffi::c_float;
+use core::ops::RangeInclusive;
+
+use sys::ffi::MIDINote;
+use sys::ffi::PDSynthInstrument;
+use sys::ffi::SoundSource as OpaqueSoundSource;
+use sys::traits::AsRaw;
+
+use crate::error::Error;
+use crate::sample::Sample;
+use crate::source::AsSoundSource;
+use crate::synth::Synth;
+
+pub mod api;
+
+
+/// Instrument, a collection of synth voices playing notes,
+/// e.g. notes of a [`Track`](crate::sequence::Track).
+///
+/// Voices added to the instrument are kept alive while the instrument is alive.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Instrument<Api: api::Api = api::Default> {
+	ptr: *mut PDSynthInstrument,
+	api: Api,
+	voices: RefCell<Vec<Rc<dyn Any>>>,
+}
+
+
+// ctor //
+
+impl<Api> Instrument<Api> where Api: api::Api {
+	/// Creates a new instrument without voices.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::newInstrument`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::newInstrument")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new instrument without voices.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::newInstrument`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::newInstrument")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_instrument();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          voices: Default::default() })
+		}
+	}
+
+	/// Creates a new instrument with `polyphony` voices playing the `sample` for all notes.
+	pub fn with_sample<SApi>(sample: impl Into<Rc<Sample<SApi>>>, polyphony: usize) -> Result<Self, Error>
+		where Api: Default,
+		      SApi: crate::sample::api::Api + 'static {
+		let instrument = Self::new()?;
+		instrument.add_sample_voices(sample, polyphony, 0.0..=127.0, 0.0)?;
+		Ok(instrument)
+	}
+}
+
+
+impl<Api: api::Api> Drop for Instrument<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_instrument();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Instrument<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsRaw for Instrument<Api> {
+	type Type = PDSynthInstrument;
+	unsafe fn as_raw(&self) -> *mut Self::Type { self.ptr }
+}
+
+impl<Api: api::Api> AsSoundSource for Instrument<Api> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Instrument<Api> {
+	/// Adds the `synth` to the instrument.
+	///
+	/// The synth will respond to notes in the `notes` range, inclusive.
+	/// `transpose` is in half-step units, and is added to the instrument’s [transpose](Self::set_transpose).
+	///
+	/// Returns [`Error::InvalidArgument`] if the synth is already in another instrument or channel.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::addVoice`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::addVoice")]
+	pub fn add_voice<SApi>(&self,
+	                       synth: impl Into<Rc<Synth<SApi>>>,
+	                       notes: RangeInclusive<MIDINote>,
+	                       transpose: c_float)
+	                       -> Result<(), Error>
+		where SApi: crate::synth::api::Api + 'static
+	{
+		let synth = synth.into();
+		let f = self.api().add_voice();
+		let ptr = unsafe { synth.as_raw() };
+		if unsafe { f(self.ptr, ptr, *notes.start(), *notes.end(), transpose) } == 0 {
+			return Err(Error::InvalidArgument);
+		}
+		self.voices.borrow_mut().push(synth);
+		Ok(())
+	}
+
+	/// Adds `count` new voices playing the `sample` for notes in the `notes` range.
+	pub fn add_sample_voices<SApi>(&self,
+	                               sample: impl Into<Rc<Sample<SApi>>>,
+	                               count: usize,
+	                               notes: RangeInclusive<MIDINote>,
+	                               transpose: c_float)
+	                               -> Result<(), Error>
+		where SApi: crate::sample::api::Api + 'static
+	{
+		let sample = sample.into();
+		for _ in 0..count {
+			let synth = Synth::<crate::synth::api::Default>::new()?;
+			synth.set_sample(sample.clone(), None);
+			self.add_voice(synth, notes.clone(), transpose)?;
+		}
+		Ok(())
+	}
+
+	/// Returns the number of voices added to the instrument.
+	pub fn voice_count(&self) -> usize { self.voices.borrow().len() }
+
+	/// Plays a note with the given `frequency` on the voice that has been off for the longest,
+	/// or has been playing longest if all voices are currently playing.
+	///
+	/// See [`Synth::play_note`] for arguments.
+	///
+	/// Returns `false` if the instrument has no voice for the note.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::playNote`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::playNote")]
+	pub fn play_note(&self, frequency: c_float, velocity: c_float, length: Option<c_float>, when: u32) -> bool {
+		let f = self.api().play_note();
+		!unsafe { f(self.ptr, frequency, velocity, length.unwrap_or(-1.0), when) }.is_null()
+	}
+
+	/// Same as [`play_note`](Self::play_note), but uses MIDI note number, where `60` is C4.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::playMIDINote`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::playMIDINote")]
+	pub fn play_midi_note(&self, note: MIDINote, velocity: c_float, length: Option<c_float>, when: u32) -> bool {
+		let f = self.api().play_midi_note();
+		!unsafe { f(self.ptr, note, velocity, length.unwrap_or(-1.0), when) }.is_null()
+	}
+
+	/// Forwards the note off event to the voice currently playing the given `note`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::noteOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::noteOff")]
+	pub fn note_off(&self, note: MIDINote, when: u32) {
+		let f = self.api().note_off();
+		unsafe { f(self.ptr, note, when) }
+	}
+
+	/// Sends a note off event to all voices in the instrument.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::allNotesOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::allNotesOff")]
+	pub fn all_notes_off(&self, when: u32) {
+		let f = self.api().all_notes_off();
+		unsafe { f(self.ptr, when) }
+	}
+
+	/// Sets the pitch bend to be applied to the voices in the instrument,
+	/// in range `-1.0 - 1.0` of the [pitch bend range](Self::set_pitch_bend_range).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::setPitchBend`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setPitchBend")]
+	pub fn set_pitch_bend(&self, bend: c_float) {
+		let f = self.api().set_pitch_bend();
+		unsafe { f(self.ptr, bend) }
+	}
+
+	/// Sets the pitch bend range in half-steps.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::setPitchBendRange`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setPitchBendRange")]
+	pub fn set_pitch_bend_range(&self, half_steps: c_float) {
+		let f = self.api().set_pitch_bend_range();
+		unsafe { f(self.ptr, half_steps) }
+	}
+
+	/// Sets the transpose parameter for all voices in the instrument, in half-steps.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::setTranspose`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setTranspose")]
+	pub fn set_transpose(&self, half_steps: c_float) {
+		let f = self.api().set_transpose();
+		unsafe { f(self.ptr, half_steps) }
+	}
+
+	/// Gets the playback volume (`0.0` - `1.0`) for `left` and `right` channels of the instrument.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::getVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::getVolume")]
+	pub fn volume(&self) -> (c_float, c_float) {
+		let mut l = 0.;
+		let mut r = 0.;
+		let f = self.api().get_volume();
+		unsafe { f(self.ptr, &mut l, &mut r) };
+		(l, r)
+	}
+
+	/// Sets the playback volume (`0.0` - `1.0`) for `left` and `right` channels of the instrument.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::setVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::setVolume")]
+	pub fn set_volume(&self, left: c_float, right: c_float) {
+		let f = self.api().set_volume();
+		unsafe { f(self.ptr, left, right) }
+	}
+
+	/// Returns the number of voices in the instrument currently playing.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_instrument::activeVoiceCount`]
+	#[doc(alias = "sys::ffi::playdate_sound_instrument::activeVoiceCount")]
+	pub fn active_voice_count(&self) -> usize {
+		let f = self.api().active_voice_count();
+		unsafe { f(self.ptr) as _ }
+	}
+}
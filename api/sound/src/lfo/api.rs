@@ -0,0 +1,143 @@
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+use sys::ffi::LFOType;
+use sys::ffi::PDSynthLFO;
+use sys::ffi::playdate_sound_lfo;
+
+
+/// Default LFO api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached LFO api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_lfo);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.lfo)) }
+}
+
+impl From<*const playdate_sound_lfo> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_lfo) -> Self { Self(unsafe { ptr.as_ref() }.expect("lfo")) }
+}
+
+impl From<&'static playdate_sound_lfo> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_lfo) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_lfo>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_lfo>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_lfo>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_lfo>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_lfo(&self) -> FnNewLfo { self.0.newLFO.expect("newLFO") }
+	fn free_lfo(&self) -> FnFreeLfo { self.0.freeLFO.expect("freeLFO") }
+	fn set_type(&self) -> FnSetType { self.0.setType.expect("setType") }
+	fn set_rate(&self) -> FnSetFloat { self.0.setRate.expect("setRate") }
+	fn set_phase(&self) -> FnSetFloat { self.0.setPhase.expect("setPhase") }
+	fn set_start_phase(&self) -> FnSetFloat { self.0.setStartPhase.expect("setStartPhase") }
+	fn set_center(&self) -> FnSetFloat { self.0.setCenter.expect("setCenter") }
+	fn set_depth(&self) -> FnSetFloat { self.0.setDepth.expect("setDepth") }
+	fn set_arpeggiation(&self) -> FnSetArpeggiation { self.0.setArpeggiation.expect("setArpeggiation") }
+	fn set_function(&self) -> FnSetFunction { self.0.setFunction.expect("setFunction") }
+	fn set_delay(&self) -> FnSetDelay { self.0.setDelay.expect("setDelay") }
+	fn set_retrigger(&self) -> FnSetFlag { self.0.setRetrigger.expect("setRetrigger") }
+	fn set_global(&self) -> FnSetFlag { self.0.setGlobal.expect("setGlobal") }
+	fn get_value(&self) -> FnGetValue { self.0.getValue.expect("getValue") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_lfo::newLFO`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::newLFO")]
+	fn new_lfo(&self) -> FnNewLfo { *sys::api!(sound.lfo.newLFO) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::freeLFO`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::freeLFO")]
+	fn free_lfo(&self) -> FnFreeLfo { *sys::api!(sound.lfo.freeLFO) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setType`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setType")]
+	fn set_type(&self) -> FnSetType { *sys::api!(sound.lfo.setType) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setRate`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setRate")]
+	fn set_rate(&self) -> FnSetFloat { *sys::api!(sound.lfo.setRate) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setPhase`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setPhase")]
+	fn set_phase(&self) -> FnSetFloat { *sys::api!(sound.lfo.setPhase) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setStartPhase`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setStartPhase")]
+	fn set_start_phase(&self) -> FnSetFloat { *sys::api!(sound.lfo.setStartPhase) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setCenter`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setCenter")]
+	fn set_center(&self) -> FnSetFloat { *sys::api!(sound.lfo.setCenter) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setDepth`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setDepth")]
+	fn set_depth(&self) -> FnSetFloat { *sys::api!(sound.lfo.setDepth) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setArpeggiation`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setArpeggiation")]
+	fn set_arpeggiation(&self) -> FnSetArpeggiation { *sys::api!(sound.lfo.setArpeggiation) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setFunction`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setFunction")]
+	fn set_function(&self) -> FnSetFunction { *sys::api!(sound.lfo.setFunction) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setDelay`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setDelay")]
+	fn set_delay(&self) -> FnSetDelay { *sys::api!(sound.lfo.setDelay) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setRetrigger`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setRetrigger")]
+	fn set_retrigger(&self) -> FnSetFlag { *sys::api!(sound.lfo.setRetrigger) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::setGlobal`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setGlobal")]
+	fn set_global(&self) -> FnSetFlag { *sys::api!(sound.lfo.setGlobal) }
+
+	/// Returns [`sys::ffi::playdate_sound_lfo::getValue`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::getValue")]
+	fn get_value(&self) -> FnGetValue { *sys::api!(sound.lfo.getValue) }
+}
+
+
+type FnNewLfo = unsafe extern "C" fn(kind: LFOType) -> *mut PDSynthLFO;
+type FnFreeLfo = unsafe extern "C" fn(lfo: *mut PDSynthLFO);
+type FnSetType = unsafe extern "C" fn(lfo: *mut PDSynthLFO, kind: LFOType);
+type FnSetFloat = unsafe extern "C" fn(lfo: *mut PDSynthLFO, value: c_float);
+type FnSetArpeggiation = unsafe extern "C" fn(lfo: *mut PDSynthLFO, n_steps: c_int, steps: *mut c_float);
+type FnSetFunction = unsafe extern "C" fn(lfo: *mut PDSynthLFO,
+                                          function: Option<unsafe extern "C" fn(lfo: *mut PDSynthLFO,
+                                                                      userdata: *mut c_void)
+                                                                      -> c_float>,
+                                          userdata: *mut c_void,
+                                          interpolate: c_int);
+type FnSetDelay = unsafe extern "C" fn(lfo: *mut PDSynthLFO, holdoff: c_float, ramp_time: c_float);
+type FnSetFlag = unsafe extern "C" fn(lfo: *mut PDSynthLFO, flag: c_int);
+type FnGetValue = unsafe extern "C" fn(lfo: *mut PDSynthLFO) -> c_float;
@@ -0,0 +1,232 @@
+//! Playdate sound LFO API
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ffi::c_float;
+use core::ffi::c_void;
+
+use sys::ffi::PDSynthLFO;
+use sys::ffi::PDSynthSignalValue;
+
+use crate::error::Error;
+use crate::signal::SignalValue;
+
+pub use sys::ffi::LFOType as LfoType;
+
+pub mod api;
+
+
+type LfoFunction = Box<dyn FnMut() -> c_float + Send>;
+
+
+/// Low-frequency oscillator, can be used as a modulation source.
+pub struct Lfo<Api: api::Api = api::Default> {
+	ptr: *mut PDSynthLFO,
+	api: Api,
+	/// Steps of the arpeggio, kept while in use by the LFO.
+	steps: RefCell<Vec<c_float>>,
+	function: RefCell<Option<Box<LfoFunction>>>,
+}
+
+#[cfg(feature = "bindings-derive-debug")]
+impl<Api: api::Api + core::fmt::Debug> core::fmt::Debug for Lfo<Api> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Lfo")
+		 .field("ptr", &self.ptr)
+		 .field("api", &self.api)
+		 .field("steps", &self.steps)
+		 .field("function", &self.function.borrow().is_some())
+		 .finish()
+	}
+}
+
+
+// ctor //
+
+impl<Api> Lfo<Api> where Api: api::Api {
+	/// Creates a new LFO of the given `kind`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::newLFO`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::newLFO")]
+	pub fn new(kind: LfoType) -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default(), kind)
+	}
+
+	/// Creates a new LFO of the given `kind`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::newLFO`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::newLFO")]
+	pub fn new_with(api: Api, kind: LfoType) -> Result<Self, Error> {
+		let f = api.new_lfo();
+		let ptr = unsafe { f(kind) };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          steps: Default::default(),
+			          function: Default::default() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for Lfo<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_lfo();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Lfo<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> SignalValue for Lfo<Api> {
+	fn signal_value(&self) -> *mut PDSynthSignalValue { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Lfo<Api> {
+	/// Sets the LFO shape.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setType`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setType")]
+	pub fn set_type(&self, kind: LfoType) {
+		let f = self.api().set_type();
+		unsafe { f(self.ptr, kind) }
+	}
+
+	/// Sets the LFO’s rate, in cycles per second.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setRate`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setRate")]
+	pub fn set_rate(&self, rate: c_float) {
+		let f = self.api().set_rate();
+		unsafe { f(self.ptr, rate) }
+	}
+
+	/// Sets the LFO’s phase, from `0` to `1`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setPhase`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setPhase")]
+	pub fn set_phase(&self, phase: c_float) {
+		let f = self.api().set_phase();
+		unsafe { f(self.ptr, phase) }
+	}
+
+	/// Sets the LFO’s initial phase, from `0` to `1`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setStartPhase`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setStartPhase")]
+	pub fn set_start_phase(&self, phase: c_float) {
+		let f = self.api().set_start_phase();
+		unsafe { f(self.ptr, phase) }
+	}
+
+	/// Sets the center value for the LFO.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setCenter`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setCenter")]
+	pub fn set_center(&self, center: c_float) {
+		let f = self.api().set_center();
+		unsafe { f(self.ptr, center) }
+	}
+
+	/// Sets the depth of the LFO.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setDepth`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setDepth")]
+	pub fn set_depth(&self, depth: c_float) {
+		let f = self.api().set_depth();
+		unsafe { f(self.ptr, depth) }
+	}
+
+	/// Sets the LFO type to arpeggio, where the given `steps` are in half-steps from the center note.
+	///
+	/// For example, the sequence `[0, 4, 7, 12]` plays the notes of a major chord.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setArpeggiation`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setArpeggiation")]
+	pub fn set_arpeggiation(&self, steps: &[c_float]) {
+		let mut steps = steps.to_vec();
+		let f = self.api().set_arpeggiation();
+		unsafe { f(self.ptr, steps.len() as _, steps.as_mut_ptr()) }
+		self.steps.replace(steps);
+	}
+
+	/// Provides a custom function for LFO values, switching the LFO to [`LfoType::kLFOTypeFunction`].
+	///
+	/// If `interpolate` is `true`, values are interpolated between calls.
+	///
+	/// The `function` is called from the audio thread, so it must be [`Send`], quick and should not allocate.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setFunction`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setFunction")]
+	pub fn set_function<F: FnMut() -> c_float + Send + 'static>(&self, function: F, interpolate: bool) {
+		let mut function: Box<LfoFunction> = Box::new(Box::new(function));
+		let userdata = function.as_mut() as *mut LfoFunction as *mut c_void;
+
+		let f = self.api().set_function();
+		unsafe { f(self.ptr, Some(proxy_function), userdata, interpolate as _) }
+		self.function.replace(Some(function));
+	}
+
+	/// Sets an initial holdoff time for the LFO where the LFO remains at its center value,
+	/// and a ramp time where the value increases linearly to its maximum depth. Values are in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setDelay`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setDelay")]
+	pub fn set_delay(&self, holdoff: c_float, ramp_time: c_float) {
+		let f = self.api().set_delay();
+		unsafe { f(self.ptr, holdoff, ramp_time) }
+	}
+
+	/// If retrigger is on, the LFO’s phase is reset to its initial phase
+	/// when a synth using the LFO starts playing a note.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setRetrigger`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setRetrigger")]
+	pub fn set_retrigger(&self, value: bool) {
+		let f = self.api().set_retrigger();
+		unsafe { f(self.ptr, value as _) }
+	}
+
+	/// If `global` is set, the LFO is continuously updated whether or not it’s currently in use.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::setGlobal`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::setGlobal")]
+	pub fn set_global(&self, global: bool) {
+		let f = self.api().set_global();
+		unsafe { f(self.ptr, global as _) }
+	}
+
+	/// Returns the current output value of the LFO.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_lfo::getValue`]
+	#[doc(alias = "sys::ffi::playdate_sound_lfo::getValue")]
+	pub fn value(&self) -> c_float {
+		let f = self.api().get_value();
+		unsafe { f(self.ptr) }
+	}
+}
+
+
+unsafe extern "C" fn proxy_function(_: *mut PDSynthLFO, userdata: *mut c_void) -> c_float {
+	if let Some(function) = (userdata as *mut LfoFunction).as_mut() {
+		function()
+	} else {
+		0.0
+	}
+}
@@ -10,20 +10,39 @@ use sys::traits::AsRaw;
 extern crate sys;
 extern crate alloc;
 
+pub mod adpcm;
+pub mod aiff;
+pub mod audio;
+pub mod callback;
+pub mod channel;
+pub mod effect;
+pub mod envelope;
 pub mod error;
+pub mod instrument;
+pub mod lfo;
+pub mod mic;
+pub mod music;
+pub mod pcm;
 pub mod player;
 pub mod sample;
+pub mod sequence;
+pub mod sfx;
+pub mod signal;
 pub mod source;
-
-// TODO: Sound api: channel, synth, sequence, effect, lfo, envelope, callbacks, etc..
-
+pub mod spatial;
+pub mod synth;
+pub mod wav;
 
 pub mod prelude {
 	pub use crate::error::ApiError as SndApiError;
 	pub use crate::error::Error as SndError;
 
+	pub use crate::channel;
+	pub use crate::effect;
 	pub use crate::player;
 	pub use crate::sample;
+	pub use crate::sequence;
+	pub use crate::synth;
 }
 
 
@@ -115,6 +134,24 @@ impl<Api: api::Api> Sound<Api> {
 		}
 	}
 
+	/// Registers a closure called with headphone and microphone presence when either changes,
+	/// replacing a previously registered one.
+	///
+	/// While registered, audio output will not automatically switch from speaker to headphones,
+	/// the closure should use [`Sound::set_outputs_active`] to change the output if needed.
+	/// Automatic switching is restored when the returned [`HeadphoneCallback`](callback::HeadphoneCallback) is dropped.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound::getHeadphoneState`]
+	#[doc(alias = "sys::ffi::playdate_sound::getHeadphoneState")]
+	pub fn set_headphone_callback<F>(&self, callback: F) -> callback::HeadphoneCallback
+		where F: FnMut(bool, bool) + 'static {
+		let f = self.0.get_headphone_state();
+		callback::HeadphoneCallback::register(callback, |proxy| unsafe {
+			let null = core::ptr::null_mut();
+			f(null, null, Some(proxy))
+		})
+	}
+
 	/// Force audio output to the given outputs, regardless of headphone status.
 	///
 	/// Equivalent to [`sys::ffi::playdate_sound::setOutputsActive`]
@@ -145,6 +182,20 @@ impl<Api: api::Api> Sound<Api> {
 		unsafe { f(callback, context, stereo.into()) }.into()
 	}
 
+	/// Registers the [`AudioSource`](audio::AudioSource) to play on the default channel.
+	///
+	/// The source is rendered until the returned [`CallbackSource`](audio::CallbackSource) is dropped.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound::addSource`]
+	#[doc(alias = "sys::ffi::playdate_sound::addSource")]
+	pub fn add_audio_source<S>(&self, source: S, stereo: bool) -> Result<audio::CallbackSource<S>, error::Error>
+		where S: audio::AudioSource + Send + 'static {
+		let f = self.0.add_source();
+		audio::CallbackSource::register(source, stereo, |callback, context| unsafe {
+			f(callback, context, stereo.into())
+		})
+	}
+
 	/// Removes the given [`SoundSource`](source::SoundSource) object from its channel,
 	/// whether it’s in the default channel or a channel created with [`Sound::add_channel`].
 	///
@@ -158,46 +209,64 @@ impl<Api: api::Api> Sound<Api> {
 	}
 
 
-	// /// Returns the default channel, where sound sources play
-	// /// if they haven’t been explicitly assigned to a different channel.
-	// ///
-	// /// Equivalent to [`sys::ffi::playdate_sound::getDefaultChannel`]
-	// #[doc(alias = "sys::ffi::playdate_sound::getDefaultChannel")]
-	// pub fn default_channel(&self) -> *mut SoundChannel {
-	// 	let f = self.0.get_default_channel();
-	// 	unsafe { f() }
-	// }
-
-	// /// Adds the given channel to the sound engine.
-	// ///
-	// /// Returns 1 if the channel was added, 0 if it was already in the engine.
-	// ///
-	// /// Equivalent to [`sys::ffi::playdate_sound::addChannel`]
-	// #[doc(alias = "sys::ffi::playdate_sound::addChannel")]
-	// pub fn add_channel(&self, channel: *mut SoundChannel) -> c_int {
-	// 	let f = self.0.add_channel();
-	// 	unsafe { f(channel) }
-	// }
-
-	// /// Removes the given channel from the sound engine.
-	// ///
-	// /// Returns 1 if the channel was successfully removed, 0 if the channel is the default channel or hadn’t been previously added.
-	// ///
-	// /// Equivalent to [`sys::ffi::playdate_sound::removeChannel`]
-	// #[doc(alias = "sys::ffi::playdate_sound::removeChannel")]
-	// pub fn remove_channel(&self, channel: *mut SoundChannel) -> c_int {
-	// 	let f = self.0.remove_channel();
-	// 	unsafe { f(channel) }
-	// }
-
-	// /// The callback you pass in will be called every audio cycle.
-	// ///
-	// /// Equivalent to [`sys::ffi::playdate_sound::setMicCallback`]
-	// #[doc(alias = "sys::ffi::playdate_sound::setMicCallback")]
-	// pub fn set_mic_callback(&self, callback: RecordCallback, context: *mut c_void, force_internal: c_int) {
-	// 	let f = self.0.set_mic_callback();
-	// 	unsafe { f(callback, context, force_internal) }
-	// }
+	/// Returns the default channel, where sound sources play
+	/// if they haven’t been explicitly assigned to a different channel.
+	///
+	/// Returned [`Channel`](channel::Channel) doesn't own the default channel,
+	/// so it isn't freed on drop, only sources and effects added with it are removed.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound::getDefaultChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound::getDefaultChannel")]
+	pub fn default_channel(&self) -> channel::Channel {
+		let f = self.0.get_default_channel();
+		let ptr = unsafe { f() };
+		channel::Channel::from_with(Default::default(), ptr, false)
+	}
+
+	/// Adds the given channel to the sound engine.
+	///
+	/// Returns `true` if the channel was added, `false` if it was already in the engine.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound::addChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound::addChannel")]
+	pub fn add_channel<ChApi: channel::api::Api>(&self, channel: &channel::Channel<ChApi>) -> bool {
+		let f = self.0.add_channel();
+		let added = unsafe { f(channel.as_raw()) == 1 };
+		if added {
+			channel.set_engine(Some(self.0.remove_channel()));
+		}
+		added
+	}
+
+	/// Removes the given channel from the sound engine.
+	///
+	/// Returns `true` if the channel was successfully removed,
+	/// `false` if the channel is the default channel or hadn’t been previously added.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound::removeChannel`]
+	#[doc(alias = "sys::ffi::playdate_sound::removeChannel")]
+	pub fn remove_channel<ChApi: channel::api::Api>(&self, channel: &channel::Channel<ChApi>) -> bool {
+		let f = self.0.remove_channel();
+		channel.set_engine(None);
+		unsafe { f(channel.as_raw()) == 1 }
+	}
+
+	/// Starts recording from the microphone, the `input` is called every audio cycle with the recorded data.
+	///
+	/// `source` selects the microphone,
+	/// [`kMicInputAutodetect`](mic::MicSource::kMicInputAutodetect) uses a headset mic if one is detected,
+	/// otherwise the device microphone.
+	///
+	/// Recording stops when the returned [`Mic`](mic::Mic) is dropped or the `input` returns `false`.
+	/// Only one recording can be active, starting a new one replaces the previous.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound::setMicCallback`]
+	#[doc(alias = "sys::ffi::playdate_sound::setMicCallback")]
+	pub fn start_mic<I>(&self, input: I, source: mic::MicSource) -> Result<mic::Mic<I>, error::Error>
+		where I: mic::MicInput + Send + 'static {
+		let f = self.0.set_mic_callback();
+		mic::Mic::start(input, |callback, context| unsafe { f(callback, context, source) })
+	}
 }
 
 
@@ -0,0 +1,318 @@
+//! Playdate microphone API
+//!
+//! Recording is started with [`Sound::start_mic`](crate::Sound::start_mic),
+//! which calls the given [`MicInput`] every audio cycle with the recorded samples,
+//! mono 16-bit PCM at `44100` samples per second.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_void;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::Ordering;
+
+use sys::ffi::RecordCallback;
+use sys::ffi::SoundFormat;
+
+use fs::Path;
+
+use crate::audio::ring::Producer;
+use crate::audio::Param;
+use crate::error::ApiError;
+use crate::error::Error;
+use crate::sample::Sample;
+
+pub use sys::ffi::MicSource;
+
+
+/// Sample rate of the recorded audio.
+pub const SAMPLE_RATE: u32 = 44100;
+
+
+/// Receiver of the recorded audio.
+///
+/// The input is moved to the record callback, so it must be [`Send`] and `'static` to start recording.
+pub trait MicInput {
+	/// Receives the samples recorded during the audio cycle.
+	///
+	/// Returns `false` to stop recording.
+	///
+	/// Called from the audio callback, so it must not block or allocate.
+	fn record(&mut self, data: &[i16]) -> bool;
+}
+
+impl<F> MicInput for F where F: FnMut(&[i16]) -> bool {
+	fn record(&mut self, data: &[i16]) -> bool { self(data) }
+}
+
+/// Pushes recorded samples to the ring buffer, dropping ones that don't fit.
+impl MicInput for Producer<i16> {
+	fn record(&mut self, data: &[i16]) -> bool {
+		for sample in data {
+			if self.push(*sample).is_err() {
+				break;
+			}
+		}
+		true
+	}
+}
+
+
+/// Context of the running recording, only one can be active at a time.
+static ACTIVE: AtomicPtr<c_void> = AtomicPtr::new(core::ptr::null_mut());
+
+
+/// Record callback proxy, calls [`MicInput::record`].
+unsafe extern "C" fn proxy_record<I: MicInput>(context: *mut c_void, data: *mut i16, len: c_int) -> c_int {
+	let Some(input) = (context as *mut I).as_mut() else {
+		return 0;
+	};
+	let data = core::slice::from_raw_parts(data, len.max(0) as usize);
+	input.record(data) as _
+}
+
+
+/// Running microphone recording.
+///
+/// Recording is stopped on drop.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Mic<I: MicInput> {
+	input: *mut I,
+	source: MicSource,
+}
+
+
+// ctor //
+
+impl<I: MicInput + Send + 'static> Mic<I> {
+	/// Starts recording with `set` getting the record callback with its context
+	/// and returning the used source or `0` on error.
+	pub(crate) fn start(input: I, set: impl FnOnce(RecordCallback, *mut c_void) -> c_int) -> Result<Self, Error> {
+		let input = Box::into_raw(Box::new(input));
+		let source = match set(Some(proxy_record::<I>), input as _) {
+			1 => MicSource::kMicInputInternal,
+			2 => MicSource::kMicInputHeadset,
+			_ => {
+				drop(unsafe { Box::from_raw(input) });
+				return Err(Error::InvalidArgument);
+			},
+		};
+		ACTIVE.store(input as _, Ordering::Release);
+		Ok(Self { input, source })
+	}
+}
+
+
+impl<I: MicInput> Drop for Mic<I> {
+	fn drop(&mut self) {
+		if !self.input.is_null() {
+			self.unset();
+			drop(unsafe { Box::from_raw(self.input) });
+			self.input = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// impl //
+
+impl<I: MicInput> Mic<I> {
+	/// Returns the source used for recording, internal or headset microphone.
+	pub fn source(&self) -> MicSource { self.source.clone() }
+
+	/// Returns `true` if this recording wasn't replaced by another one.
+	pub fn is_active(&self) -> bool { ACTIVE.load(Ordering::Acquire) == self.input as *mut c_void }
+
+	/// Stops recording and returns the [`MicInput`].
+	pub fn stop(mut self) -> I {
+		self.unset();
+		let input = unsafe { Box::from_raw(self.input) };
+		self.input = core::ptr::null_mut();
+		*input
+	}
+
+	/// Removes the record callback if it's still ours.
+	fn unset(&self) {
+		use crate::api::Api;
+
+		let ours = self.input as *mut c_void;
+		if ACTIVE.compare_exchange(ours, core::ptr::null_mut(), Ordering::AcqRel, Ordering::Acquire)
+		         .is_ok()
+		{
+			let f = crate::api::Default.set_mic_callback();
+			unsafe { f(None, core::ptr::null_mut(), MicSource::kMicInputAutodetect) };
+		}
+	}
+}
+
+
+/// Level meter of the recorded audio.
+///
+/// Clones share the same levels, so one can be recording while another is read by the game loop.
+#[derive(Debug, Clone, Default)]
+pub struct Meter {
+	peak: Param,
+	rms: Param,
+}
+
+impl Meter {
+	pub fn new() -> Self { Self::default() }
+
+	/// Measures levels of the `data`.
+	pub fn measure(&self, data: &[i16]) {
+		if data.is_empty() {
+			return;
+		}
+		let mut peak = 0;
+		let mut sum = 0.0;
+		for sample in data {
+			peak = peak.max(sample.unsigned_abs());
+			let v = *sample as c_float / 32768.0;
+			sum += v * v;
+		}
+		self.peak.set(peak as c_float / 32768.0);
+		self.rms.set(libm::sqrtf(sum / data.len() as c_float));
+	}
+
+	/// Returns peak level of the last audio cycle, in range `0.0 - 1.0`.
+	pub fn peak(&self) -> c_float { self.peak.get() }
+
+	/// Returns RMS level of the last audio cycle, in range `0.0 - 1.0`.
+	pub fn rms(&self) -> c_float { self.rms.get() }
+
+	/// Returns RMS level of the last audio cycle in decibels full scale, `0.0` is the loudest.
+	pub fn rms_db(&self) -> c_float { to_db(self.rms()) }
+}
+
+impl MicInput for Meter {
+	fn record(&mut self, data: &[i16]) -> bool {
+		self.measure(data);
+		true
+	}
+}
+
+
+/// Converts `level` to decibels full scale, down to `-96.0`.
+pub fn to_db(level: c_float) -> c_float { (20.0 * libm::log10f(level)).max(-96.0) }
+
+
+/// Records samples into a preallocated buffer, stopping when it's full.
+#[derive(Debug)]
+pub struct Recorder {
+	data: Vec<i16>,
+	limit: usize,
+	meter: Option<Meter>,
+}
+
+impl Recorder {
+	/// Creates a recorder for up to `seconds` of audio.
+	pub fn new(seconds: c_float) -> Self {
+		let limit = (seconds.max(0.0) * SAMPLE_RATE as c_float) as usize;
+		Self { data: Vec::with_capacity(limit),
+		       limit,
+		       meter: None }
+	}
+
+	/// Measures levels of the recorded audio with the `meter`.
+	pub fn with_meter(mut self, meter: Meter) -> Self {
+		self.meter = Some(meter);
+		self
+	}
+
+	/// Returns recorded samples.
+	pub fn data(&self) -> &[i16] { &self.data }
+
+	/// Returns length of the recorded audio, in seconds.
+	pub fn length(&self) -> c_float { self.data.len() as c_float / SAMPLE_RATE as c_float }
+
+	/// Returns `true` if the buffer is full.
+	pub fn is_full(&self) -> bool { self.data.len() >= self.limit }
+
+	/// Returns recorded samples, consuming the recorder.
+	pub fn into_data(self) -> Vec<i16> { self.data }
+
+	/// Creates a new [`Sample`] with a copy of the recorded audio.
+	pub fn to_sample(&self) -> Result<Sample, Error> {
+		let bytes = unsafe { core::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.data.len() * 2) };
+		Sample::new_from_pcm(bytes, SoundFormat::kSound16bitMono, SAMPLE_RATE)
+	}
+
+	/// Returns the recorded audio encoded as WAV.
+	pub fn to_wav(&self) -> Vec<u8> { crate::wav::encode(&self.data, 1, SAMPLE_RATE) }
+
+	/// Writes the recorded audio as WAV file at `path` in the data directory.
+	pub fn write_wav<P: AsRef<Path>>(&self, path: P) -> Result<(), ApiError> {
+		fs::write(path, self.to_wav()).map_err(ApiError::from_err)
+	}
+}
+
+impl MicInput for Recorder {
+	fn record(&mut self, data: &[i16]) -> bool {
+		if let Some(meter) = &self.meter {
+			meter.measure(data);
+		}
+		let len = data.len().min(self.limit - self.data.len());
+		self.data.extend_from_slice(&data[..len]);
+		!self.is_full()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+
+	#[test]
+	fn meter_levels() {
+		let meter = Meter::new();
+		meter.measure(&[16384, -16384, 16384, -16384]);
+		assert_eq!(0.5, meter.peak());
+		assert_eq!(0.5, meter.rms());
+		assert!((meter.rms_db() + 6.0206).abs() < 0.001);
+
+		meter.measure(&[0, -32768, 0, 0]);
+		assert_eq!(1.0, meter.peak());
+		assert_eq!(0.5, meter.rms());
+
+		// empty cycle keeps the last levels
+		meter.measure(&[]);
+		assert_eq!(1.0, meter.peak());
+
+		meter.measure(&[0; 4]);
+		assert_eq!(0.0, meter.peak());
+		assert_eq!(-96.0, meter.rms_db());
+	}
+
+	#[test]
+	fn recorder_stops_when_full() {
+		let mut recorder = Recorder::new(4.0 / SAMPLE_RATE as c_float);
+		assert!(!recorder.is_full());
+		assert!(recorder.record(&[1, 2, 3]));
+		assert!(!recorder.record(&[4, 5, 6]));
+		assert!(recorder.is_full());
+		assert_eq!(&[1, 2, 3, 4], recorder.data());
+
+		// further cycles are dropped
+		assert!(!recorder.record(&[7]));
+		assert_eq!(4, recorder.data().len());
+	}
+
+	#[test]
+	fn recorder_meter() {
+		let meter = Meter::new();
+		let mut recorder = Recorder::new(1.0).with_meter(meter.clone());
+		recorder.record(&[-32768, 0]);
+		assert_eq!(1.0, meter.peak());
+		assert_eq!(2.0 / SAMPLE_RATE as c_float, recorder.length());
+	}
+
+	#[test]
+	fn empty_recorder() {
+		let mut recorder = Recorder::new(-1.0);
+		assert!(recorder.is_full());
+		assert!(!recorder.record(&[1, 2]));
+		assert!(recorder.data().is_empty());
+	}
+}
@@ -0,0 +1,767 @@
+//! Music manager streaming a playlist with two file players, crossfading between songs.
+//!
+//! The manager is polled, [`Music::update`] should be called every frame.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_float;
+
+use fs::Path;
+
+use crate::error::ApiError;
+use crate::error::Error;
+use crate::player::fp;
+use crate::player::Repeat;
+use crate::Sound;
+
+
+const SAMPLE_RATE: c_float = 44100.0;
+
+/// Time left in the intro when the body is started, about one frame.
+const INTRO_LEAD: c_float = 1.0 / 50.0;
+
+
+/// Song of a playlist, an optional intro followed by the body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Song {
+	/// File played once before the body.
+	pub intro: Option<String>,
+	/// Main file of the song.
+	pub body: String,
+	/// Loop region of the body in seconds, `end` of `0.0` is the end of the file.
+	///
+	/// The body is played from its start, so a region after the start makes an intro within the same file.
+	pub loop_range: Option<(c_float, c_float)>,
+	/// Loops the body until the song is changed, instead of advancing the playlist.
+	pub looping: bool,
+}
+
+impl Song {
+	/// Song playing the file at `path` once.
+	pub fn new(path: impl Into<String>) -> Self {
+		Self { intro: None,
+		       body: path.into(),
+		       loop_range: None,
+		       looping: false }
+	}
+
+	/// Song looping the file at `path`.
+	pub fn looping(path: impl Into<String>) -> Self {
+		Self { looping: true,
+		       ..Self::new(path) }
+	}
+
+	/// Song playing the `intro` file once, then looping the `body` file.
+	///
+	/// The body is preloaded on the second file player while the intro plays
+	/// and started about one frame before the intro ends, so they overlap instead of leaving a gap.
+	/// If the intro ends while the second player is still fading out the previous song,
+	/// the body is loaded after the intro and starts with a short gap.
+	pub fn with_intro(intro: impl Into<String>, body: impl Into<String>) -> Self {
+		Self { intro: Some(intro.into()),
+		       ..Self::looping(body) }
+	}
+
+	/// Loops the body between `start` and `end` seconds.
+	pub fn with_loop_range(mut self, start: c_float, end: c_float) -> Self {
+		self.loop_range = Some((start, end));
+		self.looping = true;
+		self
+	}
+}
+
+
+/// Volume settings of the music, can be saved to the data directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MusicSettings {
+	pub volume: c_float,
+	pub muted: bool,
+}
+
+impl Default for MusicSettings {
+	fn default() -> Self {
+		Self { volume: 1.0,
+		       muted: false }
+	}
+}
+
+impl MusicSettings {
+	const LEN: usize = 5;
+
+	pub fn to_bytes(&self) -> [u8; Self::LEN] {
+		let mut bytes = [0; Self::LEN];
+		bytes[..4].copy_from_slice(&self.volume.to_le_bytes());
+		bytes[4] = self.muted as u8;
+		bytes
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+		let bytes: &[u8; Self::LEN] = bytes.get(..Self::LEN)?.try_into().ok()?;
+		let volume = c_float::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+		(0.0..=1.0).contains(&volume).then_some(Self { volume,
+		                                               muted: bytes[4] != 0 })
+	}
+
+	/// Loads settings from the file at `path` in the data directory.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApiError> {
+		let bytes = fs::read(path, true).map_err(ApiError::from_err)?;
+		Self::from_bytes(&bytes).ok_or_else(|| Error::InvalidData.into())
+	}
+
+	/// Saves settings to the file at `path` in the data directory.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ApiError> {
+		fs::write(path, self.to_bytes()).map_err(ApiError::from_err)
+	}
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+	Idle,
+	Intro,
+	/// Body is loaded, waiting for the intro on the other deck to end.
+	Ready,
+	Body,
+}
+
+
+/// One of the two file players.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+struct Deck<Api: fp::api::Api> {
+	player: fp::Player<Api>,
+	song: Option<Song>,
+	phase: Phase,
+	/// Volume relative to the master volume.
+	gain: c_float,
+	/// Sound engine time to stop at, when fading out.
+	stop_at: Option<u32>,
+}
+
+impl<Api: fp::api::Api> Deck<Api> {
+	fn fade(&self, volume: c_float, seconds: c_float) {
+		if seconds > 0.0 {
+			let len = (seconds * SAMPLE_RATE) as i32;
+			self.player.fade_volume(volume, volume, len, None);
+		} else {
+			self.player.set_volume(volume, volume);
+		}
+	}
+
+	fn stop(&mut self) {
+		self.player.stop();
+		self.song = None;
+		self.phase = Phase::Idle;
+		self.stop_at = None;
+	}
+
+	fn remaining(&self) -> c_float { self.player.length() - self.player.offset() }
+
+	/// Loads and starts the body of the song.
+	fn play_body(&mut self) -> Result<(), ApiError> {
+		let Some(song) = &self.song else { return Ok(()) };
+		self.player.load_into_player(&song.body)?;
+		if let Some((start, end)) = song.loop_range {
+			self.player.set_loop_range(start, end);
+		}
+		self.start_body();
+		Ok(())
+	}
+
+	/// Loads the body of the `song` silenced, to be started with [`start_body`](Self::start_body).
+	fn load_body(&mut self, song: Song) -> Result<(), ApiError> {
+		self.player.set_volume(0.0, 0.0);
+		self.player.load_into_player(&song.body)?;
+		if let Some((start, end)) = song.loop_range {
+			self.player.set_loop_range(start, end);
+		}
+		self.song = Some(song);
+		self.phase = Phase::Ready;
+		self.gain = 0.0;
+		Ok(())
+	}
+
+	/// Starts the loaded body of the song.
+	fn start_body(&mut self) {
+		let repeat = if self.song.as_ref().is_some_and(|song| song.looping) {
+			Repeat::LoopsEndlessly
+		} else {
+			Repeat::Loops(1)
+		};
+		self.player.play(repeat);
+		self.phase = Phase::Body;
+	}
+}
+
+
+/// Music manager playing a playlist of streamed files.
+///
+/// Crossfades between songs with two file players, ducks music for dialogue
+/// and applies [`MusicSettings`].
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Music<Api: fp::api::Api = fp::api::Default> {
+	decks: [Deck<Api>; 2],
+	active: usize,
+	playlist: Vec<Song>,
+	current: Option<usize>,
+	/// Plays the next song of the playlist when the current one ends.
+	advance: bool,
+	repeat: bool,
+	crossfade: c_float,
+	duck: c_float,
+	settings: MusicSettings,
+	/// Source of the sound engine time.
+	clock: fn() -> u32,
+}
+
+
+// ctor //
+
+impl<Api: fp::api::Api> Music<Api> {
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default(), Api::default())
+	}
+
+	pub fn new_with(a: Api, b: Api) -> Result<Self, Error> {
+		let deck = |api| {
+			fp::Player::new_with(api).map(|player| {
+				                         Deck { player,
+				                                song: None,
+				                                phase: Phase::Idle,
+				                                gain: 0.0,
+				                                stop_at: None }
+			                         })
+		};
+		Ok(Self { decks: [deck(a)?, deck(b)?],
+		          active: 0,
+		          playlist: Vec::new(),
+		          current: None,
+		          advance: false,
+		          repeat: true,
+		          crossfade: 1.0,
+		          duck: 1.0,
+		          settings: Default::default(),
+		          clock: || Sound::Default().current_time() })
+	}
+}
+
+
+// impl //
+
+impl<Api: fp::api::Api> Music<Api> {
+	/// Replaces the playlist, current song keeps playing
+	/// and the new playlist starts from its first song when it ends.
+	pub fn set_playlist(&mut self, playlist: Vec<Song>) {
+		self.playlist = playlist;
+		self.current = None;
+		self.advance = true;
+	}
+
+	/// Appends the `song` to the playlist.
+	pub fn push(&mut self, song: Song) { self.playlist.push(song) }
+
+	pub fn playlist(&self) -> &[Song] { &self.playlist }
+
+	/// Returns index of the current song in the playlist.
+	pub fn current(&self) -> Option<usize> { self.current }
+
+	/// Returns the song currently playing.
+	pub fn song(&self) -> Option<&Song> { self.decks[self.active].song.as_ref() }
+
+	/// Sets whether the playlist starts over after the last song, `true` by default.
+	pub fn set_repeat(&mut self, repeat: bool) { self.repeat = repeat }
+
+	/// Sets crossfade duration between songs in seconds, `1.0` by default.
+	pub fn set_crossfade(&mut self, seconds: c_float) { self.crossfade = seconds.max(0.0) }
+
+	/// Returns `true` if a song is playing.
+	pub fn is_playing(&self) -> bool { self.decks[self.active].phase != Phase::Idle }
+
+	/// Plays the song at `index` of the playlist, crossfading from the current one.
+	pub fn play(&mut self, index: usize) -> Result<(), ApiError> {
+		let Some(song) = self.playlist.get(index).cloned() else {
+			return Err(Error::InvalidArgument.into());
+		};
+		self.current = Some(index);
+		self.advance = true;
+		self.start(song)
+	}
+
+	/// Plays the `song` outside of the playlist, crossfading from the current one.
+	pub fn play_song(&mut self, song: Song) -> Result<(), ApiError> {
+		self.current = None;
+		self.advance = false;
+		self.start(song)
+	}
+
+	/// Plays the next song of the playlist, returns `false` if there's none.
+	pub fn play_next(&mut self) -> Result<bool, ApiError> {
+		let next = match self.current {
+			Some(index) if index + 1 < self.playlist.len() => index + 1,
+			_ if self.repeat || self.current.is_none() => 0,
+			_ => return Ok(false),
+		};
+		if next >= self.playlist.len() {
+			return Ok(false);
+		}
+		self.play(next).map(|_| true)
+	}
+
+	/// Fades out the current song over `seconds` and stops it.
+	pub fn stop(&mut self, seconds: c_float) {
+		let now = (self.clock)();
+		for deck in self.decks.iter_mut().filter(|deck| deck.phase != Phase::Idle) {
+			deck.gain = 0.0;
+			deck.fade(0.0, seconds);
+			deck.stop_at = Some(now.wrapping_add((seconds * SAMPLE_RATE) as u32));
+		}
+		self.current = None;
+		self.advance = false;
+	}
+
+	fn start(&mut self, song: Song) -> Result<(), ApiError> {
+		let now = (self.clock)();
+		let master = self.master();
+		let crossfade = self.crossfade;
+
+		let out = &mut self.decks[self.active];
+		let fade_in = if out.phase != Phase::Idle {
+			out.gain = 0.0;
+			out.fade(0.0, crossfade);
+			out.stop_at = Some(now.wrapping_add((crossfade * SAMPLE_RATE) as u32));
+			crossfade
+		} else {
+			0.0
+		};
+
+		self.active = 1 - self.active;
+		let deck = &mut self.decks[self.active];
+		deck.stop();
+		deck.player.set_volume(0.0, 0.0);
+		deck.song = Some(song.clone());
+		match &song.intro {
+			Some(intro) => {
+				deck.player.load_into_player(intro)?;
+				deck.player.play(Repeat::Loops(1));
+				deck.phase = Phase::Intro;
+			},
+			None => deck.play_body()?,
+		}
+		deck.gain = 1.0;
+		deck.fade(master, fade_in);
+		Ok(())
+	}
+
+
+	/// Lowers the music to `level` of its volume over `seconds`, e.g. for dialogue.
+	pub fn duck(&mut self, level: c_float, seconds: c_float) {
+		self.duck = level.clamp(0.0, 1.0);
+		self.apply_master(seconds);
+	}
+
+	/// Restores the music volume after [`duck`](Self::duck) over `seconds`.
+	pub fn unduck(&mut self, seconds: c_float) { self.duck(1.0, seconds) }
+
+	pub fn settings(&self) -> MusicSettings { self.settings }
+
+	/// Applies the volume `settings`.
+	pub fn set_settings(&mut self, settings: MusicSettings) {
+		self.settings = settings;
+		self.apply_master(0.0);
+	}
+
+	pub fn set_volume(&mut self, volume: c_float) {
+		self.set_settings(MusicSettings { volume: volume.clamp(0.0, 1.0),
+		                                  ..self.settings })
+	}
+
+	pub fn set_muted(&mut self, muted: bool) {
+		self.set_settings(MusicSettings { muted,
+		                                  ..self.settings })
+	}
+
+	/// Volume applied to all songs.
+	fn master(&self) -> c_float {
+		if self.settings.muted {
+			0.0
+		} else {
+			self.settings.volume * self.duck
+		}
+	}
+
+	fn apply_master(&mut self, seconds: c_float) {
+		let master = self.master();
+		for deck in self.decks
+		                .iter()
+		                .filter(|deck| deck.phase != Phase::Idle && deck.gain > 0.0)
+		{
+			deck.fade(deck.gain * master, seconds);
+		}
+	}
+
+
+	/// Stops faded out songs, starts loops after intros and advances the playlist.
+	///
+	/// Should be called every frame.
+	pub fn update(&mut self) -> Result<(), ApiError> {
+		let now = (self.clock)();
+		for deck in self.decks.iter_mut() {
+			if deck.stop_at.is_some_and(|at| now.wrapping_sub(at) as i32 >= 0) {
+				deck.stop();
+			}
+		}
+
+		self.preload()?;
+
+		let crossfade = self.crossfade;
+		let deck = &mut self.decks[self.active];
+		match deck.phase {
+			Phase::Intro if !deck.player.is_playing() || deck.remaining() <= INTRO_LEAD => self.end_intro(now)?,
+			Phase::Body if deck.song.as_ref().is_some_and(|song| !song.looping) => {
+				let ending = !deck.player.is_playing() || deck.remaining() <= crossfade;
+				if ending {
+					if !deck.player.is_playing() {
+						deck.stop();
+					}
+					if self.advance && !self.play_next()? {
+						self.current = None;
+						self.advance = false;
+					}
+				}
+			},
+			_ => {},
+		}
+		Ok(())
+	}
+
+	/// Loads the body of the song playing its intro on the idle deck.
+	fn preload(&mut self) -> Result<(), ApiError> {
+		let [a, b] = &mut self.decks;
+		let (deck, idle) = if self.active == 0 { (a, b) } else { (b, a) };
+		match &deck.song {
+			Some(song) if deck.phase == Phase::Intro && idle.phase == Phase::Idle => idle.load_body(song.clone()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Starts the body of the song after its intro, on the preloaded deck if there is one.
+	///
+	/// The intro plays out its last frame and stops.
+	fn end_intro(&mut self, now: u32) -> Result<(), ApiError> {
+		if self.decks[1 - self.active].phase != Phase::Ready {
+			return self.decks[self.active].play_body();
+		}
+		let intro = &mut self.decks[self.active];
+		intro.stop_at = Some(now.wrapping_add((intro.remaining().max(0.0) * SAMPLE_RATE) as u32));
+		let (volume, _) = intro.player.volume();
+		let gain = intro.gain;
+
+		self.active = 1 - self.active;
+		let target = gain * self.master();
+		let deck = &mut self.decks[self.active];
+		deck.gain = gain;
+		deck.player.set_volume(volume, volume);
+		deck.start_body();
+		if volume != target {
+			deck.fade(target, self.crossfade);
+		}
+		Ok(())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use core::cell::Cell;
+	use alloc::boxed::Box;
+	use core::ffi::CStr;
+	use core::ffi::c_char;
+	use core::ffi::c_int;
+	use core::ffi::c_void;
+	use sys::ffi::FilePlayer;
+	use sys::ffi::sndCallbackProc;
+
+	use crate::source::AsSoundSource;
+	use super::*;
+
+
+	std::thread_local! {
+		static NOW: Cell<u32> = const { Cell::new(0) };
+	}
+
+	fn clock() -> u32 { NOW.with(Cell::get) }
+
+	fn wait(seconds: c_float) { NOW.with(|now| now.set(now.get() + (seconds * SAMPLE_RATE) as u32)) }
+
+
+	/// State of a fake file player, files named `intro*` are 2 seconds long, others 10 seconds.
+	#[derive(Debug, Default)]
+	struct FakePlayer {
+		path: String,
+		playing: bool,
+		repeat: c_int,
+		offset: c_float,
+		volume: c_float,
+	}
+
+	/// File player end-point keeping [`FakePlayer`]s behind the player pointers.
+	#[derive(Debug, Clone, Copy, Default)]
+	struct Fake;
+
+	fn fake(ptr: *mut FilePlayer) -> &'static mut FakePlayer { unsafe { &mut *(ptr as *mut FakePlayer) } }
+
+	impl fp::api::Api for Fake {
+		fn new_player(&self) -> unsafe extern "C" fn() -> *mut FilePlayer {
+			unsafe extern "C" fn new() -> *mut FilePlayer { Box::into_raw(Box::<FakePlayer>::default()) as _ }
+			new
+		}
+
+		fn free_player(&self) -> unsafe extern "C" fn(player: *mut FilePlayer) {
+			unsafe extern "C" fn free(player: *mut FilePlayer) { drop(Box::from_raw(player as *mut FakePlayer)) }
+			free
+		}
+
+		fn load_into_player(&self) -> unsafe extern "C" fn(player: *mut FilePlayer, path: *const c_char) -> c_int {
+			unsafe extern "C" fn load(player: *mut FilePlayer, path: *const c_char) -> c_int {
+				let player = fake(player);
+				player.path = CStr::from_ptr(path).to_str().unwrap().into();
+				player.playing = false;
+				player.offset = 0.0;
+				1
+			}
+			load
+		}
+
+		fn play(&self) -> unsafe extern "C" fn(player: *mut FilePlayer, repeat: c_int) -> c_int {
+			unsafe extern "C" fn play(player: *mut FilePlayer, repeat: c_int) -> c_int {
+				let player = fake(player);
+				player.playing = true;
+				player.repeat = repeat;
+				1
+			}
+			play
+		}
+
+		fn is_playing(&self) -> unsafe extern "C" fn(player: *mut FilePlayer) -> c_int {
+			unsafe extern "C" fn is_playing(player: *mut FilePlayer) -> c_int { fake(player).playing as _ }
+			is_playing
+		}
+
+		fn stop(&self) -> unsafe extern "C" fn(player: *mut FilePlayer) {
+			unsafe extern "C" fn stop(player: *mut FilePlayer) { fake(player).playing = false }
+			stop
+		}
+
+		fn set_volume(&self) -> unsafe extern "C" fn(player: *mut FilePlayer, left: c_float, right: c_float) {
+			unsafe extern "C" fn set_volume(player: *mut FilePlayer, left: c_float, _: c_float) {
+				fake(player).volume = left
+			}
+			set_volume
+		}
+
+		fn get_volume(&self)
+		              -> unsafe extern "C" fn(player: *mut FilePlayer, left: *mut c_float, right: *mut c_float) {
+			unsafe extern "C" fn get_volume(player: *mut FilePlayer, left: *mut c_float, right: *mut c_float) {
+				*left = fake(player).volume;
+				*right = fake(player).volume;
+			}
+			get_volume
+		}
+
+		fn get_length(&self) -> unsafe extern "C" fn(player: *mut FilePlayer) -> c_float {
+			unsafe extern "C" fn get_length(player: *mut FilePlayer) -> c_float {
+				if fake(player).path.starts_with("intro") {
+					2.0
+				} else {
+					10.0
+				}
+			}
+			get_length
+		}
+
+		fn get_offset(&self) -> unsafe extern "C" fn(player: *mut FilePlayer) -> c_float {
+			unsafe extern "C" fn get_offset(player: *mut FilePlayer) -> c_float { fake(player).offset }
+			get_offset
+		}
+
+		fn fade_volume(
+			&self)
+			-> unsafe extern "C" fn(player: *mut FilePlayer,
+			                        left: c_float,
+			                        right: c_float,
+			                        len: i32,
+			                        finish_callback: sndCallbackProc,
+			                        userdata: *mut c_void) {
+			unsafe extern "C" fn fade_volume(player: *mut FilePlayer,
+			                                 left: c_float,
+			                                 _: c_float,
+			                                 _: i32,
+			                                 _: sndCallbackProc,
+			                                 _: *mut c_void) {
+				fake(player).volume = left
+			}
+			fade_volume
+		}
+	}
+
+
+	fn music(playlist: Vec<Song>) -> Music<Fake> {
+		let mut music = Music::new_with(Fake, Fake).unwrap();
+		music.clock = clock;
+		music.set_playlist(playlist);
+		music
+	}
+
+	/// Fake player of the deck.
+	fn deck(music: &Music<Fake>, index: usize) -> &'static mut FakePlayer {
+		fake(music.decks[index].player.as_sound_source() as _)
+	}
+
+	/// Plays the active deck until `seconds` before its end.
+	fn near_end(music: &Music<Fake>, seconds: c_float) {
+		let player = deck(music, music.active);
+		player.offset = if player.path.starts_with("intro") {
+			2.0
+		} else {
+			10.0
+		} - seconds;
+	}
+
+	/// Plays the active deck to its end.
+	fn finish(music: &Music<Fake>) {
+		near_end(music, 0.0);
+		deck(music, music.active).playing = false;
+	}
+
+
+	#[test]
+	fn intro_to_body() {
+		let mut music = music(Vec::new());
+		music.play_song(Song::with_intro("intro", "body")).unwrap();
+		let intro = music.active;
+		assert_eq!(
+		           ("intro", Phase::Intro),
+		           (deck(&music, intro).path.as_str(), music.decks[intro].phase)
+		);
+
+		// body is preloaded silenced on the other deck
+		music.update().unwrap();
+		let body = 1 - intro;
+		assert_eq!("body", deck(&music, body).path);
+		assert_eq!(
+		           (Phase::Ready, false),
+		           (music.decks[body].phase, deck(&music, body).playing)
+		);
+		assert_eq!(0.0, deck(&music, body).volume);
+
+		// body starts before the intro ends, looping at the intro volume
+		near_end(&music, 0.01);
+		music.update().unwrap();
+		assert_eq!(body, music.active);
+		assert_eq!(
+		           (Phase::Body, true, 0),
+		           (
+			music.decks[body].phase,
+			deck(&music, body).playing,
+			deck(&music, body).repeat
+		)
+		);
+		assert_eq!(1.0, deck(&music, body).volume);
+		assert!(deck(&music, intro).playing);
+
+		// intro plays out and stops
+		wait(0.02);
+		music.update().unwrap();
+		assert_eq!(Phase::Idle, music.decks[intro].phase);
+		assert!(!deck(&music, intro).playing);
+		assert_eq!(Some("body"), music.song().map(|song| song.body.as_str()));
+	}
+
+	#[test]
+	fn crossfade_and_repeat() {
+		let mut music = music(vec![Song::new("a"), Song::new("b")]);
+		music.play(0).unwrap();
+		let first = music.active;
+		music.update().unwrap();
+		assert_eq!(
+		           (Some(0), "a"),
+		           (music.current(), deck(&music, first).path.as_str())
+		);
+
+		// next song fades in on the other deck, while the previous fades out
+		near_end(&music, 0.5);
+		music.update().unwrap();
+		let second = 1 - first;
+		assert_eq!((Some(1), second), (music.current(), music.active));
+		assert_eq!(
+		           ("b", true, 1),
+		           (
+			deck(&music, second).path.as_str(),
+			deck(&music, second).playing,
+			deck(&music, second).repeat
+		)
+		);
+		assert_eq!(0.0, deck(&music, first).volume);
+		assert!(deck(&music, first).playing);
+		wait(1.0);
+		music.update().unwrap();
+		assert!(!deck(&music, first).playing);
+
+		// playlist starts over
+		finish(&music);
+		music.update().unwrap();
+		assert_eq!(
+		           (Some(0), "a"),
+		           (music.current(), deck(&music, music.active).path.as_str())
+		);
+
+		// or ends after the last song
+		music.set_repeat(false);
+		music.play(1).unwrap();
+		finish(&music);
+		music.update().unwrap();
+		assert_eq!(None, music.current());
+		assert!(!music.is_playing());
+	}
+
+	#[test]
+	fn next_playlist() {
+		let mut music = music(vec![Song::new("a")]);
+		music.set_repeat(false);
+		music.play(0).unwrap();
+
+		// current song keeps playing, then the new playlist starts
+		music.set_playlist(vec![Song::new("c"), Song::new("d")]);
+		music.update().unwrap();
+		assert_eq!(
+		           (None, "a"),
+		           (music.current(), deck(&music, music.active).path.as_str())
+		);
+		finish(&music);
+		music.update().unwrap();
+		assert_eq!(
+		           (Some(0), "c"),
+		           (music.current(), deck(&music, music.active).path.as_str())
+		);
+
+		// songs outside the playlist don't advance it
+		music.play_song(Song::new("e")).unwrap();
+		finish(&music);
+		music.update().unwrap();
+		assert!(!music.is_playing());
+	}
+
+
+	#[test]
+	fn settings_bytes() {
+		let settings = MusicSettings { volume: 0.5,
+		                               muted: true };
+		assert_eq!(Some(settings), MusicSettings::from_bytes(&settings.to_bytes()));
+		assert_eq!(None, MusicSettings::from_bytes(&[0; 3]));
+		assert_eq!(
+		           None,
+		           MusicSettings::from_bytes(&MusicSettings { volume: 2.0,
+		                                                      ..settings }.to_bytes())
+		);
+	}
+}
@@ -0,0 +1,80 @@
+//! Owned PCM audio buffers.
+
+use alloc::vec::Vec;
+
+use sys::ffi::SoundFormat;
+
+
+/// Samples of a [`PcmBuffer`], interleaved if stereo.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PcmData {
+	/// Signed 8-bit samples.
+	I8(Vec<i8>),
+	/// Signed 16-bit samples.
+	I16(Vec<i16>),
+}
+
+
+/// Owned PCM audio, e.g. generated or decoded from [WAV](crate::wav) or [AIFF](crate::aiff).
+///
+/// Create a sample playing it with [`Sample::new_from_buffer`](crate::sample::Sample::new_from_buffer).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcmBuffer {
+	pub data: PcmData,
+	pub stereo: bool,
+	pub sample_rate: u32,
+}
+
+impl PcmBuffer {
+	pub fn mono8(data: Vec<i8>, sample_rate: u32) -> Self { Self::new(PcmData::I8(data), false, sample_rate) }
+	pub fn stereo8(data: Vec<i8>, sample_rate: u32) -> Self { Self::new(PcmData::I8(data), true, sample_rate) }
+	pub fn mono16(data: Vec<i16>, sample_rate: u32) -> Self { Self::new(PcmData::I16(data), false, sample_rate) }
+	pub fn stereo16(data: Vec<i16>, sample_rate: u32) -> Self { Self::new(PcmData::I16(data), true, sample_rate) }
+
+	pub fn new(data: PcmData, stereo: bool, sample_rate: u32) -> Self {
+		Self { data,
+		       stereo,
+		       sample_rate }
+	}
+
+	/// Returns the [`SoundFormat`] of the data.
+	pub fn format(&self) -> SoundFormat {
+		match (&self.data, self.stereo) {
+			(PcmData::I8(_), false) => SoundFormat::kSound8bitMono,
+			(PcmData::I8(_), true) => SoundFormat::kSound8bitStereo,
+			(PcmData::I16(_), false) => SoundFormat::kSound16bitMono,
+			(PcmData::I16(_), true) => SoundFormat::kSound16bitStereo,
+		}
+	}
+
+	/// Returns number of channels, `1` or `2`.
+	pub fn channels(&self) -> usize { 1 + self.stereo as usize }
+
+	/// Returns number of frames, samples per channel.
+	pub fn frames(&self) -> usize {
+		let len = match &self.data {
+			PcmData::I8(data) => data.len(),
+			PcmData::I16(data) => data.len(),
+		};
+		len / self.channels()
+	}
+
+	/// Returns length in seconds.
+	pub fn length(&self) -> f32 { self.frames() as f32 / self.sample_rate as f32 }
+
+	/// Returns the data as bytes in native byte order, as the sound engine expects.
+	pub fn as_bytes(&self) -> &[u8] {
+		match &self.data {
+			PcmData::I8(data) => unsafe { core::slice::from_raw_parts(data.as_ptr() as *const u8, data.len()) },
+			PcmData::I16(data) => unsafe { core::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * 2) },
+		}
+	}
+
+	/// Returns the data converted to 16-bit samples.
+	pub fn to_i16(&self) -> Vec<i16> {
+		match &self.data {
+			PcmData::I8(data) => data.iter().map(|v| (*v as i16) << 8).collect(),
+			PcmData::I16(data) => data.clone(),
+		}
+	}
+}
@@ -1,3 +1,4 @@
+use core::cell::RefCell;
 use core::ffi::c_char;
 use core::ffi::c_float;
 use core::ffi::c_int;
@@ -6,18 +7,30 @@ use core::ffi::c_void;
 use sys::ffi::CString;
 use sys::ffi::FilePlayer;
 use sys::ffi::sndCallbackProc;
+use sys::ffi::SoundSource as OpaqueSoundSource;
 
 use fs::Path;
 
 use super::Repeat;
+use crate::callback::Callback;
 use crate::error::ApiError;
 use crate::error::Error;
+use crate::source::AsSoundSource;
 
 pub mod api;
 
 
 #[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
-pub struct Player<Api: api::Api = api::Default>(*mut FilePlayer, Api);
+pub struct Player<Api: api::Api = api::Default>(*mut FilePlayer, Api, Callbacks);
+
+
+/// Closures registered as player callbacks.
+#[derive(Debug, Default)]
+struct Callbacks {
+	finish: RefCell<Option<Callback>>,
+	looping: RefCell<Option<Callback>>,
+	fade: RefCell<Option<Callback>>,
+}
 
 
 // ctor //
@@ -35,7 +48,7 @@ impl<Api> Player<Api> where Api: api::Api {
 		if player.is_null() {
 			Err(Error::Alloc)
 		} else {
-			Ok(Player(player, api))
+			Ok(Player(player, api, Default::default()))
 		}
 	}
 }
@@ -60,6 +73,10 @@ impl<Api: api::Api> Player<Api> {
 	pub fn api(&self) -> &Api { &self.1 }
 }
 
+impl<Api: api::Api> AsSoundSource for Player<Api> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.0 as _ }
+}
+
 
 // impl //
 
@@ -222,8 +239,6 @@ impl<Api> Player<Api> where Api: api::Api {
 
 	// callbacks //
 
-	// TODO: rustify this functions
-
 	/// Sets a function to be called when playback has completed.
 	///
 	/// This is an alias for [`sys::ffi::playdate_sound_source::setFinishCallback`].
@@ -232,30 +247,76 @@ impl<Api> Player<Api> where Api: api::Api {
 	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::setFinishCallback")]
 	pub fn set_finish_callback(&self, callback: sndCallbackProc) {
 		let f = self.api().set_finish_callback();
-		// TODO: use userdata
-		unsafe { f(self.0, callback, core::ptr::null_mut()) }
+		unsafe { f(self.0, callback, core::ptr::null_mut()) };
+		self.2.finish.take();
 	}
 
 	/// Equivalent to [setLoopCallback](sys::ffi::playdate_sound_fileplayer::setLoopCallback)
 	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::setLoopCallback")]
 	pub fn set_loop_callback(&self, callback: sndCallbackProc) {
 		let f = self.api().set_loop_callback();
-		// TODO: use userdata
-		unsafe { f(self.0, callback, core::ptr::null_mut()) }
+		unsafe { f(self.0, callback, core::ptr::null_mut()) };
+		self.2.looping.take();
+	}
+
+	/// Sets a closure to be called when playback has completed,
+	/// replacing the previous one.
+	///
+	/// The closure is owned by the player and removed with it.
+	///
+	/// Equivalent to [setFinishCallback](sys::ffi::playdate_sound_fileplayer::setFinishCallback)
+	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::setFinishCallback")]
+	pub fn set_finish_callback_with<F: FnMut() + 'static>(&self, callback: F) {
+		let callback = Callback::new(callback);
+		let f = self.api().set_finish_callback();
+		unsafe { f(self.0, Callback::PROC, callback.userdata()) };
+		self.2.finish.replace(Some(callback));
+	}
+
+	/// Sets a closure to be called every time the player loops,
+	/// replacing the previous one.
+	///
+	/// The closure is owned by the player and removed with it.
+	///
+	/// Equivalent to [setLoopCallback](sys::ffi::playdate_sound_fileplayer::setLoopCallback)
+	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::setLoopCallback")]
+	pub fn set_loop_callback_with<F: FnMut() + 'static>(&self, callback: F) {
+		let callback = Callback::new(callback);
+		let f = self.api().set_loop_callback();
+		unsafe { f(self.0, Callback::PROC, callback.userdata()) };
+		self.2.looping.replace(Some(callback));
 	}
 
+	/// Removes the finish callback.
+	pub fn clear_finish_callback(&self) { self.set_finish_callback(None) }
+
+	/// Removes the loop callback.
+	pub fn clear_loop_callback(&self) { self.set_loop_callback(None) }
+
 	/// Changes the volume of the [`Player`] to `left` and `right` over a length of `len` sample frames,
 	/// then calls the provided `callback` (if set).
 	///
 	/// Equivalent to [fadeVolume](sys::ffi::playdate_sound_fileplayer::fadeVolume)
 	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::fadeVolume")]
-	// Probably here we can use just FnOnce, because it will dropped after call by proxy.
 	pub fn fade_volume(&self, left: c_float, right: c_float, len: i32, finish_callback: sndCallbackProc) {
 		let f = self.api().fade_volume();
-		// TODO: use userdata
 		unsafe { f(self.0, left, right, len, finish_callback, core::ptr::null_mut()) }
 	}
 
+	/// Changes the volume of the [`Player`] to `left` and `right` over a length of `len` sample frames,
+	/// then calls the `callback`.
+	///
+	/// The closure is kept until the next fade or until the player is dropped.
+	///
+	/// Equivalent to [fadeVolume](sys::ffi::playdate_sound_fileplayer::fadeVolume)
+	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::fadeVolume")]
+	pub fn fade_volume_with<F: FnMut() + 'static>(&self, left: c_float, right: c_float, len: i32, callback: F) {
+		let callback = Callback::new(callback);
+		let f = self.api().fade_volume();
+		unsafe { f(self.0, left, right, len, Callback::PROC, callback.userdata()) };
+		self.2.fade.replace(Some(callback));
+	}
+
 	/// Equivalent to [setMP3StreamSource](sys::ffi::playdate_sound_fileplayer::setMP3StreamSource)
 	#[doc(alias = "sys::ffi::playdate_sound_fileplayer::setMP3StreamSource")]
 	pub fn set_mp3_stream_source(&self,
@@ -1,10 +1,14 @@
+use core::cell::RefCell;
 use core::ffi::c_float;
 use core::ffi::c_int;
 
 use sys::ffi::SamplePlayer;
 use sys::ffi::sndCallbackProc;
+use sys::ffi::SoundSource as OpaqueSoundSource;
 
+use crate::callback::Callback;
 use crate::error::Error;
+use crate::source::AsSoundSource;
 use super::Repeat;
 
 
@@ -12,7 +16,15 @@ pub mod api;
 
 
 #[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
-pub struct Player<Api: api::Api = api::Default>(*mut SamplePlayer, Api);
+pub struct Player<Api: api::Api = api::Default>(*mut SamplePlayer, Api, Callbacks);
+
+
+/// Closures registered as player callbacks.
+#[derive(Debug, Default)]
+struct Callbacks {
+	finish: RefCell<Option<Callback>>,
+	looping: RefCell<Option<Callback>>,
+}
 
 
 // ctor //
@@ -38,7 +50,7 @@ impl<Api> Player<Api> where Api: api::Api {
 		if player.is_null() {
 			Err(Error::Alloc)
 		} else {
-			Ok(Player(player, api))
+			Ok(Player(player, api, Default::default()))
 		}
 	}
 }
@@ -63,6 +75,10 @@ impl<Api: api::Api> Player<Api> {
 	pub fn api(&self) -> &Api { &self.1 }
 }
 
+impl<Api: api::Api> AsSoundSource for Player<Api> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.0 as _ }
+}
+
 
 // impl //
 
@@ -197,24 +213,53 @@ impl<Api> Player<Api> where Api: api::Api {
 	///
 	/// Equivalent to [setFinishCallback](sys::ffi::playdate_sound_sampleplayer::setFinishCallback)
 	#[doc(alias = "sys::ffi::playdate_sound_sampleplayer::setFinishCallback")]
-	// TODO: Rustify this function, maybe impl like it done for sprites.
-	// My idea is to store user-callback into self,
-	// and here use proxy "extern C" function.
-	// Maybe move self into the wrapper with user-callback?
-	// We're need so store user-callback somewhere like StackedMap(stacked_type_map) or ErasedSet by type.
-	// Type of `F: FnMut(*mut SoundSource)` is unique, so we can do it.
-	// But with cost of memory - one static for each `F`*`Self`, so so much.
 	pub fn set_finish_callback_raw(&self, callback: sndCallbackProc) -> Result<(), Error> {
 		let f = self.api().set_finish_callback();
-		// TODO: use userdata
-		Ok(unsafe { f(self.0, callback, core::ptr::null_mut()) })
+		unsafe { f(self.0, callback, core::ptr::null_mut()) };
+		self.2.finish.take();
+		Ok(())
 	}
 
 	/// Equivalent to [setLoopCallback](sys::ffi::playdate_sound_sampleplayer::setLoopCallback)
 	#[doc(alias = "sys::ffi::playdate_sound_sampleplayer::setLoopCallback")]
 	pub fn set_loop_callback_raw(&self, callback: sndCallbackProc) -> Result<(), Error> {
 		let f = self.api().set_loop_callback();
-		// TODO: use userdata
-		Ok(unsafe { f(self.0, callback, core::ptr::null_mut()) })
+		unsafe { f(self.0, callback, core::ptr::null_mut()) };
+		self.2.looping.take();
+		Ok(())
 	}
+
+	/// Sets a closure to be called when playback has completed,
+	/// replacing the previous one.
+	///
+	/// The closure is owned by the player and removed with it.
+	///
+	/// Equivalent to [setFinishCallback](sys::ffi::playdate_sound_sampleplayer::setFinishCallback)
+	#[doc(alias = "sys::ffi::playdate_sound_sampleplayer::setFinishCallback")]
+	pub fn set_finish_callback<F: FnMut() + 'static>(&self, callback: F) {
+		let callback = Callback::new(callback);
+		let f = self.api().set_finish_callback();
+		unsafe { f(self.0, Callback::PROC, callback.userdata()) };
+		self.2.finish.replace(Some(callback));
+	}
+
+	/// Sets a closure to be called every time the sample loops,
+	/// replacing the previous one.
+	///
+	/// The closure is owned by the player and removed with it.
+	///
+	/// Equivalent to [setLoopCallback](sys::ffi::playdate_sound_sampleplayer::setLoopCallback)
+	#[doc(alias = "sys::ffi::playdate_sound_sampleplayer::setLoopCallback")]
+	pub fn set_loop_callback<F: FnMut() + 'static>(&self, callback: F) {
+		let callback = Callback::new(callback);
+		let f = self.api().set_loop_callback();
+		unsafe { f(self.0, Callback::PROC, callback.userdata()) };
+		self.2.looping.replace(Some(callback));
+	}
+
+	/// Removes the finish callback.
+	pub fn clear_finish_callback(&self) { self.set_finish_callback_raw(None).ok(); }
+
+	/// Removes the loop callback.
+	pub fn clear_loop_callback(&self) { self.set_loop_callback_raw(None).ok(); }
 }
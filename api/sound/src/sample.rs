@@ -12,6 +12,7 @@ use sys::ffi::SoundFormat;
 
 use crate::error::ApiError;
 use crate::error::Error;
+use crate::pcm::PcmBuffer;
 
 
 #[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
@@ -76,6 +77,44 @@ impl<Api: Default + api::Api> Sample<Api> {
 		let api: Api = Default::default();
 		Self::new_from_data_with(api, data, format, sample_rate)
 	}
+
+	/// Returns a new [`Sample`] with a copy of the given audio data.
+	///
+	/// The copy is owned by the sample and freed with it.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sample::newSampleFromData`]
+	#[doc(alias = "sys::ffi::playdate_sound_sample::newSampleFromData")]
+	pub fn new_from_pcm(data: &[u8], format: SoundFormat, sample_rate: u32) -> Result<Self, Error> {
+		let api: Api = Default::default();
+		Self::new_from_pcm_with(api, data, format, sample_rate)
+	}
+
+	/// Returns a new [`Sample`] with a copy of the `buffer`.
+	///
+	/// Uses [`sys::ffi::playdate_sound_sample::newSampleFromData`]
+	#[inline(always)]
+	pub fn new_from_buffer(buffer: &PcmBuffer) -> Result<Self, Error> {
+		let api: Api = Default::default();
+		Self::new_from_buffer_with(api, buffer)
+	}
+
+	/// Returns a new [`Sample`] with audio decoded from WAV file `bytes`.
+	///
+	/// See [`wav::decode`](crate::wav::decode) for supported formats.
+	#[inline(always)]
+	pub fn new_from_wav(bytes: &[u8]) -> Result<Self, Error> {
+		let api: Api = Default::default();
+		Self::new_from_wav_with(api, bytes)
+	}
+
+	/// Returns a new [`Sample`] with audio decoded from AIFF file `bytes`.
+	///
+	/// See [`aiff::decode`](crate::aiff::decode) for supported formats.
+	#[inline(always)]
+	pub fn new_from_aiff(bytes: &[u8]) -> Result<Self, Error> {
+		let api: Api = Default::default();
+		Self::new_from_aiff_with(api, bytes)
+	}
 }
 
 
@@ -148,6 +187,51 @@ impl<Api: api::Api> Sample<Api> {
 			Ok(SampleWithData(Self(ptr, api), data))
 		}
 	}
+
+	/// Returns a new [`Sample`] with a copy of the given audio data.
+	///
+	/// The copy is allocated by the system and freed by the sound engine with the sample.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sample::newSampleFromData`]
+	#[doc(alias = "sys::ffi::playdate_sound_sample::newSampleFromData")]
+	pub fn new_from_pcm_with(api: Api, data: &[u8], format: SoundFormat, sample_rate: u32) -> Result<Self, Error> {
+		let realloc = *sys::api!(system.realloc);
+		let buf = unsafe { realloc(core::ptr::null_mut(), data.len()) } as *mut u8;
+		if buf.is_null() {
+			return Err(Error::Alloc);
+		}
+		unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), buf, data.len()) };
+
+		let f = api.new_sample_from_data();
+		let ptr = unsafe { f(buf, format, sample_rate, data.len() as _, 1) };
+		if ptr.is_null() {
+			unsafe { realloc(buf as _, 0) };
+			Err(Error::Alloc)
+		} else {
+			Ok(Self(ptr, api))
+		}
+	}
+
+	/// Returns a new [`Sample`] with a copy of the `buffer`.
+	///
+	/// Uses [`sys::ffi::playdate_sound_sample::newSampleFromData`]
+	pub fn new_from_buffer_with(api: Api, buffer: &PcmBuffer) -> Result<Self, Error> {
+		Self::new_from_pcm_with(api, buffer.as_bytes(), buffer.format(), buffer.sample_rate)
+	}
+
+	/// Returns a new [`Sample`] with audio decoded from WAV file `bytes`.
+	///
+	/// See [`wav::decode`](crate::wav::decode) for supported formats.
+	pub fn new_from_wav_with(api: Api, bytes: &[u8]) -> Result<Self, Error> {
+		Self::new_from_buffer_with(api, &crate::wav::decode(bytes)?)
+	}
+
+	/// Returns a new [`Sample`] with audio decoded from AIFF file `bytes`.
+	///
+	/// See [`aiff::decode`](crate::aiff::decode) for supported formats.
+	pub fn new_from_aiff_with(api: Api, bytes: &[u8]) -> Result<Self, Error> {
+		Self::new_from_buffer_with(api, &crate::aiff::decode(bytes)?)
+	}
 }
 
 
@@ -0,0 +1,175 @@
+use core::ffi::c_char;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_uint;
+use core::ffi::c_void;
+use core::ptr::NonNull;
+
+use sys::ffi::SequenceFinishedCallback;
+use sys::ffi::SequenceTrack;
+use sys::ffi::SoundSequence;
+use sys::ffi::playdate_sound_sequence;
+
+
+/// Default sound sequence api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached sound sequence api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_sequence);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.sequence)) }
+}
+
+impl From<*const playdate_sound_sequence> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_sequence) -> Self { Self(unsafe { ptr.as_ref() }.expect("sequence")) }
+}
+
+impl From<&'static playdate_sound_sequence> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_sequence) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_sequence>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_sequence>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_sequence>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_sequence>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_sequence(&self) -> FnNewSequence { self.0.newSequence.expect("newSequence") }
+	fn free_sequence(&self) -> FnSequence { self.0.freeSequence.expect("freeSequence") }
+	fn load_midi_file(&self) -> FnLoadMidiFile { self.0.loadMIDIFile.expect("loadMIDIFile") }
+	fn get_time(&self) -> FnGetTime { self.0.getTime.expect("getTime") }
+	fn set_time(&self) -> FnSetTime { self.0.setTime.expect("setTime") }
+	fn set_loops(&self) -> FnSetLoops { self.0.setLoops.expect("setLoops") }
+	fn set_tempo(&self) -> FnSetTempo { self.0.setTempo.expect("setTempo") }
+	fn get_track_count(&self) -> FnGetInt { self.0.getTrackCount.expect("getTrackCount") }
+	fn add_track(&self) -> FnAddTrack { self.0.addTrack.expect("addTrack") }
+	fn get_track_at_index(&self) -> FnGetTrackAtIndex { self.0.getTrackAtIndex.expect("getTrackAtIndex") }
+	fn set_track_at_index(&self) -> FnSetTrackAtIndex { self.0.setTrackAtIndex.expect("setTrackAtIndex") }
+	fn all_notes_off(&self) -> FnSequence { self.0.allNotesOff.expect("allNotesOff") }
+	fn is_playing(&self) -> FnGetInt { self.0.isPlaying.expect("isPlaying") }
+	fn get_length(&self) -> FnGetTime { self.0.getLength.expect("getLength") }
+	fn play(&self) -> FnPlay { self.0.play.expect("play") }
+	fn stop(&self) -> FnSequence { self.0.stop.expect("stop") }
+	fn get_current_step(&self) -> FnGetCurrentStep { self.0.getCurrentStep.expect("getCurrentStep") }
+	fn set_current_step(&self) -> FnSetCurrentStep { self.0.setCurrentStep.expect("setCurrentStep") }
+	fn get_tempo(&self) -> FnGetTempo { self.0.getTempo.expect("getTempo") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_sequence::newSequence`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::newSequence")]
+	fn new_sequence(&self) -> FnNewSequence { *sys::api!(sound.sequence.newSequence) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::freeSequence`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::freeSequence")]
+	fn free_sequence(&self) -> FnSequence { *sys::api!(sound.sequence.freeSequence) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::loadMIDIFile`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::loadMIDIFile")]
+	fn load_midi_file(&self) -> FnLoadMidiFile { *sys::api!(sound.sequence.loadMIDIFile) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::getTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTime")]
+	fn get_time(&self) -> FnGetTime { *sys::api!(sound.sequence.getTime) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::setTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setTime")]
+	fn set_time(&self) -> FnSetTime { *sys::api!(sound.sequence.setTime) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::setLoops`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setLoops")]
+	fn set_loops(&self) -> FnSetLoops { *sys::api!(sound.sequence.setLoops) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::setTempo`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setTempo")]
+	fn set_tempo(&self) -> FnSetTempo { *sys::api!(sound.sequence.setTempo) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::getTrackCount`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTrackCount")]
+	fn get_track_count(&self) -> FnGetInt { *sys::api!(sound.sequence.getTrackCount) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::addTrack`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::addTrack")]
+	fn add_track(&self) -> FnAddTrack { *sys::api!(sound.sequence.addTrack) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::getTrackAtIndex`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTrackAtIndex")]
+	fn get_track_at_index(&self) -> FnGetTrackAtIndex { *sys::api!(sound.sequence.getTrackAtIndex) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::setTrackAtIndex`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setTrackAtIndex")]
+	fn set_track_at_index(&self) -> FnSetTrackAtIndex { *sys::api!(sound.sequence.setTrackAtIndex) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::allNotesOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::allNotesOff")]
+	fn all_notes_off(&self) -> FnSequence { *sys::api!(sound.sequence.allNotesOff) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::isPlaying`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::isPlaying")]
+	fn is_playing(&self) -> FnGetInt { *sys::api!(sound.sequence.isPlaying) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::getLength`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getLength")]
+	fn get_length(&self) -> FnGetTime { *sys::api!(sound.sequence.getLength) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::play`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::play")]
+	fn play(&self) -> FnPlay { *sys::api!(sound.sequence.play) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::stop`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::stop")]
+	fn stop(&self) -> FnSequence { *sys::api!(sound.sequence.stop) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::getCurrentStep`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getCurrentStep")]
+	fn get_current_step(&self) -> FnGetCurrentStep { *sys::api!(sound.sequence.getCurrentStep) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::setCurrentStep`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setCurrentStep")]
+	fn set_current_step(&self) -> FnSetCurrentStep { *sys::api!(sound.sequence.setCurrentStep) }
+
+	/// Returns [`sys::ffi::playdate_sound_sequence::getTempo`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTempo")]
+	fn get_tempo(&self) -> FnGetTempo { *sys::api!(sound.sequence.getTempo) }
+}
+
+
+type FnNewSequence = unsafe extern "C" fn() -> *mut SoundSequence;
+type FnSequence = unsafe extern "C" fn(seq: *mut SoundSequence);
+type FnLoadMidiFile = unsafe extern "C" fn(seq: *mut SoundSequence, path: *const c_char) -> c_int;
+type FnGetTime = unsafe extern "C" fn(seq: *mut SoundSequence) -> u32;
+type FnSetTime = unsafe extern "C" fn(seq: *mut SoundSequence, time: u32);
+type FnSetLoops = unsafe extern "C" fn(seq: *mut SoundSequence, loopstart: c_int, loopend: c_int, loops: c_int);
+type FnGetInt = unsafe extern "C" fn(seq: *mut SoundSequence) -> c_int;
+type FnSetTempo = unsafe extern "C" fn(seq: *mut SoundSequence, steps_per_second: c_float);
+type FnAddTrack = unsafe extern "C" fn(seq: *mut SoundSequence) -> *mut SequenceTrack;
+type FnGetTrackAtIndex = unsafe extern "C" fn(seq: *mut SoundSequence, track: c_uint) -> *mut SequenceTrack;
+type FnSetTrackAtIndex = unsafe extern "C" fn(seq: *mut SoundSequence, track: *mut SequenceTrack, idx: c_uint);
+type FnPlay = unsafe extern "C" fn(seq: *mut SoundSequence,
+                                   finish_callback: SequenceFinishedCallback,
+                                   userdata: *mut c_void);
+type FnGetCurrentStep = unsafe extern "C" fn(seq: *mut SoundSequence, time_offset: *mut c_int) -> c_int;
+type FnSetCurrentStep =
+	unsafe extern "C" fn(seq: *mut SoundSequence, step: c_int, time_offset: c_int, play_notes: c_int);
+type FnGetTempo = unsafe extern "C" fn(seq: *mut SoundSequence) -> c_float;
@@ -0,0 +1,281 @@
+//! Playdate sound sequence API
+//!
+//! Sequences play MIDI-like note events of their [tracks](Track) with the assigned [instruments](crate::instrument::Instrument).
+
+use alloc::vec::Vec;
+use core::ffi::c_char;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ffi::c_void;
+
+use sys::ffi::CString;
+use sys::ffi::SequenceFinishedCallback;
+use sys::ffi::SoundSequence;
+use sys::traits::AsRaw;
+
+use fs::Path;
+
+use crate::error::ApiError;
+use crate::error::Error;
+
+pub mod api;
+pub mod track;
+
+pub use track::Note;
+pub use track::Track;
+
+
+/// Sequence of tracks, e.g. loaded from a MIDI file.
+///
+/// Owns its tracks, see [`Track`].
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Sequence<Api: api::Api = api::Default> {
+	ptr: *mut SoundSequence,
+	api: Api,
+	tracks: Vec<Track>,
+}
+
+
+// ctor //
+
+impl<Api> Sequence<Api> where Api: api::Api {
+	/// Creates a new empty sequence.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::newSequence`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::newSequence")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new empty sequence.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::newSequence`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::newSequence")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_sequence();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          tracks: Vec::new() })
+		}
+	}
+
+	/// Creates a new sequence and loads the MIDI file at `path` into it.
+	pub fn from_midi_file<P: AsRef<Path>>(path: P) -> Result<Self, ApiError>
+		where Api: Default {
+		let mut seq = Self::new().map_err(Into::<ApiError>::into)?;
+		seq.load_midi_file(path)?;
+		Ok(seq)
+	}
+}
+
+
+impl<Api: api::Api> Drop for Sequence<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_sequence();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Sequence<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsRaw for Sequence<Api> {
+	type Type = SoundSequence;
+	unsafe fn as_raw(&self) -> *mut Self::Type { self.ptr }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Sequence<Api> {
+	/// If the sequence is empty, loads data from the MIDI file at `path` into the sequence.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::loadMIDIFile`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::loadMIDIFile")]
+	pub fn load_midi_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ApiError> {
+		let path_cs = CString::new(path.as_ref())?;
+		let path_ptr = path_cs.as_ptr() as *const c_char;
+
+		let f = self.api().load_midi_file();
+		if unsafe { f(self.ptr, path_ptr) } == 1 {
+			self.sync_tracks();
+			Ok(())
+		} else {
+			Err(Error::FileNotExist.into())
+		}
+	}
+
+	/// Wraps tracks created by the sound engine, keeping already wrapped ones.
+	fn sync_tracks(&mut self) {
+		let f = self.api().get_track_count();
+		let count = unsafe { f(self.ptr) }.max(0) as usize;
+		let f = self.api().get_track_at_index();
+		for index in self.tracks.len()..count {
+			let ptr = unsafe { f(self.ptr, index as _) };
+			self.tracks.push(Track::from_with(Default::default(), ptr));
+		}
+	}
+
+	/// Returns the number of tracks in the sequence.
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTrackCount")]
+	pub fn track_count(&self) -> usize { self.tracks.len() }
+
+	/// Returns the track at `index`.
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTrackAtIndex")]
+	pub fn track(&self, index: usize) -> Option<&Track> { self.tracks.get(index) }
+
+	/// Returns all tracks of the sequence.
+	pub fn tracks(&self) -> &[Track] { &self.tracks }
+
+	/// Adds a new empty track to the sequence.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::addTrack`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::addTrack")]
+	pub fn add_track(&mut self) -> Result<&Track, Error> {
+		let f = self.api().add_track();
+		let ptr = unsafe { f(self.ptr) };
+		if ptr.is_null() {
+			return Err(Error::Alloc);
+		}
+		self.tracks.push(Track::from_with(Default::default(), ptr));
+		Ok(&self.tracks[self.tracks.len() - 1])
+	}
+
+	/// Returns the current time in the sequence, in samples since the start of the file.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::getTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTime")]
+	pub fn time(&self) -> u32 {
+		let f = self.api().get_time();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Sets the current time in the sequence, in samples since the start of the file.
+	///
+	/// Which step this moves the sequence to depends on the current tempo.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::setTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setTime")]
+	pub fn set_time(&self, time: u32) {
+		let f = self.api().set_time();
+		unsafe { f(self.ptr, time) }
+	}
+
+	/// Sets the looping range of the sequence, in steps.
+	///
+	/// If `loops` is `0`, the loop repeats endlessly.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::setLoops`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setLoops")]
+	pub fn set_loops(&self, start: c_int, end: c_int, loops: c_int) {
+		let f = self.api().set_loops();
+		unsafe { f(self.ptr, start, end, loops) }
+	}
+
+	/// Returns the tempo of the sequence, in steps per second.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::getTempo`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getTempo")]
+	pub fn tempo(&self) -> c_float {
+		let f = self.api().get_tempo();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Sets the tempo of the sequence, in steps per second.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::setTempo`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setTempo")]
+	pub fn set_tempo(&self, steps_per_second: c_float) {
+		let f = self.api().set_tempo();
+		unsafe { f(self.ptr, steps_per_second) }
+	}
+
+	/// Returns the length of the longest track in the sequence, in steps.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::getLength`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getLength")]
+	pub fn length(&self) -> u32 {
+		let f = self.api().get_length();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Returns the step number the sequence is currently at
+	/// and the current sample offset within the step.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::getCurrentStep`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::getCurrentStep")]
+	pub fn current_step(&self) -> (c_int, c_int) {
+		let mut offset = 0;
+		let f = self.api().get_current_step();
+		let step = unsafe { f(self.ptr, &mut offset) };
+		(step, offset)
+	}
+
+	/// Sets the current step for the sequence, `time_offset` is a sample offset within the step.
+	///
+	/// If `play_notes` is `true`, notes at the given step (ignoring `time_offset`) are played.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::setCurrentStep`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::setCurrentStep")]
+	pub fn set_current_step(&self, step: c_int, time_offset: c_int, play_notes: bool) {
+		let f = self.api().set_current_step();
+		unsafe { f(self.ptr, step, time_offset, play_notes as _) }
+	}
+
+	/// Returns `true` if the sequence is currently playing.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::isPlaying`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::isPlaying")]
+	pub fn is_playing(&self) -> bool {
+		let f = self.api().is_playing();
+		unsafe { f(self.ptr) == 1 }
+	}
+
+	/// Starts playing the sequence.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::play`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::play")]
+	pub fn play(&self) { self.play_with_callback_raw(None, core::ptr::null_mut()) }
+
+	/// Starts playing the sequence.
+	///
+	/// `callback` is called with `userdata` when the sequence finishes playing or is stopped.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::play`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::play")]
+	pub fn play_with_callback_raw(&self, callback: SequenceFinishedCallback, userdata: *mut c_void) {
+		let f = self.api().play();
+		unsafe { f(self.ptr, callback, userdata) }
+	}
+
+	/// Stops playing the sequence.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::stop`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::stop")]
+	pub fn stop(&self) {
+		let f = self.api().stop();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Sends a stop signal to all playing notes on all tracks.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_sequence::allNotesOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_sequence::allNotesOff")]
+	pub fn all_notes_off(&self) {
+		let f = self.api().all_notes_off();
+		unsafe { f(self.ptr) }
+	}
+}
@@ -0,0 +1,193 @@
+//! Playdate sound sequence track API
+
+use alloc::rc::Rc;
+use core::any::Any;
+use core::cell::RefCell;
+use core::ffi::c_float;
+
+use sys::ffi::MIDINote;
+use sys::ffi::SequenceTrack;
+use sys::traits::AsRaw;
+
+use crate::instrument::Instrument;
+
+pub mod api;
+
+
+/// Note event of a [`Track`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Note {
+	/// Step the note starts at.
+	pub step: u32,
+	/// Length of the note in steps.
+	pub length: u32,
+	/// MIDI note number, `60` is C4.
+	pub note: MIDINote,
+	/// Velocity in range `0.0 - 1.0`.
+	pub velocity: c_float,
+}
+
+
+/// Track of a [`Sequence`](super::Sequence), playing its notes with the assigned [`Instrument`].
+///
+/// Tracks are owned by the sequence.
+/// The instrument assigned to a track is kept alive while the sequence is alive or until it’s replaced.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Track<Api: api::Api = api::Default> {
+	ptr: *mut SequenceTrack,
+	api: Api,
+	instrument: RefCell<Option<Rc<dyn Any>>>,
+}
+
+
+// ctor //
+
+impl<Api: api::Api> Track<Api> {
+	pub(crate) fn from_with(api: Api, ptr: *mut SequenceTrack) -> Self {
+		Self { ptr,
+		       api,
+		       instrument: Default::default() }
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Track<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsRaw for Track<Api> {
+	type Type = SequenceTrack;
+	unsafe fn as_raw(&self) -> *mut Self::Type { self.ptr }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Track<Api> {
+	/// Sets the [`Instrument`] playing notes of the track.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::setInstrument`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::setInstrument")]
+	pub fn set_instrument<IApi>(&self, instrument: impl Into<Rc<Instrument<IApi>>>)
+		where IApi: crate::instrument::api::Api + 'static {
+		let instrument = instrument.into();
+		let f = self.api().set_instrument();
+		unsafe { f(self.ptr, instrument.as_raw()) }
+		self.instrument.replace(Some(instrument));
+	}
+
+	/// Adds a single note event to the track.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::addNoteEvent`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::addNoteEvent")]
+	pub fn add_note_event(&self, step: u32, length: u32, note: MIDINote, velocity: c_float) {
+		let f = self.api().add_note_event();
+		unsafe { f(self.ptr, step, length, note, velocity) }
+	}
+
+	/// Removes the event at `step` playing `note`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::removeNoteEvent`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::removeNoteEvent")]
+	pub fn remove_note_event(&self, step: u32, note: MIDINote) {
+		let f = self.api().remove_note_event();
+		unsafe { f(self.ptr, step, note) }
+	}
+
+	/// Clears all notes from the track.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::clearNotes`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::clearNotes")]
+	pub fn clear_notes(&self) {
+		let f = self.api().clear_notes();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Clears all control signals from the track.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::clearControlEvents`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::clearControlEvents")]
+	pub fn clear_control_events(&self) {
+		let f = self.api().clear_control_events();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Returns the maximum number of simultaneously playing notes in the track.
+	///
+	/// Currently it’s only set when the track was loaded from a MIDI file.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::getPolyphony`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::getPolyphony")]
+	pub fn polyphony(&self) -> usize {
+		let f = self.api().get_polyphony();
+		unsafe { f(self.ptr) as _ }
+	}
+
+	/// Returns the number of voices currently playing in the track’s instrument.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::activeVoiceCount`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::activeVoiceCount")]
+	pub fn active_voice_count(&self) -> usize {
+		let f = self.api().active_voice_count();
+		unsafe { f(self.ptr) as _ }
+	}
+
+	/// Mutes or unmutes the track.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::setMuted`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::setMuted")]
+	pub fn set_muted(&self, muted: bool) {
+		let f = self.api().set_muted();
+		unsafe { f(self.ptr, muted as _) }
+	}
+
+	/// Returns the length in steps of the track, that is the step where the last note ends.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::getLength`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::getLength")]
+	pub fn length(&self) -> u32 {
+		let f = self.api().get_length();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Returns the index of the first note at the given `step`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::getIndexForStep`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::getIndexForStep")]
+	pub fn index_for_step(&self, step: u32) -> usize {
+		let f = self.api().get_index_for_step();
+		unsafe { f(self.ptr, step) as _ }
+	}
+
+	/// Returns the note at the given `index`, if it’s in range.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_track::getNoteAtIndex`]
+	#[doc(alias = "sys::ffi::playdate_sound_track::getNoteAtIndex")]
+	pub fn note_at_index(&self, index: usize) -> Option<Note> {
+		let mut step = 0;
+		let mut length = 0;
+		let mut note = 0.;
+		let mut velocity = 0.;
+		let f = self.api().get_note_at_index();
+		let found = unsafe {
+			f(
+			  self.ptr,
+			  index as _,
+			  &mut step,
+			  &mut length,
+			  &mut note,
+			  &mut velocity,
+			)
+		};
+		(found == 1).then_some(Note { step,
+		                              length,
+		                              note,
+		                              velocity })
+	}
+
+	/// Returns an iterator over all notes of the track.
+	pub fn notes(&self) -> impl Iterator<Item = Note> + '_ { (0..).map_while(|index| self.note_at_index(index)) }
+}
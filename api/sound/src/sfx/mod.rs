@@ -0,0 +1,8 @@
+//! Sound effects helpers
+
+pub mod pool;
+pub mod sfxr;
+
+pub use pool::SfxPool;
+pub use pool::PlayOptions;
+pub use pool::VoiceHandle;
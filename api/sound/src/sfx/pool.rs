@@ -0,0 +1,381 @@
+//! Voice pool playing sound effects on preallocated sample players.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::ffi::c_float;
+
+use crate::error::Error;
+use crate::player::sp;
+use crate::player::Repeat;
+use crate::sample::Sample;
+use crate::Sound;
+
+
+/// Options of a sound effect played by [`SfxPool`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayOptions {
+	/// Voices playing with higher priority aren't stolen by lower ones.
+	pub priority: i32,
+	/// Volume in range `0.0 - 1.0`.
+	pub volume: c_float,
+	/// Pan in range `-1.0` (left) to `1.0` (right).
+	pub pan: c_float,
+	/// Playback rate, `1.0` is normal speed.
+	pub rate: c_float,
+	/// Repeat mode, plays once by default.
+	pub repeat: Repeat,
+}
+
+impl Default for PlayOptions {
+	fn default() -> Self {
+		Self { priority: 0,
+		       volume: 1.0,
+		       pan: 0.0,
+		       rate: 1.0,
+		       repeat: Repeat::Loops(1) }
+	}
+}
+
+impl PlayOptions {
+	pub fn priority(mut self, priority: i32) -> Self {
+		self.priority = priority;
+		self
+	}
+
+	pub fn volume(mut self, volume: c_float) -> Self {
+		self.volume = volume;
+		self
+	}
+
+	pub fn pan(mut self, pan: c_float) -> Self {
+		self.pan = pan;
+		self
+	}
+
+	pub fn rate(mut self, rate: c_float) -> Self {
+		self.rate = rate;
+		self
+	}
+
+	pub fn repeat(mut self, repeat: Repeat) -> Self {
+		self.repeat = repeat;
+		self
+	}
+}
+
+
+/// Handle of a voice playing a sound effect.
+///
+/// Becomes stale when the voice is reused for another sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoiceHandle {
+	index: usize,
+	generation: u32,
+}
+
+impl VoiceHandle {
+	/// Returns index of the voice in the pool.
+	pub fn index(&self) -> usize { self.index }
+}
+
+
+/// Volume fade of a voice, in sound engine time.
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+	from: c_float,
+	to: c_float,
+	start: u32,
+	frames: u32,
+	stop: bool,
+}
+
+
+/// State of a voice used to pick one for a new sound.
+#[derive(Debug, Clone, Copy, Default)]
+struct Slot {
+	playing: bool,
+	/// Address of the playing sample.
+	sample: usize,
+	priority: i32,
+	/// Order of the last start.
+	started: u64,
+}
+
+
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+struct Voice<Api: sp::api::Api> {
+	player: sp::Player<Api>,
+	sample: Option<Rc<Sample>>,
+	slot: Slot,
+	generation: u32,
+	volume: c_float,
+	pan: c_float,
+	fade: Option<Fade>,
+}
+
+
+/// Pool of preallocated sample players for sound effects.
+///
+/// Plays a sample on a free voice, or steals the lowest priority and then the oldest voice when all are busy.
+/// The number of voices playing the same sample can be limited with [`set_max_instances`](Self::set_max_instances).
+///
+/// Samples are kept alive while they're assigned to a voice.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct SfxPool<Api: sp::api::Api = sp::api::Default> {
+	voices: Vec<Voice<Api>>,
+	max_instances: Option<usize>,
+	counter: u64,
+}
+
+
+// ctor //
+
+impl<Api: sp::api::Api> SfxPool<Api> {
+	/// Creates a pool of `voices` sample players.
+	pub fn new(voices: usize) -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(voices, Api::default)
+	}
+
+	/// Creates a pool of `voices` sample players, using `api` for each of them.
+	pub fn new_with(voices: usize, mut api: impl FnMut() -> Api) -> Result<Self, Error> {
+		let voices = (0..voices).map(|_| {
+			                        Ok(Voice { player: sp::Player::new_with(api())?,
+			                                   sample: None,
+			                                   slot: Slot::default(),
+			                                   generation: 0,
+			                                   volume: 1.0,
+			                                   pan: 0.0,
+			                                   fade: None })
+		                        })
+		                        .collect::<Result<_, Error>>()?;
+		Ok(Self { voices,
+		          max_instances: None,
+		          counter: 0 })
+	}
+}
+
+
+// impl //
+
+impl<Api: sp::api::Api> SfxPool<Api> {
+	/// Returns the number of voices.
+	pub fn voice_count(&self) -> usize { self.voices.len() }
+
+	/// Returns the number of voices currently playing.
+	pub fn playing_count(&self) -> usize { self.voices.iter().filter(|v| v.player.is_playing()).count() }
+
+	/// Limits the number of voices playing the same sample, `None` is unlimited.
+	pub fn set_max_instances(&mut self, max: Option<usize>) { self.max_instances = max; }
+
+	/// Plays the `sample` with default [`PlayOptions`].
+	pub fn play(&mut self, sample: &Rc<Sample>) -> Option<VoiceHandle> {
+		self.play_with(sample, Default::default())
+	}
+
+	/// Plays the `sample` on a free or stolen voice.
+	///
+	/// Returns `None` if all suitable voices play sounds with higher priority.
+	pub fn play_with(&mut self, sample: &Rc<Sample>, options: PlayOptions) -> Option<VoiceHandle> {
+		self.update();
+		for voice in self.voices.iter_mut() {
+			voice.slot.playing = voice.player.is_playing();
+		}
+		let slots: Vec<Slot> = self.voices.iter().map(|v| v.slot).collect();
+		let index = pick(
+		                 &slots,
+		                 Rc::as_ptr(sample) as usize,
+		                 options.priority,
+		                 self.max_instances,
+		)?;
+
+		self.counter += 1;
+		let voice = &mut self.voices[index];
+		voice.player.stop();
+		voice.player.set_sample(sample);
+		voice.sample = Some(sample.clone());
+		voice.slot = Slot { playing: true,
+		                    sample: Rc::as_ptr(sample) as usize,
+		                    priority: options.priority,
+		                    started: self.counter };
+		voice.generation = voice.generation.wrapping_add(1);
+		voice.volume = options.volume;
+		voice.pan = options.pan;
+		voice.fade = None;
+		voice.apply_volume(options.volume);
+		voice.player.play(options.repeat, options.rate);
+
+		Some(VoiceHandle { index,
+		                   generation: voice.generation })
+	}
+
+	fn voice(&self, handle: VoiceHandle) -> Option<&Voice<Api>> {
+		self.voices
+		    .get(handle.index)
+		    .filter(|v| v.generation == handle.generation)
+	}
+
+	fn voice_mut(&mut self, handle: VoiceHandle) -> Option<&mut Voice<Api>> {
+		self.voices
+		    .get_mut(handle.index)
+		    .filter(|v| v.generation == handle.generation)
+	}
+
+	/// Returns `true` if the voice still plays the sound it was started with.
+	pub fn is_playing(&self, handle: VoiceHandle) -> bool {
+		self.voice(handle).is_some_and(|v| v.player.is_playing())
+	}
+
+	/// Stops the voice, if it still plays the sound it was started with.
+	pub fn stop(&mut self, handle: VoiceHandle) {
+		if let Some(voice) = self.voice_mut(handle) {
+			voice.stop();
+		}
+	}
+
+	/// Stops all voices.
+	pub fn stop_all(&mut self) { self.voices.iter_mut().for_each(Voice::stop); }
+
+	/// Sets volume of the voice, cancelling its fade.
+	pub fn set_volume(&mut self, handle: VoiceHandle, volume: c_float) {
+		if let Some(voice) = self.voice_mut(handle) {
+			voice.fade = None;
+			voice.volume = volume;
+			voice.apply_volume(volume);
+		}
+	}
+
+	/// Sets pan of the voice, in range `-1.0` (left) to `1.0` (right).
+	pub fn set_pan(&mut self, handle: VoiceHandle, pan: c_float) {
+		if let Some(voice) = self.voice_mut(handle) {
+			voice.pan = pan;
+			voice.apply_volume(voice.volume);
+		}
+	}
+
+	/// Sets playback rate of the voice.
+	pub fn set_rate(&mut self, handle: VoiceHandle, rate: c_float) {
+		if let Some(voice) = self.voice_mut(handle) {
+			voice.player.set_rate(rate);
+		}
+	}
+
+	/// Fades volume of the voice to `volume` over `seconds`, then stops it if `stop` is `true`.
+	///
+	/// Fades progress with [`update`](Self::update).
+	pub fn fade(&mut self, handle: VoiceHandle, volume: c_float, seconds: c_float, stop: bool) {
+		let now = Sound::Default().current_time();
+		if let Some(voice) = self.voice_mut(handle) {
+			voice.fade = Some(Fade { from: voice.volume,
+			                         to: volume,
+			                         start: now,
+			                         frames: (seconds.max(0.0) * 44100.0) as u32,
+			                         stop });
+		}
+		self.update();
+	}
+
+	/// Fades the voice out over `seconds` and stops it.
+	pub fn fade_out(&mut self, handle: VoiceHandle, seconds: c_float) { self.fade(handle, 0.0, seconds, true) }
+
+	/// Progresses fades and releases samples of finished voices, should be called every frame.
+	pub fn update(&mut self) {
+		let now = Sound::Default().current_time();
+		for voice in self.voices.iter_mut() {
+			if let Some(fade) = voice.fade {
+				let elapsed = now.wrapping_sub(fade.start);
+				if elapsed >= fade.frames {
+					voice.fade = None;
+					voice.volume = fade.to;
+					voice.apply_volume(fade.to);
+					if fade.stop {
+						voice.stop();
+					}
+				} else {
+					let t = elapsed as c_float / fade.frames as c_float;
+					voice.volume = fade.from + (fade.to - fade.from) * t;
+					voice.apply_volume(voice.volume);
+				}
+			}
+
+			if voice.sample.is_some() && !voice.player.is_playing() {
+				voice.sample = None;
+				voice.slot.playing = false;
+			}
+		}
+	}
+}
+
+
+impl<Api: sp::api::Api> Voice<Api> {
+	fn apply_volume(&self, volume: c_float) {
+		let pan = self.pan.clamp(-1.0, 1.0);
+		let left = volume * (1.0 - pan).min(1.0);
+		let right = volume * (1.0 + pan).min(1.0);
+		self.player.set_volume(left, right);
+	}
+
+	fn stop(&mut self) {
+		self.player.stop();
+		self.fade = None;
+		self.sample = None;
+		self.slot.playing = false;
+	}
+}
+
+
+/// Picks a voice for a new sound of `sample` with `priority`.
+///
+/// If `max_instances` of the sample are playing, one of them is replaced,
+/// otherwise a free voice or the lowest priority and then the oldest playing one.
+fn pick(slots: &[Slot], sample: usize, priority: i32, max_instances: Option<usize>) -> Option<usize> {
+	let stealable = |slot: &&Slot| slot.priority <= priority;
+	let victim = |candidates: &mut dyn Iterator<Item = (usize, &Slot)>| {
+		candidates.filter(|(_, slot)| stealable(slot))
+		          .min_by_key(|(_, slot)| (slot.priority, slot.started))
+		          .map(|(index, _)| index)
+	};
+
+	let instances = slots.iter().filter(|s| s.playing && s.sample == sample).count();
+	if max_instances.is_some_and(|max| instances >= max) {
+		let mut same = slots.iter()
+		                    .enumerate()
+		                    .filter(|(_, s)| s.playing && s.sample == sample);
+		return victim(&mut same);
+	}
+
+	slots.iter()
+	     .position(|slot| !slot.playing)
+	     .or_else(|| victim(&mut slots.iter().enumerate()))
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+
+	fn slot(sample: usize, priority: i32, started: u64) -> Slot {
+		Slot { playing: true,
+		       sample,
+		       priority,
+		       started }
+	}
+
+	#[test]
+	fn pick_voice() {
+		let mut slots = [slot(1, 0, 1), slot(2, 1, 2), Slot::default()];
+		assert_eq!(Some(2), pick(&slots, 1, 0, None));
+
+		slots[2] = slot(2, 0, 3);
+		// oldest of the lowest priority
+		assert_eq!(Some(0), pick(&slots, 3, 0, None));
+		// higher priority sounds aren't stolen
+		assert_eq!(None, pick(&slots, 3, -1, None));
+		// instance limit replaces one of the instances, even if there are free voices
+		slots[0].playing = false;
+		assert_eq!(Some(2), pick(&slots, 2, 1, Some(2)));
+		assert_eq!(None, pick(&slots, 2, -1, Some(2)));
+		assert_eq!(Some(0), pick(&slots, 2, -1, Some(3)));
+	}
+}
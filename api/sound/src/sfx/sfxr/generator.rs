@@ -0,0 +1,336 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_float;
+
+use libm::powf;
+
+use crate::audio::AudioSource;
+use super::Params;
+use super::WaveType;
+use super::SAMPLE_RATE;
+
+
+const MASTER_VOL: c_float = 0.05;
+const SUPERSAMPLING: u32 = 8;
+const PHASER_LEN: usize = 1024;
+const NOISE_LEN: usize = 32;
+
+
+/// Returns the wave period in supersampled frames for the `base_freq` parameter.
+pub(super) fn start_period(base_freq: c_float) -> c_float { 100.0 / (base_freq * base_freq + 0.001) }
+
+/// Returns frequency in Hz of the wave `period`.
+pub(super) fn frequency(period: c_float) -> c_float { (SAMPLE_RATE * SUPERSAMPLING) as c_float / period }
+
+
+/// Pitch of the sound: slides, vibrato, arpeggio and repeats.
+#[derive(Debug, Clone)]
+pub(super) struct Pitch {
+	period: c_float,
+	max_period: c_float,
+	slide: c_float,
+	delta_slide: c_float,
+	arp_mod: c_float,
+	arp_time: u32,
+	arp_limit: u32,
+	vib_phase: c_float,
+	vib_speed: c_float,
+	vib_amp: c_float,
+	duty: c_float,
+	duty_slide: c_float,
+	rep_time: u32,
+	rep_limit: u32,
+	/// Frequency dropped below the limit.
+	pub limited: bool,
+}
+
+impl Pitch {
+	pub fn new(p: &Params) -> Self {
+		let mut pitch = Self { period: 0.0,
+		                       max_period: 0.0,
+		                       slide: 0.0,
+		                       delta_slide: 0.0,
+		                       arp_mod: 0.0,
+		                       arp_time: 0,
+		                       arp_limit: 0,
+		                       vib_phase: 0.0,
+		                       vib_speed: powf(p.vib_speed, 2.0) * 0.01,
+		                       vib_amp: p.vib_strength * 0.5,
+		                       duty: 0.0,
+		                       duty_slide: 0.0,
+		                       rep_time: 0,
+		                       rep_limit: if p.repeat_speed == 0.0 {
+			                       0
+		                       } else {
+			                       (powf(1.0 - p.repeat_speed, 2.0) * 20000.0 + 32.0) as u32
+		                       },
+		                       limited: false };
+		pitch.restart(p);
+		pitch
+	}
+
+	/// Restarts slides and arpeggio.
+	fn restart(&mut self, p: &Params) {
+		self.period = start_period(p.base_freq);
+		self.max_period = start_period(p.freq_limit);
+		self.slide = 1.0 - powf(p.freq_ramp, 3.0) * 0.01;
+		self.delta_slide = -powf(p.freq_dramp, 3.0) * 0.000001;
+		self.duty = 0.5 - p.duty * 0.5;
+		self.duty_slide = -p.duty_ramp * 0.00005;
+		self.arp_mod = if p.arp_mod >= 0.0 {
+			1.0 - powf(p.arp_mod, 2.0) * 0.9
+		} else {
+			1.0 + powf(p.arp_mod, 2.0) * 10.0
+		};
+		self.arp_time = 0;
+		self.arp_limit = if p.arp_speed == 1.0 {
+			0
+		} else {
+			(powf(1.0 - p.arp_speed, 2.0) * 20000.0 + 32.0) as u32
+		};
+	}
+
+	/// Advances by one frame, returns the wave period with vibrato applied.
+	pub fn step(&mut self, p: &Params) -> c_float {
+		self.rep_time += 1;
+		if self.rep_limit != 0 && self.rep_time >= self.rep_limit {
+			self.rep_time = 0;
+			self.restart(p);
+		}
+
+		self.arp_time += 1;
+		if self.arp_limit != 0 && self.arp_time >= self.arp_limit {
+			self.arp_limit = 0;
+			self.period *= self.arp_mod;
+		}
+
+		self.slide += self.delta_slide;
+		self.period *= self.slide;
+		if self.period > self.max_period {
+			self.period = self.max_period;
+			if p.freq_limit > 0.0 {
+				self.limited = true;
+			}
+		}
+
+		self.duty = (self.duty + self.duty_slide).clamp(0.0, 0.5);
+
+		if self.vib_amp > 0.0 {
+			self.vib_phase += self.vib_speed;
+			self.period * (1.0 + libm::sinf(self.vib_phase) * self.vib_amp)
+		} else {
+			self.period
+		}
+	}
+}
+
+
+/// Synthesizer of a sound effect, the sfxr algorithm.
+///
+/// Iterates over mono 16-bit samples at [`SAMPLE_RATE`] until the sound ends,
+/// and can be played as an [`AudioSource`].
+#[derive(Debug, Clone)]
+pub struct Generator {
+	params: Params,
+	pitch: Pitch,
+	playing: bool,
+	rng: u32,
+	phase: u32,
+
+	env_length: [u32; 3],
+	env_stage: usize,
+	env_time: u32,
+	env_vol: c_float,
+
+	lp: c_float,
+	lp_delta: c_float,
+	lp_w: c_float,
+	lp_w_delta: c_float,
+	lp_damping: c_float,
+	hp: c_float,
+	hp_cutoff: c_float,
+	hp_cutoff_delta: c_float,
+
+	pha: c_float,
+	pha_delta: c_float,
+	pha_offset: usize,
+	pha_pos: usize,
+	phaser: Vec<c_float>,
+	noise: [c_float; NOISE_LEN],
+}
+
+
+// ctor //
+
+impl Generator {
+	pub fn new(params: Params) -> Self { Self::with_seed(params, 0x5EED) }
+
+	/// Creates generator with `seed` of the noise.
+	pub fn with_seed(params: Params, seed: u32) -> Self {
+		let p = &params;
+		let lp_w = powf(p.lpf_freq, 3.0) * 0.1;
+		let lp_damping = 5.0 / (1.0 + powf(p.lpf_resonance, 2.0) * 20.0) * (0.01 + lp_w);
+		let pha = libm::copysignf(powf(p.pha_offset, 2.0) * 1020.0, p.pha_offset);
+		let mut generator = Self { pitch: Pitch::new(p),
+		                           playing: true,
+		                           rng: seed.max(1),
+		                           phase: 0,
+		                           env_length: p.envelope(),
+		                           env_stage: 0,
+		                           env_time: 0,
+		                           env_vol: 0.0,
+		                           lp: 0.0,
+		                           lp_delta: 0.0,
+		                           lp_w,
+		                           lp_w_delta: 1.0 + p.lpf_ramp * 0.0001,
+		                           lp_damping: lp_damping.min(0.8),
+		                           hp: 0.0,
+		                           hp_cutoff: powf(p.hpf_freq, 2.0) * 0.1,
+		                           hp_cutoff_delta: 1.0 + p.hpf_ramp * 0.0003,
+		                           pha,
+		                           pha_delta: libm::copysignf(powf(p.pha_ramp, 2.0), p.pha_ramp),
+		                           pha_offset: (libm::fabsf(pha) as usize).min(PHASER_LEN - 1),
+		                           pha_pos: 0,
+		                           phaser: vec![0.0; PHASER_LEN],
+		                           noise: [0.0; NOISE_LEN],
+		                           params };
+		generator.fill_noise();
+		generator
+	}
+}
+
+
+// impl //
+
+impl Generator {
+	pub fn params(&self) -> &Params { &self.params }
+
+	/// Returns `true` until the sound ends.
+	pub fn is_playing(&self) -> bool { self.playing }
+
+	/// Starts the sound over.
+	pub fn restart(&mut self) { *self = Self::with_seed(self.params, self.rng) }
+
+	fn random(&mut self) -> c_float {
+		self.rng ^= self.rng << 13;
+		self.rng ^= self.rng >> 17;
+		self.rng ^= self.rng << 5;
+		self.rng as c_float / u32::MAX as c_float * 2.0 - 1.0
+	}
+
+	fn fill_noise(&mut self) {
+		for i in 0..NOISE_LEN {
+			self.noise[i] = self.random();
+		}
+	}
+
+	/// Returns the next sample in range `-1.0` - `1.0`, or `None` if the sound ended.
+	pub fn next_sample(&mut self) -> Option<c_float> {
+		if !self.playing {
+			return None;
+		}
+		let p = self.params;
+
+		let period = self.pitch.step(&p);
+		if self.pitch.limited {
+			self.playing = false;
+		}
+		let period = (period as u32).max(8);
+
+		// volume envelope
+		self.env_time += 1;
+		if self.env_time > self.env_length[self.env_stage] {
+			self.env_time = 0;
+			self.env_stage += 1;
+		}
+		let (time, length) = (self.env_time, self.env_length);
+		let t = |stage: usize| time as c_float / length[stage].max(1) as c_float;
+		self.env_vol = match self.env_stage {
+			0 => t(0),
+			1 => 1.0 + (1.0 - t(1)) * 2.0 * p.env_punch,
+			2 => 1.0 - t(2),
+			_ => {
+				self.playing = false;
+				0.0
+			},
+		};
+
+		// phaser
+		self.pha += self.pha_delta;
+		self.pha_offset = (libm::fabsf(self.pha) as usize).min(PHASER_LEN - 1);
+
+		if self.hp_cutoff_delta != 0.0 {
+			self.hp_cutoff = (self.hp_cutoff * self.hp_cutoff_delta).clamp(0.00001, 0.1);
+		}
+
+		let mut sum = 0.0;
+		for _ in 0..SUPERSAMPLING {
+			self.phase += 1;
+			if self.phase >= period {
+				self.phase %= period;
+				if p.wave_type == WaveType::Noise {
+					self.fill_noise();
+				}
+			}
+
+			let fp = self.phase as c_float / period as c_float;
+			let mut sample = match p.wave_type {
+				WaveType::Square if fp < self.pitch.duty => 0.5,
+				WaveType::Square => -0.5,
+				WaveType::Sawtooth => 1.0 - fp * 2.0,
+				WaveType::Sine => libm::sinf(fp * 2.0 * core::f32::consts::PI),
+				WaveType::Noise => self.noise[(self.phase as usize * NOISE_LEN / period as usize).min(NOISE_LEN - 1)],
+			};
+
+			// low-pass filter
+			let prev = self.lp;
+			self.lp_w = (self.lp_w * self.lp_w_delta).clamp(0.0, 0.1);
+			if p.lpf_freq != 1.0 {
+				self.lp_delta += (sample - self.lp) * self.lp_w;
+				self.lp_delta -= self.lp_delta * self.lp_damping;
+			} else {
+				self.lp = sample;
+				self.lp_delta = 0.0;
+			}
+			self.lp += self.lp_delta;
+
+			// high-pass filter
+			self.hp += self.lp - prev;
+			self.hp -= self.hp * self.hp_cutoff;
+			sample = self.hp;
+
+			// phaser
+			self.phaser[self.pha_pos & (PHASER_LEN - 1)] = sample;
+			sample += self.phaser[(self.pha_pos + PHASER_LEN - self.pha_offset) & (PHASER_LEN - 1)];
+			self.pha_pos = (self.pha_pos + 1) & (PHASER_LEN - 1);
+
+			sum += sample * self.env_vol;
+		}
+
+		let sample = sum / SUPERSAMPLING as c_float * MASTER_VOL * 2.0 * p.sound_vol;
+		Some(sample.clamp(-1.0, 1.0))
+	}
+}
+
+impl Iterator for Generator {
+	type Item = i16;
+
+	fn next(&mut self) -> Option<i16> { self.next_sample().map(|v| (v * i16::MAX as c_float) as i16) }
+}
+
+
+/// Plays the sound until it ends, then stays silent.
+impl AudioSource for Generator {
+	fn render(&mut self, left: &mut [i16], right: Option<&mut [i16]>) -> bool {
+		if !self.playing {
+			return false;
+		}
+		for sample in left.iter_mut() {
+			*sample = self.next().unwrap_or(0);
+		}
+		if let Some(right) = right {
+			right.copy_from_slice(left);
+		}
+		true
+	}
+}
@@ -0,0 +1,149 @@
+use core::cell::Cell;
+use core::cell::RefCell;
+use core::ffi::c_float;
+
+use sys::ffi::SoundSource as OpaqueSoundSource;
+
+use crate::audio::ring;
+use crate::error::Error;
+use crate::lfo::Lfo;
+use crate::lfo::LfoType;
+use crate::source::AsSoundSource;
+use crate::synth::Synth;
+use crate::Sound;
+use super::generator::start_period;
+use super::generator::Pitch;
+use super::Params;
+use super::SAMPLE_RATE;
+
+
+/// Pitch of the playing note, advanced by the frequency modulator.
+#[derive(Debug)]
+struct Live {
+	params: Params,
+	pitch: Pitch,
+	/// Sound engine time of the note start.
+	start: u32,
+	elapsed: u32,
+	period: c_float,
+}
+
+impl Live {
+	fn new(params: Params, start: u32) -> Self {
+		Self { pitch: Pitch::new(&params),
+		       start,
+		       elapsed: 0,
+		       period: start_period(params.base_freq),
+		       params }
+	}
+
+	/// Returns pitch at the sound engine time `now` in octaves relative to the start frequency.
+	fn octaves(&mut self, now: u32) -> c_float {
+		let end = self.params.envelope().iter().sum();
+		let target = now.wrapping_sub(self.start).min(end);
+		while self.elapsed < target {
+			self.period = self.pitch.step(&self.params);
+			self.elapsed += 1;
+		}
+		libm::log2f(start_period(self.params.base_freq) / self.period)
+	}
+}
+
+
+/// Sound effect played live through a [`Synth`].
+///
+/// Waveform, volume envelope, slides, vibrato, arpeggio and repeats are applied,
+/// duty, punch, filters and phaser need [rendering](Params::render).
+///
+/// The pitch is computed by the frequency modulator in the audio context,
+/// [`play`](Self::play) hands the restarted pitch over through a lock-free [`ring`] buffer.
+pub struct SfxrSynth {
+	synth: Synth,
+	params: Cell<Params>,
+	restart: RefCell<ring::Producer<Live>>,
+}
+
+#[cfg(feature = "bindings-derive-debug")]
+impl core::fmt::Debug for SfxrSynth {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("SfxrSynth")
+		 .field("synth", &self.synth)
+		 .field("params", &self.params.get())
+		 .finish()
+	}
+}
+
+
+// ctor //
+
+impl SfxrSynth {
+	pub fn new(params: Params) -> Result<Self, Error> {
+		let synth = Synth::new()?;
+		let lfo: Lfo = Lfo::new(LfoType::kLFOTypeFunction)?;
+		// Restarts are taken every audio cycle, so only a few plays within one frame can queue up.
+		let (restart, mut restarts) = ring::buffer(4);
+		let mut live = Live::new(params, 0);
+		lfo.set_function(
+		                 move || {
+			                 if let Some(restart) = restarts.drain().last() {
+				                 live = restart;
+			                 }
+			                 live.octaves(Sound::Default().current_time())
+		                 },
+		                 true,
+		);
+		synth.set_frequency_modulator(Some(lfo.into()));
+
+		let sfx = Self { synth,
+		                 params: Cell::new(params),
+		                 restart: RefCell::new(restart) };
+		sfx.apply(&params);
+		Ok(sfx)
+	}
+}
+
+
+// impl //
+
+impl SfxrSynth {
+	pub fn synth(&self) -> &Synth { &self.synth }
+
+	pub fn params(&self) -> Params { self.params.get() }
+
+	/// Sets `params` for the next [`play`](Self::play).
+	pub fn set_params(&self, params: Params) {
+		self.apply(&params);
+		self.params.set(params);
+	}
+
+	fn apply(&self, params: &Params) {
+		let [attack, _, decay] = params.envelope()
+		                               .map(|len| len as c_float / SAMPLE_RATE as c_float);
+		self.synth.set_waveform(params.wave_type.waveform());
+		self.synth.set_adsr(attack, 0.0, 1.0, decay);
+	}
+
+	/// Plays the sound from the start with the given `volume`.
+	pub fn play(&self, volume: c_float) {
+		let params = self.params();
+		let [attack, sustain, _] = params.envelope();
+		let length = (attack + sustain) as c_float / SAMPLE_RATE as c_float;
+		// If the modulator is behind, the previous restarts are still queued and the pitch is only late.
+		self.restart
+		    .borrow_mut()
+		    .push(Live::new(params, Sound::Default().current_time()))
+		    .ok();
+		let velocity = (volume * params.sound_vol * 2.0).min(1.0);
+		self.synth
+		    .play_note(params.frequency(), velocity, Some(length), 0);
+	}
+
+	/// Stops the sound immediately.
+	pub fn stop(&self) { self.synth.stop() }
+
+	pub fn is_playing(&self) -> bool { self.synth.is_playing() }
+}
+
+impl AsSoundSource for SfxrSynth {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.synth.as_sound_source() }
+}
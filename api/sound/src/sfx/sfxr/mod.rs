@@ -0,0 +1,196 @@
+//! Procedural sound effects with [sfxr](https://www.drpetter.se/project_sfxr.html)
+//! and jsfxr compatible parameters.
+//!
+//! [`Params`] can be rendered offline into a [`Sample`], played with full synthesis
+//! as an [`AudioSource`](crate::audio::AudioSource) by a [`Generator`],
+//! or played live through a synth with [`SfxrSynth`].
+//!
+//! Presets are loaded from sfxr `.sfs` files or from text with `key = value` lines,
+//! which also accepts jsfxr JSON exports.
+
+use alloc::vec::Vec;
+use core::ffi::c_float;
+
+use fs::Path;
+
+use crate::error::ApiError;
+use crate::error::Error;
+use crate::pcm::PcmBuffer;
+use crate::sample::Sample;
+use crate::synth::Waveform;
+
+mod generator;
+mod live;
+mod preset;
+
+pub use generator::Generator;
+pub use live::SfxrSynth;
+
+
+/// Sample rate of the synthesis.
+pub const SAMPLE_RATE: u32 = 44100;
+
+
+/// Oscillator wave of the sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaveType {
+	#[default]
+	Square,
+	Sawtooth,
+	Sine,
+	Noise,
+}
+
+impl WaveType {
+	/// Returns wave type by its sfxr index.
+	pub fn from_index(index: u32) -> Option<Self> {
+		match index {
+			0 => Some(Self::Square),
+			1 => Some(Self::Sawtooth),
+			2 => Some(Self::Sine),
+			3 => Some(Self::Noise),
+			_ => None,
+		}
+	}
+
+	/// Returns matching synth waveform.
+	pub fn waveform(self) -> Waveform {
+		match self {
+			Self::Square => Waveform::kWaveformSquare,
+			Self::Sawtooth => Waveform::kWaveformSawtooth,
+			Self::Sine => Waveform::kWaveformSine,
+			Self::Noise => Waveform::kWaveformNoise,
+		}
+	}
+}
+
+
+/// Parameters of a sound effect, named and ranged as in sfxr.
+///
+/// Values are in range `0.0` - `1.0`, or `-1.0` - `1.0` for slides and ramps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+	pub wave_type: WaveType,
+
+	/// Attack time of the volume envelope.
+	pub env_attack: c_float,
+	/// Sustain time of the volume envelope.
+	pub env_sustain: c_float,
+	/// Extra volume at the start of the sustain, fading out over it.
+	pub env_punch: c_float,
+	/// Decay time of the volume envelope.
+	pub env_decay: c_float,
+
+	/// Start frequency.
+	pub base_freq: c_float,
+	/// Frequency at which a downward slide stops the sound.
+	pub freq_limit: c_float,
+	/// Frequency slide.
+	pub freq_ramp: c_float,
+	/// Change of the frequency slide.
+	pub freq_dramp: c_float,
+
+	pub vib_strength: c_float,
+	pub vib_speed: c_float,
+
+	/// Frequency change of the arpeggio, positive is up.
+	pub arp_mod: c_float,
+	/// Time of the arpeggio change, `0.0` is at the end of the sound.
+	pub arp_speed: c_float,
+
+	/// Square wave duty cycle.
+	pub duty: c_float,
+	/// Change of the square wave duty cycle.
+	pub duty_ramp: c_float,
+
+	/// Speed of restarting the frequency slide and arpeggio, `0.0` is off.
+	pub repeat_speed: c_float,
+
+	pub pha_offset: c_float,
+	pub pha_ramp: c_float,
+
+	/// Low-pass filter cutoff, `1.0` is off.
+	pub lpf_freq: c_float,
+	pub lpf_ramp: c_float,
+	pub lpf_resonance: c_float,
+	/// High-pass filter cutoff, `0.0` is off.
+	pub hpf_freq: c_float,
+	pub hpf_ramp: c_float,
+
+	/// Master volume of the sound.
+	pub sound_vol: c_float,
+}
+
+/// Default sfxr parameters, a short square blip.
+impl Default for Params {
+	fn default() -> Self {
+		Self { wave_type: WaveType::Square,
+		       env_attack: 0.0,
+		       env_sustain: 0.3,
+		       env_punch: 0.0,
+		       env_decay: 0.4,
+		       base_freq: 0.3,
+		       freq_limit: 0.0,
+		       freq_ramp: 0.0,
+		       freq_dramp: 0.0,
+		       vib_strength: 0.0,
+		       vib_speed: 0.0,
+		       arp_mod: 0.0,
+		       arp_speed: 0.0,
+		       duty: 0.0,
+		       duty_ramp: 0.0,
+		       repeat_speed: 0.0,
+		       pha_offset: 0.0,
+		       pha_ramp: 0.0,
+		       lpf_freq: 1.0,
+		       lpf_ramp: 0.0,
+		       lpf_resonance: 0.0,
+		       hpf_freq: 0.0,
+		       hpf_ramp: 0.0,
+		       sound_vol: 0.5 }
+	}
+}
+
+
+impl Params {
+	/// Returns the start frequency in Hz.
+	pub fn frequency(&self) -> c_float { generator::frequency(generator::start_period(self.base_freq)) }
+
+	/// Returns lengths of the attack, sustain and decay stages in samples.
+	pub fn envelope(&self) -> [u32; 3] {
+		let len = |v: c_float| (v * v * 100000.0) as u32;
+		[len(self.env_attack), len(self.env_sustain), len(self.env_decay)]
+	}
+
+	/// Returns the length of the sound in seconds, at most, as a frequency limit can stop it earlier.
+	pub fn duration(&self) -> c_float { self.envelope().iter().sum::<u32>() as c_float / SAMPLE_RATE as c_float }
+
+
+	/// Renders the sound, mono 16-bit at [`SAMPLE_RATE`].
+	pub fn render(&self) -> Vec<i16> { Generator::new(*self).collect() }
+
+	/// Renders the sound into the buffer.
+	pub fn to_buffer(&self) -> PcmBuffer { PcmBuffer::mono16(self.render(), SAMPLE_RATE) }
+
+	/// Renders the sound into a new [`Sample`].
+	pub fn to_sample(&self) -> Result<Sample, Error> { Sample::new_from_buffer(&self.to_buffer()) }
+
+
+	/// Loads the preset file at `path`, sfxr `.sfs` or text.
+	///
+	/// See [`from_bytes`](Self::from_bytes).
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ApiError> {
+		let bytes = fs::read(path, false).map_err(ApiError::from_err)?;
+		Self::from_bytes(&bytes).map_err(|err| err.into())
+	}
+
+	/// Parses a preset, sfxr `.sfs` if `bytes` start with its version, text otherwise.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+		if preset::is_sfs(bytes) {
+			Self::from_sfs(bytes)
+		} else {
+			let text = core::str::from_utf8(bytes).map_err(|_| Error::InvalidData)?;
+			Self::from_text(text)
+		}
+	}
+}
@@ -0,0 +1,196 @@
+use core::ffi::c_float;
+
+use crate::error::Error;
+use super::Params;
+use super::WaveType;
+
+
+/// Returns `true` if `bytes` start with a known sfxr `.sfs` version.
+pub(super) fn is_sfs(bytes: &[u8]) -> bool { matches!(bytes, [100..=102, 0, 0, 0, ..]) }
+
+
+/// Little-endian reader of `.sfs` fields.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+	fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+		let (head, tail) = self.0.split_at_checked(N).ok_or(Error::InvalidData)?;
+		self.0 = tail;
+		Ok(head.try_into().unwrap())
+	}
+
+	fn int(&mut self) -> Result<i32, Error> { self.bytes().map(i32::from_le_bytes) }
+	fn float(&mut self) -> Result<c_float, Error> { self.bytes().map(c_float::from_le_bytes) }
+	fn bool(&mut self) -> Result<bool, Error> { self.bytes::<1>().map(|[v]| v != 0) }
+}
+
+
+impl Params {
+	/// Parses an sfxr `.sfs` file, versions `100` - `102`.
+	pub fn from_sfs(bytes: &[u8]) -> Result<Self, Error> {
+		let mut r = Reader(bytes);
+		let version = r.int()?;
+		if !(100..=102).contains(&version) {
+			return Err(Error::InvalidData);
+		}
+
+		let wave_type = WaveType::from_index(r.int()? as u32).ok_or(Error::InvalidData)?;
+		let mut p = Self { wave_type,
+		                   ..Default::default() };
+		if version == 102 {
+			p.sound_vol = r.float()?;
+		}
+		p.base_freq = r.float()?;
+		p.freq_limit = r.float()?;
+		p.freq_ramp = r.float()?;
+		if version >= 101 {
+			p.freq_dramp = r.float()?;
+		}
+		p.duty = r.float()?;
+		p.duty_ramp = r.float()?;
+		p.vib_strength = r.float()?;
+		p.vib_speed = r.float()?;
+		let _vib_delay = r.float()?;
+		p.env_attack = r.float()?;
+		p.env_sustain = r.float()?;
+		p.env_decay = r.float()?;
+		p.env_punch = r.float()?;
+		let _filter_on = r.bool()?;
+		p.lpf_resonance = r.float()?;
+		p.lpf_freq = r.float()?;
+		p.lpf_ramp = r.float()?;
+		p.hpf_freq = r.float()?;
+		p.hpf_ramp = r.float()?;
+		p.pha_offset = r.float()?;
+		p.pha_ramp = r.float()?;
+		p.repeat_speed = r.float()?;
+		if version >= 101 {
+			p.arp_speed = r.float()?;
+			p.arp_mod = r.float()?;
+		}
+		Ok(p)
+	}
+
+	/// Parses a text preset of `key = value` lines, or a jsfxr JSON export.
+	///
+	/// Keys are named as the fields, optionally with the `p_` prefix used by sfxr and jsfxr,
+	/// missing keys keep default values and unknown keys are ignored.
+	/// Lines starting with `#` are comments.
+	///
+	/// ```text
+	/// wave_type = 0
+	/// p_base_freq = 0.45
+	/// p_env_decay = 0.25
+	/// ```
+	pub fn from_text(text: &str) -> Result<Self, Error> {
+		let mut params = Self::default();
+		for entry in text.split(['\n', ',']) {
+			let entry = entry.trim().trim_matches(['{', '}']).trim();
+			if entry.is_empty() || entry.starts_with('#') {
+				continue;
+			}
+			let (key, value) = entry.split_once(['=', ':']).ok_or(Error::InvalidData)?;
+			params.set(key.trim().trim_matches('"'), value.trim().trim_matches('"'))?;
+		}
+		Ok(params)
+	}
+
+	fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+		let key = key.strip_prefix("p_").unwrap_or(key);
+		if key == "wave_type" {
+			let index = value.parse().map_err(|_| Error::InvalidData)?;
+			self.wave_type = WaveType::from_index(index).ok_or(Error::InvalidData)?;
+			return Ok(());
+		}
+
+		let field = match key {
+			"env_attack" => &mut self.env_attack,
+			"env_sustain" => &mut self.env_sustain,
+			"env_punch" => &mut self.env_punch,
+			"env_decay" => &mut self.env_decay,
+			"base_freq" => &mut self.base_freq,
+			"freq_limit" => &mut self.freq_limit,
+			"freq_ramp" => &mut self.freq_ramp,
+			"freq_dramp" => &mut self.freq_dramp,
+			"vib_strength" => &mut self.vib_strength,
+			"vib_speed" => &mut self.vib_speed,
+			"arp_mod" => &mut self.arp_mod,
+			"arp_speed" => &mut self.arp_speed,
+			"duty" => &mut self.duty,
+			"duty_ramp" => &mut self.duty_ramp,
+			"repeat_speed" => &mut self.repeat_speed,
+			"pha_offset" => &mut self.pha_offset,
+			"pha_ramp" => &mut self.pha_ramp,
+			"lpf_freq" => &mut self.lpf_freq,
+			"lpf_ramp" => &mut self.lpf_ramp,
+			"lpf_resonance" => &mut self.lpf_resonance,
+			"hpf_freq" => &mut self.hpf_freq,
+			"hpf_ramp" => &mut self.hpf_ramp,
+			"sound_vol" => &mut self.sound_vol,
+			_ => return Ok(()),
+		};
+		*field = value.parse().map_err(|_| Error::InvalidData)?;
+		Ok(())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use super::*;
+
+
+	#[test]
+	fn text_and_sfs() {
+		let json =
+			r#"{"oldParams": true, "wave_type": 2, "p_base_freq": 0.5, "p_env_decay": 0.2, "sound_vol": 0.25}"#;
+		let params = Params::from_text(json).unwrap();
+		assert_eq!(WaveType::Sine, params.wave_type);
+		assert_eq!(
+		           (0.5, 0.2, 0.25),
+		           (params.base_freq, params.env_decay, params.sound_vol)
+		);
+		assert_eq!(params, Params::from_text("# sine\nwave_type = 2\nbase_freq=0.5\n env_decay = 0.2\nsound_vol = 0.25\n").unwrap());
+		assert!(Params::from_text("base_freq = high").is_err());
+
+		// version 100 has no volume, delta slide and arpeggio
+		let mut sfs = Vec::new();
+		sfs.extend(100_i32.to_le_bytes());
+		sfs.extend(3_i32.to_le_bytes());
+		for v in [0.7, 0.1, 0.2, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.3, 0.4, 0.5_f32] {
+			sfs.extend(v.to_le_bytes());
+		}
+		sfs.push(0);
+		for v in [0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0_f32] {
+			sfs.extend(v.to_le_bytes());
+		}
+		let params = Params::from_bytes(&sfs).unwrap();
+		assert_eq!(WaveType::Noise, params.wave_type);
+		assert_eq!(
+		           (0.7, 0.1, 0.2),
+		           (params.base_freq, params.freq_limit, params.freq_ramp)
+		);
+		assert_eq!(
+		           (0.3, 0.4, 0.5),
+		           (params.env_sustain, params.env_decay, params.env_punch)
+		);
+		assert!(Params::from_sfs(&sfs[..sfs.len() - 1]).is_err());
+	}
+
+	#[test]
+	fn render() {
+		let params = Params::default();
+		let samples = params.render();
+		let [attack, sustain, decay] = params.envelope();
+		assert!((samples.len() as u32).abs_diff(attack + sustain + decay) <= 3);
+		assert!(samples.iter().any(|v| v.unsigned_abs() > 1000));
+
+		// frequency limit stops a falling slide early
+		let falling = Params { freq_ramp: -0.5,
+		                       freq_limit: 0.2,
+		                       ..params };
+		assert!(falling.render().len() < samples.len());
+	}
+}
@@ -0,0 +1,79 @@
+//! Modulation sources for synths, channels and effects.
+
+use alloc::rc::Rc;
+use core::any::Any;
+use core::cell::RefCell;
+
+use sys::ffi::PDSynthSignalValue;
+
+
+/// Anything that can be used as a modulation source, e.g. [`Lfo`](crate::lfo::Lfo)
+/// or [`Envelope`](crate::envelope::Envelope).
+pub trait SignalValue {
+	/// Returns pointer to the underlying signal value.
+	///
+	/// The pointer must remain valid while `self` is alive.
+	fn signal_value(&self) -> *mut PDSynthSignalValue;
+}
+
+impl<T: SignalValue + ?Sized> SignalValue for Rc<T> {
+	fn signal_value(&self) -> *mut PDSynthSignalValue { (**self).signal_value() }
+}
+
+
+/// Type-erased modulation source connected to a modulated object.
+///
+/// Keeps the source alive while anything it's connected to is alive,
+/// so the source cannot be freed while still in use by the sound engine.
+///
+/// Wrap the source into [`Rc`] before connecting to keep access to it:
+/// ```no_run
+/// use std::rc::Rc;
+/// use playdate_sound::lfo::Lfo;
+/// use playdate_sound::lfo::LfoType;
+/// use playdate_sound::synth::Synth;
+///
+/// # fn main() -> Result<(), playdate_sound::error::Error> {
+/// let synth: Synth = Synth::new()?;
+/// let lfo: Rc<Lfo> = Rc::new(Lfo::new(LfoType::kLFOTypeSine)?);
+/// synth.set_frequency_modulator(Some(lfo.clone().into()));
+/// lfo.set_rate(2.0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Modulator {
+	ptr: *mut PDSynthSignalValue,
+	_owner: Rc<dyn Any>,
+}
+
+impl Modulator {
+	pub fn new<S: SignalValue + 'static>(signal: S) -> Self {
+		Self { ptr: signal.signal_value(),
+		       _owner: Rc::new(signal) }
+	}
+
+	pub fn as_raw(&self) -> *mut PDSynthSignalValue { self.ptr }
+}
+
+impl<S: SignalValue + 'static> From<S> for Modulator {
+	fn from(signal: S) -> Self { Self::new(signal) }
+}
+
+
+/// Modulator connected to a modulated object, kept alive while connected.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub(crate) struct Slot(RefCell<Option<Modulator>>);
+
+impl Slot {
+	pub fn get(&self) -> Option<Modulator> { self.0.borrow().clone() }
+
+	/// Connects the `modulator` with `connect`, then retains it instead of the previous one.
+	pub fn set(&self, modulator: Option<Modulator>, connect: impl FnOnce(*mut PDSynthSignalValue)) {
+		connect(modulator.as_ref()
+		                 .map_or(core::ptr::null_mut(), Modulator::as_raw));
+		self.0.replace(modulator);
+	}
+}
@@ -1,26 +1,70 @@
 //! Playdate sound-source API
 
+use alloc::rc::Rc;
+use core::cell::RefCell;
 use core::ffi::c_float;
+use core::ffi::c_void;
 
 use sys::ffi::sndCallbackProc;
 use sys::ffi::SoundSource as OpaqueSoundSource;
 use sys::traits::AsRaw;
 
+use crate::callback::Callback;
+
+
+/// Anything that plays as a sound source, e.g. players and synths.
+pub trait AsSoundSource {
+	/// Returns pointer to the underlying sound source.
+	///
+	/// The pointer must remain valid while `self` is alive.
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource;
+}
+
+impl<T: AsSoundSource + ?Sized> AsSoundSource for Rc<T> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { (**self).as_sound_source() }
+}
+
 
 #[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
-pub struct SoundSource<Api = api::Default>(*mut OpaqueSoundSource, Api);
+pub struct SoundSource<Api = api::Default>(*mut OpaqueSoundSource, Api, RefCell<Option<Finish>>);
+
+
+type FnSetFinishCallback = unsafe extern "C" fn(*mut OpaqueSoundSource, sndCallbackProc, *mut c_void);
+
+/// Finish closure with the function to unregister it.
+#[derive(Debug)]
+struct Finish {
+	_callback: Callback,
+	set: FnSetFinishCallback,
+}
+
+
+impl<Api> Drop for SoundSource<Api> {
+	fn drop(&mut self) {
+		if let Some(Finish { set, .. }) = self.2.get_mut().take() {
+			unsafe { set(self.0, None, core::ptr::null_mut()) }
+		}
+	}
+}
 
 impl<Api> AsRaw for SoundSource<Api> {
 	type Type = OpaqueSoundSource;
 	unsafe fn as_raw(&self) -> *mut Self::Type { self.0 }
 }
 
+impl<Api> AsSoundSource for SoundSource<Api> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.0 }
+}
+
 impl<Api: Default> From<*mut OpaqueSoundSource> for SoundSource<Api> {
-	fn from(ptr: *mut OpaqueSoundSource) -> Self { Self(ptr, Default::default()) }
+	fn from(ptr: *mut OpaqueSoundSource) -> Self { Self(ptr, Default::default(), Default::default()) }
 }
 
 impl<Api: api::Api> SoundSource<Api> {
-	pub fn from_with(api: Api, ptr: *mut OpaqueSoundSource) -> Self { Self(ptr, api) }
+	/// Wraps the sound source at `ptr` without taking ownership.
+	///
+	/// The wrapper must not outlive the source.
+	pub fn from_with(api: Api, ptr: *mut OpaqueSoundSource) -> Self { Self(ptr, api, Default::default()) }
 
 	/// Returns `true` if the source is currently playing.
 	///
@@ -57,9 +101,27 @@ impl<Api: api::Api> SoundSource<Api> {
 	#[doc(alias = "sys::ffi::playdate_sound_source::setFinishCallback")]
 	pub fn set_finish_callback_raw(&self, callback: sndCallbackProc) {
 		let f = self.1.set_finish_callback();
-		// TODO: use userdata
-		unsafe { f(self.0, callback, core::ptr::null_mut()) }
+		unsafe { f(self.0, callback, core::ptr::null_mut()) };
+		self.2.take();
 	}
+
+	/// Sets a closure to be called when the source finishes playing,
+	/// replacing the previous one.
+	///
+	/// The closure is owned by this wrapper and unregistered when it's dropped.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_source::setFinishCallback`]
+	#[doc(alias = "sys::ffi::playdate_sound_source::setFinishCallback")]
+	pub fn set_finish_callback<F: FnMut() + 'static>(&self, callback: F) {
+		let callback = Callback::new(callback);
+		let f = self.1.set_finish_callback();
+		unsafe { f(self.0, Callback::PROC, callback.userdata()) };
+		self.2.replace(Some(Finish { _callback: callback,
+		                             set: f }));
+	}
+
+	/// Removes the finish callback.
+	pub fn clear_finish_callback(&self) { self.set_finish_callback_raw(None) }
 }
 
 
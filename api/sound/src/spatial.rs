@@ -0,0 +1,296 @@
+//! Positional audio, pan and attenuation of sound sources by their position in the world.
+//!
+//! An [`Emitter`] ties a sound target, e.g. a [sample player](crate::player::sp::Player),
+//! [synth](crate::synth::Synth) or [channel](crate::channel::Channel), to a [`Position`]
+//! and updates its volume relative to a [`Listener`], usually the camera.
+//!
+//! ```no_run
+//! use playdate_sound::player::sp::Player;
+//! use playdate_sound::spatial::Emitter;
+//! use playdate_sound::spatial::Listener;
+//!
+//! # struct Sprite;
+//! # impl Sprite {
+//! #     fn position(&self) -> (f32, f32) { (0.0, 0.0) }
+//! # }
+//! # fn main() -> Result<(), playdate_sound::error::Error> {
+//! # let (sprite, dx, dy) = (Sprite, 0, 0);
+//! let player: Player = Player::new()?;
+//! let emitter = Emitter::new(player, move || sprite.position());
+//! // every frame:
+//! let listener = Listener::from_draw_offset(dx, dy);
+//! emitter.update(&listener);
+//! # Ok(())
+//! # }
+//! ```
+
+use alloc::rc::Rc;
+use core::ffi::c_float;
+use core::ffi::c_int;
+
+use sys::ffi::LCD_COLUMNS;
+use sys::ffi::LCD_ROWS;
+
+use crate::channel::Channel;
+use crate::instrument::Instrument;
+use crate::player::fp;
+use crate::player::sp;
+use crate::source::SoundSource;
+use crate::synth::Synth;
+
+
+/// Listener of positioned sounds, usually the center of the camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+	pub x: c_float,
+	pub y: c_float,
+	/// Distance within which sounds play at full volume.
+	pub near: c_float,
+	/// Distance from which sounds are silent.
+	pub far: c_float,
+	/// Horizontal distance at which sounds are panned fully to one side.
+	pub pan_width: c_float,
+}
+
+impl Listener {
+	/// Listener at `x`, `y` hearing sounds within one screen width of it,
+	/// panned fully to a side at the screen edge.
+	pub fn new(x: c_float, y: c_float) -> Self {
+		Self { x,
+		       y,
+		       near: 32.0,
+		       far: LCD_COLUMNS as c_float,
+		       pan_width: (LCD_COLUMNS / 2) as c_float }
+	}
+
+	/// Listener at the center of the screen with the given draw offset,
+	/// as set with `gfx::set_draw_offset`.
+	pub fn from_draw_offset(dx: c_int, dy: c_int) -> Self {
+		let mut listener = Self::new(0.0, 0.0);
+		listener.set_draw_offset(dx, dy);
+		listener
+	}
+
+	/// Sets full volume and silence distances.
+	pub fn with_range(mut self, near: c_float, far: c_float) -> Self {
+		self.near = near;
+		self.far = far;
+		self
+	}
+
+	/// Sets horizontal distance at which sounds are panned fully to one side.
+	pub fn with_pan_width(mut self, width: c_float) -> Self {
+		self.pan_width = width;
+		self
+	}
+
+	pub fn set_position(&mut self, x: c_float, y: c_float) {
+		self.x = x;
+		self.y = y;
+	}
+
+	/// Moves the listener to the center of the screen with the given draw offset.
+	pub fn set_draw_offset(&mut self, dx: c_int, dy: c_int) {
+		self.x = (LCD_COLUMNS / 2) as c_float - dx as c_float;
+		self.y = (LCD_ROWS / 2) as c_float - dy as c_float;
+	}
+
+	/// Returns gain and pan of a sound at `x`, `y`.
+	///
+	/// Gain falls off linearly from `1.0` at [`near`](Self::near) to `0.0` at [`far`](Self::far).
+	pub fn spatialize(&self, x: c_float, y: c_float) -> Spatial {
+		let dx = x - self.x;
+		let dy = y - self.y;
+		let distance = libm::sqrtf(dx * dx + dy * dy);
+		let gain = if distance <= self.near {
+			1.0
+		} else if distance >= self.far {
+			0.0
+		} else {
+			1.0 - (distance - self.near) / (self.far - self.near)
+		};
+		let pan = if self.pan_width > 0.0 {
+			(dx / self.pan_width).clamp(-1.0, 1.0)
+		} else {
+			0.0
+		};
+		Spatial { gain, pan }
+	}
+}
+
+
+/// Gain and pan of a positioned sound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spatial {
+	/// Attenuation by distance, `0.0` - `1.0`.
+	pub gain: c_float,
+	/// Pan, in range `-1.0` (left) to `1.0` (right).
+	pub pan: c_float,
+}
+
+impl Spatial {
+	/// Returns `left` and `right` volumes for the base `volume`.
+	pub fn stereo(&self, volume: c_float) -> (c_float, c_float) {
+		let volume = volume * self.gain;
+		let left = volume * (1.0 - self.pan).min(1.0);
+		let right = volume * (1.0 + self.pan).min(1.0);
+		(left, right)
+	}
+}
+
+
+/// Position of a sound in the world.
+///
+/// Implemented for fixed `(x, y)` positions and closures returning one,
+/// e.g. `move || sprite.position()`,
+/// and for sprites of `playdate-sprite` with its `sound` feature.
+pub trait Position {
+	fn position(&self) -> (c_float, c_float);
+}
+
+impl Position for (c_float, c_float) {
+	fn position(&self) -> (c_float, c_float) { *self }
+}
+
+impl<F: Fn() -> (c_float, c_float)> Position for F {
+	fn position(&self) -> (c_float, c_float) { self() }
+}
+
+
+/// Sound whose volume can be set by [`Spatial`] gain and pan.
+pub trait SpatialTarget {
+	/// Applies `spatial` with the base `volume`.
+	fn set_spatial(&self, spatial: Spatial, volume: c_float);
+}
+
+impl<T: SpatialTarget + ?Sized> SpatialTarget for Rc<T> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) { (**self).set_spatial(spatial, volume) }
+}
+
+impl<T: SpatialTarget + ?Sized> SpatialTarget for &'_ T {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) { (**self).set_spatial(spatial, volume) }
+}
+
+impl<Api: sp::api::Api> SpatialTarget for sp::Player<Api> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) {
+		let (left, right) = spatial.stereo(volume);
+		self.set_volume(left, right)
+	}
+}
+
+impl<Api: fp::api::Api> SpatialTarget for fp::Player<Api> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) {
+		let (left, right) = spatial.stereo(volume);
+		self.set_volume(left, right)
+	}
+}
+
+impl<Api: crate::synth::api::Api> SpatialTarget for Synth<Api> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) {
+		let (left, right) = spatial.stereo(volume);
+		self.set_volume(left, right)
+	}
+}
+
+impl<Api: crate::instrument::api::Api> SpatialTarget for Instrument<Api> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) {
+		let (left, right) = spatial.stereo(volume);
+		self.set_volume(left, right)
+	}
+}
+
+impl<Api: crate::source::api::Api> SpatialTarget for SoundSource<Api> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) {
+		let (left, right) = spatial.stereo(volume);
+		self.set_volume(left, right)
+	}
+}
+
+/// Sets channel volume and pan, so all sources of the channel are positioned together.
+impl<Api: crate::channel::api::Api> SpatialTarget for Channel<Api> {
+	fn set_spatial(&self, spatial: Spatial, volume: c_float) {
+		self.set_volume(volume * spatial.gain);
+		self.set_pan(spatial.pan);
+	}
+}
+
+
+/// Sound target following a [`Position`].
+#[derive(Debug, Clone)]
+pub struct Emitter<T: SpatialTarget, P: Position = (c_float, c_float)> {
+	target: T,
+	position: P,
+	volume: c_float,
+}
+
+
+// ctor //
+
+impl<T: SpatialTarget, P: Position> Emitter<T, P> {
+	/// Positions `target` at the `position`, which can be a closure
+	/// to follow e.g. a sprite.
+	pub fn new(target: T, position: P) -> Self {
+		Self { target,
+		       position,
+		       volume: 1.0 }
+	}
+}
+
+
+// impl //
+
+impl<T: SpatialTarget, P: Position> Emitter<T, P> {
+	pub fn target(&self) -> &T { &self.target }
+
+	pub fn into_target(self) -> T { self.target }
+
+	pub fn position(&self) -> &P { &self.position }
+
+	pub fn set_position(&mut self, position: P) { self.position = position }
+
+	/// Replaces the position, e.g. to follow another sprite.
+	pub fn follow<P2: Position>(self, position: P2) -> Emitter<T, P2> {
+		Emitter { target: self.target,
+		          position,
+		          volume: self.volume }
+	}
+
+	/// Base volume, before attenuation.
+	pub fn volume(&self) -> c_float { self.volume }
+
+	/// Sets base volume, before attenuation. Applied on the next [`update`](Self::update).
+	pub fn set_volume(&mut self, volume: c_float) { self.volume = volume }
+
+	/// Updates volume of the target for the `listener`, should be called every frame.
+	pub fn update(&self, listener: &Listener) -> Spatial {
+		let (x, y) = self.position.position();
+		let spatial = listener.spatialize(x, y);
+		self.target.set_spatial(spatial, self.volume);
+		spatial
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+
+	#[test]
+	fn attenuation_and_pan() {
+		let listener = Listener::from_draw_offset(0, 0).with_range(10.0, 110.0)
+		                                               .with_pan_width(100.0);
+		assert_eq!((200.0, 120.0), (listener.x, listener.y));
+
+		let center = listener.spatialize(205.0, 120.0);
+		assert_eq!(Spatial { gain: 1.0, pan: 0.05 }, center);
+
+		let right = listener.spatialize(260.0, 120.0);
+		assert_eq!(0.5, right.gain);
+		let (l, r) = right.stereo(1.0);
+		assert!((l - 0.2).abs() < 1e-6 && r == 0.5);
+
+		assert_eq!(0.0, listener.spatialize(0.0, 0.0).gain);
+		assert_eq!(-1.0, listener.spatialize(0.0, 120.0).pan);
+	}
+}
@@ -0,0 +1,214 @@
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ptr::NonNull;
+
+use sys::ffi::AudioSample;
+use sys::ffi::MIDINote;
+use sys::ffi::PDSynth;
+use sys::ffi::PDSynthEnvelope;
+use sys::ffi::PDSynthSignalValue;
+use sys::ffi::SoundWaveform;
+use sys::ffi::playdate_sound_synth;
+
+
+/// Default synth api end-point, ZST.
+///
+/// All calls approximately costs ~4 derefs.
+#[derive(Debug, Clone, Copy, core::default::Default)]
+pub struct Default;
+impl Api for Default {}
+
+
+/// Cached synth api end-point.
+///
+/// Stores one reference, so size on stack is eq `usize`.
+///
+/// All calls approximately costs ~1 deref.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Cache(&'static playdate_sound_synth);
+
+impl core::default::Default for Cache {
+	fn default() -> Self { Self(sys::api!(sound.synth)) }
+}
+
+impl From<*const playdate_sound_synth> for Cache {
+	#[inline(always)]
+	fn from(ptr: *const playdate_sound_synth) -> Self { Self(unsafe { ptr.as_ref() }.expect("synth")) }
+}
+
+impl From<&'static playdate_sound_synth> for Cache {
+	#[inline(always)]
+	fn from(r: &'static playdate_sound_synth) -> Self { Self(r) }
+}
+
+impl From<NonNull<playdate_sound_synth>> for Cache {
+	#[inline(always)]
+	fn from(ptr: NonNull<playdate_sound_synth>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+impl From<&'_ NonNull<playdate_sound_synth>> for Cache {
+	#[inline(always)]
+	fn from(ptr: &NonNull<playdate_sound_synth>) -> Self { Self(unsafe { ptr.as_ref() }) }
+}
+
+
+impl Api for Cache {
+	fn new_synth(&self) -> FnNewSynth { self.0.newSynth.expect("newSynth") }
+	fn free_synth(&self) -> FnFreeSynth { self.0.freeSynth.expect("freeSynth") }
+	fn copy(&self) -> FnCopy { self.0.copy.expect("copy") }
+	fn set_waveform(&self) -> FnSetWaveform { self.0.setWaveform.expect("setWaveform") }
+	fn set_sample(&self) -> FnSetSample { self.0.setSample.expect("setSample") }
+	fn set_wavetable(&self) -> FnSetWavetable { self.0.setWavetable.expect("setWavetable") }
+	fn set_attack_time(&self) -> FnSetFloat { self.0.setAttackTime.expect("setAttackTime") }
+	fn set_decay_time(&self) -> FnSetFloat { self.0.setDecayTime.expect("setDecayTime") }
+	fn set_sustain_level(&self) -> FnSetFloat { self.0.setSustainLevel.expect("setSustainLevel") }
+	fn set_release_time(&self) -> FnSetFloat { self.0.setReleaseTime.expect("setReleaseTime") }
+	fn set_transpose(&self) -> FnSetFloat { self.0.setTranspose.expect("setTranspose") }
+	fn set_frequency_modulator(&self) -> FnSetModulator {
+		self.0.setFrequencyModulator.expect("setFrequencyModulator")
+	}
+	fn set_amplitude_modulator(&self) -> FnSetModulator {
+		self.0.setAmplitudeModulator.expect("setAmplitudeModulator")
+	}
+	fn get_parameter_count(&self) -> FnGetParameterCount { self.0.getParameterCount.expect("getParameterCount") }
+	fn set_parameter(&self) -> FnSetParameter { self.0.setParameter.expect("setParameter") }
+	fn set_parameter_modulator(&self) -> FnSetParameterModulator {
+		self.0.setParameterModulator.expect("setParameterModulator")
+	}
+	fn play_note(&self) -> FnPlayNote { self.0.playNote.expect("playNote") }
+	fn play_midi_note(&self) -> FnPlayMidiNote { self.0.playMIDINote.expect("playMIDINote") }
+	fn note_off(&self) -> FnNoteOff { self.0.noteOff.expect("noteOff") }
+	fn stop(&self) -> FnStop { self.0.stop.expect("stop") }
+	fn set_volume(&self) -> FnSetVolume { self.0.setVolume.expect("setVolume") }
+	fn get_volume(&self) -> FnGetVolume { self.0.getVolume.expect("getVolume") }
+	fn is_playing(&self) -> FnIsPlaying { self.0.isPlaying.expect("isPlaying") }
+	fn get_envelope(&self) -> FnGetEnvelope { self.0.getEnvelope.expect("getEnvelope") }
+}
+
+
+pub trait Api {
+	/// Returns [`sys::ffi::playdate_sound_synth::newSynth`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::newSynth")]
+	fn new_synth(&self) -> FnNewSynth { *sys::api!(sound.synth.newSynth) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::freeSynth`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::freeSynth")]
+	fn free_synth(&self) -> FnFreeSynth { *sys::api!(sound.synth.freeSynth) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::copy`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::copy")]
+	fn copy(&self) -> FnCopy { *sys::api!(sound.synth.copy) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setWaveform`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setWaveform")]
+	fn set_waveform(&self) -> FnSetWaveform { *sys::api!(sound.synth.setWaveform) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setSample`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setSample")]
+	fn set_sample(&self) -> FnSetSample { *sys::api!(sound.synth.setSample) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setWavetable`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setWavetable")]
+	fn set_wavetable(&self) -> FnSetWavetable { *sys::api!(sound.synth.setWavetable) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setAttackTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setAttackTime")]
+	fn set_attack_time(&self) -> FnSetFloat { *sys::api!(sound.synth.setAttackTime) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setDecayTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setDecayTime")]
+	fn set_decay_time(&self) -> FnSetFloat { *sys::api!(sound.synth.setDecayTime) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setSustainLevel`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setSustainLevel")]
+	fn set_sustain_level(&self) -> FnSetFloat { *sys::api!(sound.synth.setSustainLevel) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setReleaseTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setReleaseTime")]
+	fn set_release_time(&self) -> FnSetFloat { *sys::api!(sound.synth.setReleaseTime) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setTranspose`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setTranspose")]
+	fn set_transpose(&self) -> FnSetFloat { *sys::api!(sound.synth.setTranspose) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setFrequencyModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setFrequencyModulator")]
+	fn set_frequency_modulator(&self) -> FnSetModulator { *sys::api!(sound.synth.setFrequencyModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setAmplitudeModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setAmplitudeModulator")]
+	fn set_amplitude_modulator(&self) -> FnSetModulator { *sys::api!(sound.synth.setAmplitudeModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::getParameterCount`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getParameterCount")]
+	fn get_parameter_count(&self) -> FnGetParameterCount { *sys::api!(sound.synth.getParameterCount) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setParameter`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setParameter")]
+	fn set_parameter(&self) -> FnSetParameter { *sys::api!(sound.synth.setParameter) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setParameterModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setParameterModulator")]
+	fn set_parameter_modulator(&self) -> FnSetParameterModulator { *sys::api!(sound.synth.setParameterModulator) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::playNote`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::playNote")]
+	fn play_note(&self) -> FnPlayNote { *sys::api!(sound.synth.playNote) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::playMIDINote`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::playMIDINote")]
+	fn play_midi_note(&self) -> FnPlayMidiNote { *sys::api!(sound.synth.playMIDINote) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::noteOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::noteOff")]
+	fn note_off(&self) -> FnNoteOff { *sys::api!(sound.synth.noteOff) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::stop`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::stop")]
+	fn stop(&self) -> FnStop { *sys::api!(sound.synth.stop) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::setVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setVolume")]
+	fn set_volume(&self) -> FnSetVolume { *sys::api!(sound.synth.setVolume) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::getVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getVolume")]
+	fn get_volume(&self) -> FnGetVolume { *sys::api!(sound.synth.getVolume) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::isPlaying`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::isPlaying")]
+	fn is_playing(&self) -> FnIsPlaying { *sys::api!(sound.synth.isPlaying) }
+
+	/// Returns [`sys::ffi::playdate_sound_synth::getEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getEnvelope")]
+	fn get_envelope(&self) -> FnGetEnvelope { *sys::api!(sound.synth.getEnvelope) }
+}
+
+
+type FnNewSynth = unsafe extern "C" fn() -> *mut PDSynth;
+type FnFreeSynth = unsafe extern "C" fn(synth: *mut PDSynth);
+type FnCopy = unsafe extern "C" fn(synth: *mut PDSynth) -> *mut PDSynth;
+type FnSetWaveform = unsafe extern "C" fn(synth: *mut PDSynth, wave: SoundWaveform);
+type FnSetSample =
+	unsafe extern "C" fn(synth: *mut PDSynth, sample: *mut AudioSample, sustain_start: u32, sustain_end: u32);
+type FnSetWavetable = unsafe extern "C" fn(synth: *mut PDSynth,
+                                           sample: *mut AudioSample,
+                                           log2size: c_int,
+                                           columns: c_int,
+                                           rows: c_int) -> c_int;
+type FnSetFloat = unsafe extern "C" fn(synth: *mut PDSynth, value: c_float);
+type FnSetModulator = unsafe extern "C" fn(synth: *mut PDSynth, modulator: *mut PDSynthSignalValue);
+type FnGetParameterCount = unsafe extern "C" fn(synth: *mut PDSynth) -> c_int;
+type FnSetParameter = unsafe extern "C" fn(synth: *mut PDSynth, parameter: c_int, value: c_float) -> c_int;
+type FnSetParameterModulator =
+	unsafe extern "C" fn(synth: *mut PDSynth, parameter: c_int, modulator: *mut PDSynthSignalValue);
+type FnPlayNote = unsafe extern "C" fn(synth: *mut PDSynth, freq: c_float, vel: c_float, len: c_float, when: u32);
+type FnPlayMidiNote =
+	unsafe extern "C" fn(synth: *mut PDSynth, note: MIDINote, vel: c_float, len: c_float, when: u32);
+type FnNoteOff = unsafe extern "C" fn(synth: *mut PDSynth, when: u32);
+type FnStop = unsafe extern "C" fn(synth: *mut PDSynth);
+type FnSetVolume = unsafe extern "C" fn(synth: *mut PDSynth, left: c_float, right: c_float);
+type FnGetVolume = unsafe extern "C" fn(synth: *mut PDSynth, left: *mut c_float, right: *mut c_float);
+type FnIsPlaying = unsafe extern "C" fn(synth: *mut PDSynth) -> c_int;
+type FnGetEnvelope = unsafe extern "C" fn(synth: *mut PDSynth) -> *mut PDSynthEnvelope;
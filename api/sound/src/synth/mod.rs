@@ -0,0 +1,441 @@
+//! Playdate sound synth API
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::RefCell;
+use core::ffi::c_float;
+use core::ffi::c_int;
+
+use sys::ffi::MIDINote;
+use sys::ffi::PDSynth;
+use sys::ffi::SoundSource as OpaqueSoundSource;
+use sys::traits::AsRaw;
+
+use crate::envelope::EnvelopeRef;
+use crate::error::Error;
+use crate::sample::Sample;
+use crate::signal::Modulator;
+use crate::signal::Slot;
+use crate::source::AsSoundSource;
+
+pub use sys::ffi::SoundWaveform as Waveform;
+
+pub mod api;
+
+
+/// Objects used by the synth, kept alive while it's in use.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+struct Retained {
+	sample: RefCell<Option<Rc<dyn Any>>>,
+	frequency: Slot,
+	amplitude: Slot,
+	parameters: RefCell<Vec<(c_int, Modulator)>>,
+}
+
+
+/// Synthesizer, a sound source playing notes.
+///
+/// Modulators and samples assigned to the synth are kept alive until they're replaced or the synth is dropped.
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Synth<Api: api::Api = api::Default> {
+	ptr: *mut PDSynth,
+	api: Api,
+	retained: Retained,
+}
+
+
+// ctor //
+
+impl<Api> Synth<Api> where Api: api::Api {
+	/// Creates a new synth.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::newSynth`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::newSynth")]
+	pub fn new() -> Result<Self, Error>
+		where Api: Default {
+		Self::new_with(Api::default())
+	}
+
+	/// Creates a new synth.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::newSynth`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::newSynth")]
+	pub fn new_with(api: Api) -> Result<Self, Error> {
+		let f = api.new_synth();
+		let ptr = unsafe { f() };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api,
+			          retained: Default::default() })
+		}
+	}
+
+	/// Creates a new synth playing `waveform`.
+	pub fn with_waveform(waveform: Waveform) -> Result<Self, Error>
+		where Api: Default {
+		let synth = Self::new()?;
+		synth.set_waveform(waveform);
+		Ok(synth)
+	}
+
+	/// Returns a copy of the synth, sharing its modulators and sample.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::copy`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::copy")]
+	pub fn try_clone(&self) -> Result<Self, Error>
+		where Api: Clone {
+		let f = self.api().copy();
+		let ptr = unsafe { f(self.ptr) };
+		if ptr.is_null() {
+			Err(Error::Alloc)
+		} else {
+			Ok(Self { ptr,
+			          api: self.api.clone(),
+			          retained: self.retained.clone() })
+		}
+	}
+}
+
+
+impl<Api: api::Api> Drop for Synth<Api> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = self.api().free_synth();
+			unsafe { f(self.ptr) }
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+
+// utils //
+
+impl<Api: api::Api> Synth<Api> {
+	#[inline(always)]
+	pub fn api(&self) -> &Api { &self.api }
+}
+
+impl<Api: api::Api> AsRaw for Synth<Api> {
+	type Type = PDSynth;
+	unsafe fn as_raw(&self) -> *mut Self::Type { self.ptr }
+}
+
+impl<Api: api::Api> AsSoundSource for Synth<Api> {
+	fn as_sound_source(&self) -> *mut OpaqueSoundSource { self.ptr as _ }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Synth<Api> {
+	/// Sets the waveform of the synth.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setWaveform`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setWaveform")]
+	pub fn set_waveform(&self, waveform: Waveform) {
+		let f = self.api().set_waveform();
+		unsafe { f(self.ptr, waveform) }
+	}
+
+	/// Provides a `sample` for the synth to play.
+	///
+	/// If `sustain` frames range is given, the sample loops over it while the note is held.
+	/// Sample data must be uncompressed PCM, not ADPCM.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setSample`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setSample")]
+	pub fn set_sample<SApi>(&self, sample: impl Into<Rc<Sample<SApi>>>, sustain: Option<(u32, u32)>)
+		where SApi: crate::sample::api::Api + 'static {
+		let sample = sample.into();
+		let (start, end) = sustain.unwrap_or((0, 0));
+		let f = self.api().set_sample();
+		unsafe { f(self.ptr, sample.0, start, end) }
+		self.retained.sample.replace(Some(sample));
+	}
+
+	/// Sets a wavetable for the synth to play.
+	///
+	/// Sample data must be 16-bit mono uncompressed.
+	/// `log2size` is the base 2 logarithm of the number of samples in each waveform "cell" in the table,
+	/// `columns` and `rows` give the number of cells in each direction.
+	///
+	/// Position in the table is set with parameters `1` and `2`,
+	/// see [`set_parameter`](Self::set_parameter) and [`set_parameter_modulator`](Self::set_parameter_modulator).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setWavetable`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setWavetable")]
+	pub fn set_wavetable<SApi>(&self,
+	                           sample: impl Into<Rc<Sample<SApi>>>,
+	                           log2size: c_int,
+	                           columns: c_int,
+	                           rows: c_int)
+	                           -> Result<(), Error>
+		where SApi: crate::sample::api::Api + 'static
+	{
+		let sample = sample.into();
+		let f = self.api().set_wavetable();
+		if unsafe { f(self.ptr, sample.0, log2size, columns, rows) } == 1 {
+			self.retained.sample.replace(Some(sample));
+			Ok(())
+		} else {
+			Err(Error::InvalidArgument)
+		}
+	}
+
+
+	// envelope //
+
+	/// Sets the attack time of the synth’s amplitude envelope, in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setAttackTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setAttackTime")]
+	pub fn set_attack_time(&self, attack: c_float) {
+		let f = self.api().set_attack_time();
+		unsafe { f(self.ptr, attack) }
+	}
+
+	/// Sets the decay time of the synth’s amplitude envelope, in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setDecayTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setDecayTime")]
+	pub fn set_decay_time(&self, decay: c_float) {
+		let f = self.api().set_decay_time();
+		unsafe { f(self.ptr, decay) }
+	}
+
+	/// Sets the sustain level of the synth’s amplitude envelope, `0..=1`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setSustainLevel`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setSustainLevel")]
+	pub fn set_sustain_level(&self, sustain: c_float) {
+		let f = self.api().set_sustain_level();
+		unsafe { f(self.ptr, sustain) }
+	}
+
+	/// Sets the release time of the synth’s amplitude envelope, in seconds.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setReleaseTime`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setReleaseTime")]
+	pub fn set_release_time(&self, release: c_float) {
+		let f = self.api().set_release_time();
+		unsafe { f(self.ptr, release) }
+	}
+
+	/// Sets the whole amplitude envelope at once.
+	pub fn set_adsr(&self, attack: c_float, decay: c_float, sustain: c_float, release: c_float) {
+		self.set_attack_time(attack);
+		self.set_decay_time(decay);
+		self.set_sustain_level(sustain);
+		self.set_release_time(release);
+	}
+
+	/// Returns the synth’s amplitude envelope.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::getEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getEnvelope")]
+	pub fn envelope(&self) -> EnvelopeRef<'_> { self.envelope_with(Default::default()) }
+
+	/// Returns the synth’s amplitude envelope using the given envelope `api`.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::getEnvelope`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getEnvelope")]
+	pub fn envelope_with<EApi: crate::envelope::api::Api>(&self, api: EApi) -> EnvelopeRef<'_, EApi> {
+		let f = self.api().get_envelope();
+		EnvelopeRef::from_with(api, unsafe { f(self.ptr) })
+	}
+
+	/// Transposes the synth’s output by the given number of half steps.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setTranspose`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setTranspose")]
+	pub fn set_transpose(&self, half_steps: c_float) {
+		let f = self.api().set_transpose();
+		unsafe { f(self.ptr, half_steps) }
+	}
+
+
+	// modulation //
+
+	/// Sets a signal to modulate the synth’s frequency.
+	///
+	/// The signal is scaled so that a value of `1` doubles the synth pitch (an octave up)
+	/// and `-1` halves it (an octave down). `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setFrequencyModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setFrequencyModulator")]
+	pub fn set_frequency_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_frequency_modulator();
+		self.retained
+		    .frequency
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current frequency modulator.
+	pub fn frequency_modulator(&self) -> Option<Modulator> { self.retained.frequency.get() }
+
+	/// Sets a signal to modulate the synth’s output amplitude. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setAmplitudeModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setAmplitudeModulator")]
+	pub fn set_amplitude_modulator(&self, modulator: Option<Modulator>) {
+		let f = self.api().set_amplitude_modulator();
+		self.retained
+		    .amplitude
+		    .set(modulator, |modulator| unsafe { f(self.ptr, modulator) })
+	}
+
+	/// Returns the current amplitude modulator.
+	pub fn amplitude_modulator(&self) -> Option<Modulator> { self.retained.amplitude.get() }
+
+	/// Returns the number of parameters advertised by the synth.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::getParameterCount`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getParameterCount")]
+	pub fn parameter_count(&self) -> c_int {
+		let f = self.api().get_parameter_count();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Sets the (1-based) `parameter` to the given `value`.
+	///
+	/// Returns [`Error::InvalidArgument`] if `parameter` is not a valid parameter index.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setParameter`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setParameter")]
+	pub fn set_parameter(&self, parameter: c_int, value: c_float) -> Result<(), Error> {
+		let f = self.api().set_parameter();
+		if unsafe { f(self.ptr, parameter, value) } == 0 {
+			Err(Error::InvalidArgument)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Sets a signal to modulate the (1-based) `parameter`. `None` clears the modulator.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setParameterModulator`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setParameterModulator")]
+	pub fn set_parameter_modulator(&self, parameter: c_int, modulator: Option<Modulator>) {
+		let f = self.api().set_parameter_modulator();
+		unsafe {
+			f(
+			  self.ptr,
+			  parameter,
+			  modulator.as_ref()
+			           .map_or(core::ptr::null_mut(), Modulator::as_raw),
+			)
+		}
+
+		let mut parameters = self.retained.parameters.borrow_mut();
+		parameters.retain(|(i, _)| *i != parameter);
+		if let Some(modulator) = modulator {
+			parameters.push((parameter, modulator));
+		}
+	}
+
+	/// Returns the current modulator of the (1-based) `parameter`.
+	pub fn parameter_modulator(&self, parameter: c_int) -> Option<Modulator> {
+		self.retained
+		    .parameters
+		    .borrow()
+		    .iter()
+		    .find_map(|(i, modulator)| (*i == parameter).then(|| modulator.clone()))
+	}
+
+
+	// playback //
+
+	/// Plays a note at the given `frequency` in Hz with `velocity` in `0..=1`.
+	///
+	/// The note plays for `length` seconds, or until [`note_off`](Self::note_off) if `length` is `None`.
+	/// If `when` is `0`, the note is played immediately, otherwise it's scheduled for the given
+	/// [sound engine time](crate::Sound::current_time).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::playNote`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::playNote")]
+	pub fn play_note(&self, frequency: c_float, velocity: c_float, length: Option<c_float>, when: u32) {
+		let f = self.api().play_note();
+		unsafe { f(self.ptr, frequency, velocity, length.unwrap_or(-1.0), when) }
+	}
+
+	/// Same as [`play_note`](Self::play_note), but uses MIDI note number, where `60` is C4.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::playMIDINote`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::playMIDINote")]
+	pub fn play_midi_note(&self, note: MIDINote, velocity: c_float, length: Option<c_float>, when: u32) {
+		let f = self.api().play_midi_note();
+		unsafe { f(self.ptr, note, velocity, length.unwrap_or(-1.0), when) }
+	}
+
+	/// Sends a note off event to the synth, either immediately (`when = 0`) or at the scheduled time.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::noteOff`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::noteOff")]
+	pub fn note_off(&self, when: u32) {
+		let f = self.api().note_off();
+		unsafe { f(self.ptr, when) }
+	}
+
+	/// Stops the synth immediately, without playing the release part of the envelope.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::stop`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::stop")]
+	pub fn stop(&self) {
+		let f = self.api().stop();
+		unsafe { f(self.ptr) }
+	}
+
+	/// Returns `true` if the synth is still playing, including the release phase of the envelope.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::isPlaying`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::isPlaying")]
+	pub fn is_playing(&self) -> bool {
+		let f = self.api().is_playing();
+		unsafe { f(self.ptr) == 1 }
+	}
+
+	/// Gets the current left and right channel volume of the synth.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::getVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::getVolume")]
+	pub fn volume(&self) -> (c_float, c_float) {
+		let (mut left, mut right) = (0.0, 0.0);
+		let f = self.api().get_volume();
+		unsafe { f(self.ptr, &mut left, &mut right) };
+		(left, right)
+	}
+
+	/// Sets the playback volume (`0.0` - `1.0`) for left and right channels of the synth.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sound_synth::setVolume`]
+	#[doc(alias = "sys::ffi::playdate_sound_synth::setVolume")]
+	pub fn set_volume(&self, left: c_float, right: c_float) {
+		let f = self.api().set_volume();
+		unsafe { f(self.ptr, left, right) }
+	}
+}
+
+
+/// Returns frequency in Hz of the MIDI `note`, where `69` is A4 at 440 Hz.
+pub fn midi_to_frequency(note: MIDINote) -> c_float { 440.0 * libm::exp2f((note - 69.0) / 12.0) }
+
+/// Returns MIDI note of the given `frequency` in Hz, where `69` is A4 at 440 Hz.
+pub fn frequency_to_midi(frequency: c_float) -> MIDINote { 69.0 + 12.0 * libm::log2f(frequency / 440.0) }
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn midi_frequency() {
+		assert_eq!(440.0, midi_to_frequency(69.0));
+		assert_eq!(880.0, midi_to_frequency(81.0));
+		assert!((261.6256 - midi_to_frequency(60.0)).abs() < 0.001);
+		assert!((60.0 - frequency_to_midi(midi_to_frequency(60.0))).abs() < 0.0001);
+	}
+}
@@ -0,0 +1,186 @@
+//! WAV encoding and decoding of PCM and IMA ADPCM audio.
+
+use alloc::vec::Vec;
+
+use crate::adpcm;
+use crate::error::Error;
+use crate::pcm::PcmBuffer;
+use crate::pcm::PcmData;
+
+
+const FORMAT_PCM: u16 = 0x0001;
+const FORMAT_IMA_ADPCM: u16 = 0x0011;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Size of ADPCM block per channel used by [`encode_adpcm`].
+const ADPCM_BLOCK_ALIGN: usize = 512;
+
+
+/// Encodes 16-bit interleaved PCM `data` into a WAV file.
+pub fn encode(data: &[i16], channels: u16, sample_rate: u32) -> Vec<u8> {
+	let block_align = channels * 2;
+	let mut fmt = Vec::with_capacity(16);
+	fmt.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+	fmt.extend_from_slice(&channels.to_le_bytes());
+	fmt.extend_from_slice(&sample_rate.to_le_bytes());
+	fmt.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+	fmt.extend_from_slice(&block_align.to_le_bytes());
+	fmt.extend_from_slice(&16u16.to_le_bytes());
+
+	let mut pcm = Vec::with_capacity(data.len() * 2);
+	for sample in data {
+		pcm.extend_from_slice(&sample.to_le_bytes());
+	}
+
+	riff(&[(b"fmt ", &fmt), (b"data", &pcm)])
+}
+
+/// Encodes 16-bit interleaved PCM `data` into a WAV file with IMA ADPCM compression.
+///
+/// Returns [`Error::InvalidArgument`] if `channels` is `0`.
+pub fn encode_adpcm(data: &[i16], channels: u16, sample_rate: u32) -> Result<Vec<u8>, Error> {
+	if channels == 0 {
+		return Err(Error::InvalidArgument);
+	}
+	let block_align = ADPCM_BLOCK_ALIGN * channels as usize;
+	let block_frames = adpcm::samples_per_block(block_align, channels as usize);
+	let frames = data.len() / channels as usize;
+
+	let mut fmt = Vec::with_capacity(20);
+	fmt.extend_from_slice(&FORMAT_IMA_ADPCM.to_le_bytes());
+	fmt.extend_from_slice(&channels.to_le_bytes());
+	fmt.extend_from_slice(&sample_rate.to_le_bytes());
+	fmt.extend_from_slice(&((sample_rate as usize * block_align / block_frames) as u32).to_le_bytes());
+	fmt.extend_from_slice(&(block_align as u16).to_le_bytes());
+	fmt.extend_from_slice(&4u16.to_le_bytes());
+	fmt.extend_from_slice(&2u16.to_le_bytes());
+	fmt.extend_from_slice(&(block_frames as u16).to_le_bytes());
+
+	let fact = (frames as u32).to_le_bytes();
+	let encoded = adpcm::encode(data, channels as usize, block_align);
+	Ok(riff(&[(b"fmt ", &fmt), (b"fact", &fact), (b"data", &encoded)]))
+}
+
+fn riff(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+	let len: usize = chunks.iter()
+	                       .map(|(_, data)| 8 + data.len() + data.len() % 2)
+	                       .sum();
+	let mut out = Vec::with_capacity(12 + len);
+	out.extend_from_slice(b"RIFF");
+	out.extend_from_slice(&(4 + len as u32).to_le_bytes());
+	out.extend_from_slice(b"WAVE");
+	for (id, data) in chunks {
+		out.extend_from_slice(*id);
+		out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+		out.extend_from_slice(data);
+		if data.len() % 2 == 1 {
+			out.push(0);
+		}
+	}
+	out
+}
+
+
+/// Decodes a WAV file with 8 or 16-bit PCM or IMA ADPCM data, mono or stereo.
+///
+/// 8-bit samples are converted to signed, ADPCM is decoded to 16-bit samples.
+pub fn decode(bytes: &[u8]) -> Result<PcmBuffer, Error> {
+	if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+		return Err(Error::InvalidData);
+	}
+
+	let mut fmt = None;
+	let mut fact = None;
+	let mut data = None;
+	let mut rest = &bytes[12..];
+	while rest.len() >= 8 {
+		let id = &rest[..4];
+		let len = u32_le(&rest[4..8]) as usize;
+		let end = len.checked_add(8).ok_or(Error::InvalidData)?;
+		let body = rest.get(8..end).unwrap_or(&rest[8..]);
+		match id {
+			b"fmt " => fmt = Some(body),
+			b"fact" if body.len() >= 4 => fact = Some(u32_le(body) as usize),
+			b"data" => data = Some(body),
+			_ => {},
+		}
+		let next = end.checked_add(len % 2).ok_or(Error::InvalidData)?;
+		rest = rest.get(next..).unwrap_or_default();
+	}
+
+	let (Some(fmt), Some(data)) = (fmt, data) else {
+		return Err(Error::InvalidData);
+	};
+	if fmt.len() < 16 {
+		return Err(Error::InvalidData);
+	}
+	let mut format = u16_le(&fmt[0..2]);
+	let channels = u16_le(&fmt[2..4]);
+	let sample_rate = u32_le(&fmt[4..8]);
+	let block_align = u16_le(&fmt[12..14]) as usize;
+	let bits = u16_le(&fmt[14..16]);
+	if format == FORMAT_EXTENSIBLE && fmt.len() >= 26 {
+		format = u16_le(&fmt[24..26]);
+	}
+	let stereo = match channels {
+		1 => false,
+		2 => true,
+		_ => return Err(Error::InvalidData),
+	};
+
+	let data = match (format, bits) {
+		(FORMAT_PCM, 8) => PcmData::I8(data.iter().map(|v| (v ^ 0x80) as i8).collect()),
+		(FORMAT_PCM, 16) => {
+			PcmData::I16(
+			             data.chunks_exact(2)
+			                 .map(|v| i16::from_le_bytes([v[0], v[1]]))
+			                 .collect(),
+			)
+		},
+		(FORMAT_IMA_ADPCM, 4) => {
+			let mut pcm = adpcm::decode(data, channels as usize, block_align);
+			if let Some(frames) = fact {
+				pcm.truncate(frames * channels as usize);
+			}
+			PcmData::I16(pcm)
+		},
+		_ => return Err(Error::InvalidData),
+	};
+	Ok(PcmBuffer::new(data, stereo, sample_rate))
+}
+
+
+fn u16_le(bytes: &[u8]) -> u16 { u16::from_le_bytes([bytes[0], bytes[1]]) }
+fn u32_le(bytes: &[u8]) -> u32 { u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) }
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+
+	#[test]
+	fn encode_mono() {
+		let wav = encode(&[1, -1], 1, 44100);
+		assert_eq!(48, wav.len());
+		assert_eq!(b"RIFF", &wav[..4]);
+		assert_eq!(40, u32::from_le_bytes(wav[4..8].try_into().unwrap()));
+		assert_eq!(88200, u32::from_le_bytes(wav[28..32].try_into().unwrap()));
+		assert_eq!(&[1, 0, 0xFF, 0xFF], &wav[44..]);
+	}
+
+	#[test]
+	fn decode_encoded() {
+		let pcm = PcmBuffer::stereo16(alloc::vec![1, -1, 300, -300], 22050);
+		assert_eq!(pcm, decode(&encode(&pcm.to_i16(), 2, 22050)).unwrap());
+
+		let adpcm = decode(&encode_adpcm(&[0; 100], 1, 22050).unwrap()).unwrap();
+		assert_eq!(100, adpcm.frames());
+		assert_eq!(22050, adpcm.sample_rate);
+	}
+
+	#[test]
+	fn encode_adpcm_without_channels() {
+		assert!(matches!(encode_adpcm(&[0; 100], 0, 22050), Err(Error::InvalidArgument)));
+	}
+}
@@ -0,0 +1,644 @@
+//! Frame animation of sprites.
+//!
+//! [`Animation`] is a set of named [`Clip`]s playing frames of a bitmap table,
+//! [`Animated`] plays it on a sprite, setting its image during the sprite update.
+//!
+//! ```no_run
+//! use gfx::bitmap::table::BitmapTable;
+//! use playdate_sprite::Sprite;
+//! use playdate_sprite::animation::Animated;
+//! use playdate_sprite::animation::Animation;
+//! use playdate_sprite::animation::Clip;
+//!
+//! #[derive(Default)]
+//! struct Player {
+//!     moving: bool,
+//! }
+//!
+//! #[derive(Debug, Clone)]
+//! struct Hit;
+//!
+//! # fn main() -> Result<(), gfx::error::ApiError> {
+//! let table: BitmapTable = BitmapTable::load("images/player")?;
+//! let animation = Animation::new().with_clip("idle", Clip::new(0..=3, 150))
+//!                                 .with_clip("run", Clip::new(4..=9, 80))
+//!                                 .with_clip("attack", Clip::new(10..=13, 60).once().then("idle").with_event(2, Hit));
+//! let player = Animated::new(Sprite::<Player>::new(), table, animation);
+//! player.set_userdata(Player::default());
+//! player.set_update_function(|sprite, animation| {
+//!     let moving = sprite.userdata().is_some_and(|player| player.moving);
+//!     animation.play(if moving { "run" } else { "idle" });
+//! });
+//! player.set_event_handler(|_sprite, _animation, Hit| { /* ... */ });
+//! player.add();
+//! # Ok(())
+//! # }
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ops::Deref;
+use core::ops::RangeInclusive;
+
+use gfx::bitmap::BitmapFlip;
+use gfx::bitmap::table::BitmapTable;
+
+use crate::api;
+use crate::callback::func::SpriteCb;
+use crate::Sprite;
+
+
+/// What happens when a clip reaches its last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+	/// Starts over from the first frame.
+	#[default]
+	Loop,
+	/// Stops on the last frame, then plays the [next](Clip::then) clip if any.
+	Once,
+	/// Plays backwards to the first frame, then forwards again.
+	PingPong,
+}
+
+
+/// Sequence of bitmap table frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip<E = ()> {
+	frames: Vec<c_int>,
+	/// Duration of each frame in milliseconds.
+	durations: Vec<u32>,
+	mode: LoopMode,
+	flip: BitmapFlip,
+	next: Option<String>,
+	/// Events by position of the frame in the clip.
+	events: Vec<(usize, E)>,
+}
+
+impl<E> Clip<E> {
+	/// Creates looping clip of the `frames` range of the table, each shown for `duration` milliseconds.
+	pub fn new(frames: RangeInclusive<c_int>, duration: u32) -> Self { Self::from_frames(frames, duration) }
+
+	/// Creates looping clip of the table `frames` in the given order, each shown for `duration` milliseconds.
+	pub fn from_frames(frames: impl IntoIterator<Item = c_int>, duration: u32) -> Self {
+		let frames: Vec<_> = frames.into_iter().collect();
+		Self { durations: alloc::vec![duration.max(1); frames.len()],
+		       frames,
+		       mode: LoopMode::Loop,
+		       flip: BitmapFlip::kBitmapUnflipped,
+		       next: None,
+		       events: Vec::new() }
+	}
+
+	pub fn with_mode(mut self, mode: LoopMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	/// Plays the clip once, see [`LoopMode::Once`].
+	pub fn once(self) -> Self { self.with_mode(LoopMode::Once) }
+
+	/// Sets duration of the frame at `position` in the clip, in milliseconds.
+	pub fn with_duration(mut self, position: usize, duration: u32) -> Self {
+		if let Some(d) = self.durations.get_mut(position) {
+			*d = duration.max(1);
+		}
+		self
+	}
+
+	/// Flips frames of the clip, combined with the flip of the [`Animation`].
+	pub fn with_flip(mut self, flip: BitmapFlip) -> Self {
+		self.flip = flip;
+		self
+	}
+
+	/// Plays the `next` clip after this one, if it's played [once](LoopMode::Once).
+	pub fn then(mut self, next: impl Into<String>) -> Self {
+		self.next = Some(next.into());
+		self
+	}
+
+	/// Emits `event` when the frame at `position` in the clip is shown.
+	pub fn with_event(mut self, position: usize, event: E) -> Self {
+		self.events.push((position, event));
+		self
+	}
+
+	pub fn frames(&self) -> &[c_int] { &self.frames }
+	pub fn mode(&self) -> LoopMode { self.mode }
+
+	/// Returns total duration of the clip in milliseconds.
+	pub fn duration(&self) -> u32 { self.durations.iter().sum() }
+}
+
+
+/// Named clips and the playback state.
+#[derive(Debug, Clone)]
+pub struct Animation<E = ()> {
+	clips: BTreeMap<String, Clip<E>>,
+	current: Option<String>,
+	position: usize,
+	backwards: bool,
+	/// Time shown of the current frame, in milliseconds.
+	elapsed: c_float,
+	speed: c_float,
+	flip: BitmapFlip,
+	finished: bool,
+	/// Events of the current frame are not emitted yet.
+	entered: bool,
+}
+
+impl<E> Default for Animation<E> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<E> Animation<E> {
+	pub fn new() -> Self {
+		Self { clips: BTreeMap::new(),
+		       current: None,
+		       position: 0,
+		       backwards: false,
+		       elapsed: 0.0,
+		       speed: 1.0,
+		       flip: BitmapFlip::kBitmapUnflipped,
+		       finished: false,
+		       entered: true }
+	}
+
+	pub fn with_clip(mut self, name: impl Into<String>, clip: Clip<E>) -> Self {
+		self.add_clip(name, clip);
+		self
+	}
+
+	pub fn add_clip(&mut self, name: impl Into<String>, clip: Clip<E>) { self.clips.insert(name.into(), clip); }
+
+	pub fn clip(&self, name: &str) -> Option<&Clip<E>> { self.clips.get(name) }
+
+
+	/// Plays the clip with the `name` from the start, unless it's already playing.
+	///
+	/// Finished clip played [once](LoopMode::Once) is started over.
+	///
+	/// Returns `false` if there is no such clip.
+	pub fn play(&mut self, name: &str) -> bool {
+		if !self.clips.contains_key(name) {
+			return false;
+		}
+		if self.current.as_deref() != Some(name) || self.finished {
+			self.current = Some(name.into());
+			self.restart();
+		}
+		true
+	}
+
+	/// Plays the clip with the `name` from the start, even if it's already playing.
+	fn replay(&mut self, name: &str) -> bool {
+		if !self.clips.contains_key(name) {
+			return false;
+		}
+		self.current = Some(name.into());
+		self.restart();
+		true
+	}
+
+	/// Plays the current clip from the start.
+	pub fn restart(&mut self) {
+		self.position = 0;
+		self.backwards = false;
+		self.elapsed = 0.0;
+		self.finished = false;
+		self.entered = true;
+	}
+
+	/// Stops playing, nothing is shown.
+	pub fn stop(&mut self) { self.current = None }
+
+	/// Returns name of the playing clip.
+	pub fn current(&self) -> Option<&str> { self.current.as_deref() }
+
+	fn current_clip(&self) -> Option<&Clip<E>> { self.clips.get(self.current.as_deref()?) }
+
+	/// Returns position of the shown frame in the current clip.
+	pub fn position(&self) -> usize { self.position }
+
+	/// Returns index of the shown frame in the bitmap table.
+	pub fn frame(&self) -> Option<c_int> { self.current_clip()?.frames.get(self.position).copied() }
+
+	/// Returns `true` if the clip played [once](LoopMode::Once) reached its end.
+	pub fn is_finished(&self) -> bool { self.finished }
+
+
+	/// Sets playback speed, `1.0` is normal.
+	pub fn set_speed(&mut self, speed: c_float) { self.speed = speed.max(0.0) }
+	pub fn speed(&self) -> c_float { self.speed }
+
+	/// Sets flip of all clips, e.g. [`kBitmapFlippedX`](BitmapFlip::kBitmapFlippedX) for facing left.
+	pub fn set_flip(&mut self, flip: BitmapFlip) { self.flip = flip }
+
+	/// Returns flip of the shown frame, of the animation combined with the clip one.
+	pub fn flip(&self) -> BitmapFlip {
+		let clip = self.current_clip()
+		               .map_or(BitmapFlip::kBitmapUnflipped, |clip| clip.flip);
+		let (x1, y1) = flip_axes(self.flip);
+		let (x2, y2) = flip_axes(clip);
+		match (x1 != x2, y1 != y2) {
+			(false, false) => BitmapFlip::kBitmapUnflipped,
+			(true, false) => BitmapFlip::kBitmapFlippedX,
+			(false, true) => BitmapFlip::kBitmapFlippedY,
+			(true, true) => BitmapFlip::kBitmapFlippedXY,
+		}
+	}
+
+
+	/// Advances playback by `ms` milliseconds, calling `on_event` with events of every shown frame.
+	///
+	/// Returns `true` if the shown frame changed.
+	pub fn advance(&mut self, ms: u32, mut on_event: impl FnMut(&E)) -> bool {
+		if self.current.is_none() {
+			return false;
+		}
+		let mut changed = false;
+		if self.entered {
+			self.entered = false;
+			changed = true;
+			self.emit(&mut on_event);
+		}
+
+		self.elapsed += ms as c_float * self.speed;
+		while !self.finished {
+			let Some(clip) = self.current_clip() else {
+				break;
+			};
+			let Some(duration) = clip.durations.get(self.position).map(|d| *d as c_float) else {
+				break;
+			};
+			if self.elapsed < duration {
+				break;
+			}
+			let (mode, len, next) = (clip.mode, clip.frames.len(), clip.next.clone());
+			self.elapsed -= duration;
+
+			match self.next_position(mode, len) {
+				Some(position) => self.position = position,
+				None => {
+					self.elapsed = 0.0;
+					match next {
+						Some(next) if self.replay(&next) => self.entered = false,
+						_ => {
+							self.finished = true;
+							break;
+						},
+					}
+				},
+			}
+			changed = true;
+			self.emit(&mut on_event);
+		}
+		changed
+	}
+
+	fn next_position(&mut self, mode: LoopMode, len: usize) -> Option<usize> {
+		let last = len.checked_sub(1)?;
+		let position = self.position;
+		match mode {
+			LoopMode::Loop if position < last => Some(position + 1),
+			LoopMode::Loop => Some(0),
+			LoopMode::Once if position < last => Some(position + 1),
+			LoopMode::Once => None,
+			LoopMode::PingPong if last == 0 => Some(0),
+			LoopMode::PingPong => {
+				if self.backwards && position == 0 || !self.backwards && position == last {
+					self.backwards = !self.backwards;
+				}
+				Some(if self.backwards {
+					position - 1
+				} else {
+					position + 1
+				})
+			},
+		}
+	}
+
+	fn emit(&self, on_event: &mut impl FnMut(&E)) {
+		if let Some(clip) = self.current_clip() {
+			clip.events
+			    .iter()
+			    .filter(|(position, _)| *position == self.position)
+			    .for_each(|(_, event)| on_event(event));
+		}
+	}
+}
+
+fn flip_axes(flip: BitmapFlip) -> (bool, bool) {
+	match flip {
+		BitmapFlip::kBitmapUnflipped => (false, false),
+		BitmapFlip::kBitmapFlippedX => (true, false),
+		BitmapFlip::kBitmapFlippedY => (false, true),
+		BitmapFlip::kBitmapFlippedXY => (true, true),
+	}
+}
+
+
+type UpdateFn<UD, E, Api> = dyn FnMut(&SpriteCb<UD, Api, false>, &mut Animation<E>);
+type EventFn<UD, E, Api> = dyn FnMut(&SpriteCb<UD, Api, false>, &mut Animation<E>, E);
+/// Change of the state requested while the animation is advanced.
+type Deferred<UD, E, Api> = Box<dyn FnOnce(&mut State<UD, E, Api>)>;
+type Queue<UD, E, Api> = RefCell<Vec<Deferred<UD, E, Api>>>;
+
+struct State<UD, E, Api: api::Api> {
+	animation: Animation<E>,
+	table: Rc<BitmapTable>,
+	/// Shown frame and flip.
+	shown: Option<(c_int, BitmapFlip)>,
+	/// System time of the last update, in milliseconds.
+	time: Option<u32>,
+	update: Option<Box<UpdateFn<UD, E, Api>>>,
+	event: Option<Box<EventFn<UD, E, Api>>>,
+}
+
+impl<UD, E, Api: api::Api> State<UD, E, Api> {
+	/// Sets image of the sprite to the current frame if it changed.
+	fn show<const FOD: bool>(&mut self, sprite: &SpriteCb<UD, Api, FOD>) {
+		let frame = self.animation.frame().map(|frame| (frame, self.animation.flip()));
+		if frame == self.shown {
+			return;
+		}
+		self.shown = frame;
+		if let Some((frame, flip)) = frame {
+			if let Some(bitmap) = self.table.get_ref(frame) {
+				sprite.set_image(bitmap, flip);
+			}
+		}
+		sprite.mark_dirty();
+	}
+}
+
+
+/// Sprite playing an [`Animation`] of frames of a bitmap table.
+///
+/// The shown frame is updated during the sprite update,
+/// so the sprite should be in the display list.
+///
+/// Changes requested from the update function or an event handler,
+/// e.g. with [`play`](Self::play) on the shared sprite, are applied after it returns.
+pub struct Animated<UD: 'static = (), E: 'static = (), Api: api::Api + Default + 'static = api::Default> {
+	sprite: SpriteCb<UD, Api, true>,
+	state: Rc<RefCell<State<UD, E, Api>>>,
+	deferred: Rc<Queue<UD, E, Api>>,
+}
+
+impl<UD, E, Api> core::fmt::Debug for Animated<UD, E, Api>
+	where UD: core::fmt::Debug,
+	      E: core::fmt::Debug,
+	      Api: api::Api + Default + core::fmt::Debug
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Animated")
+		 .field("sprite", &self.sprite)
+		 .field("animation", &self.state.borrow().animation)
+		 .finish()
+	}
+}
+
+
+// ctor //
+
+impl<UD: 'static, E: Clone + 'static, Api: api::Api + Default + 'static> Animated<UD, E, Api> {
+	/// Plays `animation` on the `sprite` with frames of the `table`.
+	///
+	/// Update function of the sprite is replaced,
+	/// use [`set_update_function`](Self::set_update_function) instead.
+	pub fn new(sprite: Sprite<UD, Api, true>, table: impl Into<Rc<BitmapTable>>, animation: Animation<E>) -> Self {
+		let state = State { animation,
+		                    table: table.into(),
+		                    shown: None,
+		                    time: None,
+		                    update: None,
+		                    event: None };
+		let state = Rc::new(RefCell::new(state));
+		let deferred = Rc::new(RefCell::new(Vec::new()));
+		let sprite = sprite.with_callbacks();
+		{
+			let state = state.clone();
+			let deferred = deferred.clone();
+			sprite.set_update_function(move |sprite| update(sprite, &state, &deferred));
+		}
+		let animated = Self { sprite,
+		                      state,
+		                      deferred };
+		animated.state.borrow_mut().show(&animated.sprite);
+		animated
+	}
+}
+
+
+// impl //
+
+impl<UD: 'static, E: Clone + 'static, Api: api::Api + Default + 'static> Animated<UD, E, Api> {
+	pub fn sprite(&self) -> &SpriteCb<UD, Api, true> { &self.sprite }
+
+	/// Calls `f` with the state, then updates the shown frame.
+	///
+	/// While the animation is advanced, `f` is queued and called after the callbacks return.
+	fn modify(&self, f: impl FnOnce(&mut State<UD, E, Api>) + 'static) {
+		match self.state.try_borrow_mut() {
+			Ok(mut state) => {
+				f(&mut state);
+				state.show(&self.sprite);
+			},
+			Err(_) => self.deferred.borrow_mut().push(Box::new(f)),
+		}
+	}
+
+	/// Calls `f` with the animation, then updates the shown frame.
+	///
+	/// Called from the update function or an event handler, `f` is deferred until they return,
+	/// use the animation passed to them to change it immediately.
+	pub fn with_animation(&self, f: impl FnOnce(&mut Animation<E>) + 'static) {
+		self.modify(move |state| f(&mut state.animation))
+	}
+
+	/// Plays the clip with the `name`, see [`Animation::play`].
+	pub fn play(&self, name: &str) {
+		let name = String::from(name);
+		self.with_animation(move |animation| {
+			    animation.play(&name);
+		    })
+	}
+
+	/// Sets flip of all clips, see [`Animation::set_flip`].
+	pub fn set_flip(&self, flip: BitmapFlip) { self.with_animation(move |animation| animation.set_flip(flip)) }
+
+	/// Sets playback speed, see [`Animation::set_speed`].
+	pub fn set_speed(&self, speed: c_float) { self.with_animation(move |animation| animation.set_speed(speed)) }
+
+	/// Sets closure called on every sprite update before advancing the animation.
+	pub fn set_update_function<F>(&self, f: F)
+		where F: FnMut(&SpriteCb<UD, Api, false>, &mut Animation<E>) + 'static {
+		self.modify(move |state| state.update = Some(Box::new(f)))
+	}
+
+	/// Sets closure called with events of shown frames.
+	pub fn set_event_handler<F>(&self, f: F)
+		where F: FnMut(&SpriteCb<UD, Api, false>, &mut Animation<E>, E) + 'static {
+		self.modify(move |state| state.event = Some(Box::new(f)))
+	}
+
+	/// Advances the animation by `ms` milliseconds, as the sprite update does with the elapsed time.
+	pub fn advance(&self, ms: u32) {
+		let view = self.sprite.to_shared();
+		advance(&view, &self.state, &self.deferred, ms);
+	}
+}
+
+impl<UD: 'static, E: 'static, Api: api::Api + Default + 'static> Deref for Animated<UD, E, Api> {
+	type Target = SpriteCb<UD, Api, true>;
+	fn deref(&self) -> &Self::Target { &self.sprite }
+}
+
+
+fn update<UD, E: Clone, Api: api::Api>(sprite: &SpriteCb<UD, Api, false>,
+                                       state: &RefCell<State<UD, E, Api>>,
+                                       deferred: &Queue<UD, E, Api>) {
+	let now = {
+		let f = *sys::api!(system.getCurrentTimeMilliseconds);
+		unsafe { f() }
+	};
+	let ms = {
+		let Ok(mut state) = state.try_borrow_mut() else {
+			return;
+		};
+		let ms = state.time.map_or(0, |time| now.wrapping_sub(time));
+		state.time = Some(now);
+		ms
+	};
+	advance(sprite, state, deferred, ms);
+}
+
+fn advance<UD, E: Clone, Api: api::Api>(sprite: &SpriteCb<UD, Api, false>,
+                                        state: &RefCell<State<UD, E, Api>>,
+                                        deferred: &Queue<UD, E, Api>,
+                                        ms: u32) {
+	let Ok(mut state) = state.try_borrow_mut() else {
+		return;
+	};
+	let state = &mut *state;
+
+	if let Some(update) = state.update.as_mut() {
+		update(sprite, &mut state.animation);
+	}
+
+	let mut events = Vec::new();
+	state.animation.advance(ms, |event| events.push(event.clone()));
+	if let Some(handler) = state.event.as_mut() {
+		for event in events {
+			handler(sprite, &mut state.animation, event);
+		}
+	}
+
+	loop {
+		let queued = core::mem::take(&mut *deferred.borrow_mut());
+		if queued.is_empty() {
+			break;
+		}
+		queued.into_iter().for_each(|f| f(state));
+	}
+
+	state.show(sprite);
+}
+
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+
+	#[test]
+	fn clips() {
+		let mut animation =
+			Animation::new().with_clip("idle", Clip::new(0..=2, 100))
+			                .with_clip("bounce", Clip::new(3..=5, 10).with_mode(LoopMode::PingPong))
+			                .with_clip("hit", Clip::new(6..=7, 50).once().then("idle").with_event(1, 'x'));
+		assert_eq!(None, animation.frame());
+		assert!(!animation.play("missing"));
+
+		assert!(animation.play("idle"));
+		assert!(animation.advance(0, |_| {}));
+		assert_eq!(Some(0), animation.frame());
+		assert!(!animation.advance(99, |_| {}));
+		assert!(animation.advance(1, |_| {}));
+		assert_eq!(Some(1), animation.frame());
+		animation.advance(200, |_| {});
+		assert_eq!(Some(0), animation.frame());
+
+		// playing the same clip doesn't restart it
+		animation.advance(150, |_| {});
+		animation.play("idle");
+		assert_eq!(Some(1), animation.frame());
+
+		animation.play("bounce");
+		let mut frames = Vec::new();
+		for _ in 0..6 {
+			animation.advance(10, |_| {});
+			frames.push(animation.frame().unwrap());
+		}
+		assert_eq!(vec![4, 5, 4, 3, 4, 5], frames);
+
+		// events, then the next clip
+		let mut events = Vec::new();
+		animation.play("hit");
+		animation.advance(50, |event| events.push(*event));
+		assert_eq!((Some(7), vec!['x']), (animation.frame(), events));
+		animation.advance(50, |_| {});
+		assert_eq!((Some("idle"), Some(0)), (animation.current(), animation.frame()));
+	}
+
+	#[test]
+	fn once_restarts() {
+		let mut animation: Animation = Animation::new().with_clip("blink", Clip::new(0..=1, 10).once())
+		                                               .with_clip("spin", Clip::new(2..=3, 10).once().then("spin"));
+
+		// finished clip starts over when played again
+		animation.play("blink");
+		animation.advance(30, |_| {});
+		assert!(animation.is_finished());
+		assert_eq!(Some(1), animation.frame());
+		animation.play("blink");
+		assert!(!animation.is_finished());
+		assert_eq!(Some(0), animation.frame());
+
+		// clip followed by itself loops
+		animation.play("spin");
+		let mut frames = Vec::new();
+		for _ in 0..5 {
+			animation.advance(10, |_| {});
+			frames.push(animation.frame().unwrap());
+		}
+		assert_eq!(vec![3, 2, 3, 2, 3], frames);
+		assert!(!animation.is_finished());
+	}
+
+	#[test]
+	fn flip_and_speed() {
+		let mut animation: Animation =
+			Animation::new().with_clip(
+			                           "run",
+			                           Clip::new(0..=3, 100).with_flip(BitmapFlip::kBitmapFlippedX),
+			);
+		animation.play("run");
+		assert_eq!(BitmapFlip::kBitmapFlippedX, animation.flip());
+		animation.set_flip(BitmapFlip::kBitmapFlippedXY);
+		assert_eq!(BitmapFlip::kBitmapFlippedY, animation.flip());
+
+		animation.set_speed(2.0);
+		animation.advance(100, |_| {});
+		assert_eq!(Some(2), animation.frame());
+	}
+}
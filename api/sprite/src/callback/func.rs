@@ -1,96 +1,307 @@
-//! WiP
+//! Closure callbacks.
+//!
+//! [`SpriteCb`] keeps update, draw and collision response closures
+//! together with the userdata of the sprite, and frees them with it.
+//!
+//! ```no_run
+//! use playdate_sprite::Sprite;
+//!
+//! #[derive(Default)]
+//! struct Player {
+//!     dx: f32,
+//!     dy: f32,
+//! }
+//!
+//! let sprite = Sprite::<Player>::new().with_callbacks();
+//! sprite.set_userdata(Player::default());
+//! sprite.set_update_function(|sprite| {
+//!     if let Some(player) = sprite.userdata() {
+//!         sprite.move_by(player.dx, player.dy);
+//!     }
+//! });
+//! ```
 
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::cell::RefCell;
 use core::ops::Deref;
-use core::pin::Pin;
 
-use sys::traits::AsRaw;
 use sys::ffi::LCDSprite;
+use sys::ffi::PDRect;
+use sys::ffi::SpriteCollisionResponseType;
+use sys::traits::AsRaw;
 
+use crate::api;
 use crate::AnySprite;
 use crate::Sprite;
 use crate::SpriteApi;
 use crate::SpriteRef;
-use crate::api;
+use crate::TypedSprite;
+
+
+type Shared<F> = Rc<RefCell<Box<F>>>;
+
+type UpdateFn<UD, Api> = dyn FnMut(&SpriteCb<UD, Api, false>);
+type DrawFn<UD, Api> = dyn FnMut(&SpriteCb<UD, Api, false>, PDRect, PDRect);
+type CollisionFn<UD, Api> = dyn FnMut(&SpriteCb<UD, Api, false>, SpriteRef) -> SpriteCollisionResponseType;
 
 
 impl<UD, Api: api::Api, const FOD: bool> Sprite<UD, Api, FOD> {
-	/// Wraps into sprite that supports fn callbacks.
+	/// Wraps into sprite that supports closure callbacks.
 	///
-	/// But this doesn’t supports static stateless handlers.
+	/// Current userdata of the sprite is kept.
 	pub fn with_callbacks(self) -> SpriteCb<UD, Api, FOD> { SpriteCb::new(self) }
 }
 
 
+/// Userdata of a [`SpriteCb`]: the user's data and the callbacks.
+pub struct Slot<UD, Api: api::Api> {
+	userdata: Option<UD>,
+	update: Option<Shared<UpdateFn<UD, Api>>>,
+	draw: Option<Shared<DrawFn<UD, Api>>>,
+	collision: Option<Shared<CollisionFn<UD, Api>>>,
+}
+
+impl<UD, Api: api::Api> Default for Slot<UD, Api> {
+	fn default() -> Self {
+		Self { userdata: None,
+		       update: None,
+		       draw: None,
+		       collision: None }
+	}
+}
+
+impl<UD: core::fmt::Debug, Api: api::Api> core::fmt::Debug for Slot<UD, Api> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Slot")
+		 .field("userdata", &self.userdata)
+		 .field("update", &self.update.is_some())
+		 .field("draw", &self.draw.is_some())
+		 .field("collision", &self.collision.is_some())
+		 .finish()
+	}
+}
+
+
+/// Sprite with update, draw and collision response closures.
+///
+/// Closures are stored in the sprite's userdata and dropped with it,
+/// so the sprite must not be freed from inside its own callbacks.
 #[derive(Debug)]
 #[repr(transparent)]
-pub struct SpriteCb<Userdata = (), Api: api::Api = api::Default, const FREE_ON_DROP: bool = true>(Sprite<Userdata, Api, FREE_ON_DROP>);
+pub struct SpriteCb<Userdata = (), Api: api::Api = api::Default, const FREE_ON_DROP: bool = true>(
+	Sprite<Slot<Userdata, Api>, Api, FREE_ON_DROP>,
+);
+
 
+// ctor //
 
 impl<UD, Api: api::Api, const FOD: bool> SpriteCb<UD, Api, FOD> {
 	fn new(sprite: Sprite<UD, Api, FOD>) -> Self {
-		// reassign userdata
-		let ud = sprite.take_userdata();
-		// TODO: init ud layout/structure. set ud into it.
+		let userdata = sprite.take_userdata().map(|ud| *ud);
+		let sprite = sprite.into_userdata_type::<Slot<UD, Api>>();
+		sprite.set_userdata(Slot { userdata,
+		                           ..Default::default() });
+		Self(sprite)
+	}
+}
+
+impl<UD, Api: api::Api, const FOD: bool> Drop for SpriteCb<UD, Api, FOD> {
+	fn drop(&mut self) {
+		if FOD && !unsafe { self.0.as_raw() }.is_null() {
+			let ptr = unsafe { self.0.as_raw() };
+			let api = self.0.api_ref();
+			unsafe {
+				(api.set_update_function())(ptr, None);
+				(api.set_draw_function())(ptr, None);
+				(api.set_collision_response_function())(ptr, None);
+			}
+		}
+	}
+}
+
 
-		sprite.set_userdata(*ud.unwrap());
-		//
-		todo!()
+// impl //
+
+impl<UD, Api: api::Api, const FOD: bool> SpriteCb<UD, Api, FOD> {
+	fn slot(&self) -> &mut Slot<UD, Api> {
+		if self.0.userdata().is_none() {
+			self.0.set_userdata(Default::default());
+		}
+		self.0.userdata().unwrap()
 	}
 
-	// TODO: override get/set/take_userdata methods
+	/// Sets custom data to the sprite, keeping the callbacks.
+	pub fn set_userdata(&self, data: UD) { self.slot().userdata = Some(data) }
 
+	/// Gets the _mutable__ reference to sprite’s userdata.
+	pub fn userdata(&self) -> Option<&mut UD> { self.slot().userdata.as_mut() }
 
-	pub fn set_update_function<F>(&self, on_update: Pin<F>)
-		where F: FnMut(SpriteRef) {
-		let f = self.0.api_ref().set_update_function();
-		// unsafe { f(self.0.as_raw(), ) }
+	/// Returns __taken__ value the sprite’s userdata.
+	pub fn take_userdata(&self) -> Option<UD> { self.slot().userdata.take() }
+
+	/// Unwraps the sprite, removing the callbacks and keeping the userdata.
+	pub fn into_inner(self) -> Sprite<UD, Api, FOD> {
+		let userdata = self.take_userdata();
+		self.clear_update_function();
+		self.clear_draw_function();
+		self.clear_collision_response_function();
+
+		let sprite = core::mem::ManuallyDrop::new(self);
+		let sprite = unsafe { core::ptr::read(&sprite.0) };
+		drop(sprite.take_userdata());
+		let set = sprite.api_ref().set_userdata();
+		unsafe { set(sprite.as_raw(), core::ptr::null_mut()) };
+
+		let sprite = sprite.into_userdata_type::<UD>();
+		if let Some(userdata) = userdata {
+			sprite.set_userdata(userdata);
+		}
+		sprite
 	}
+}
 
 
-	pub fn set_draw_function(&self) {
-		let f = self.0.api_ref().set_draw_function();
-		// unsafe { f(self.0.as_raw(), ) }
+impl<UD: 'static, Api: api::Api + Default + 'static, const FOD: bool> SpriteCb<UD, Api, FOD> {
+	/// Sets the update closure for the sprite, called by [`update_and_draw_sprites`](crate::update_and_draw_sprites).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sprite::setUpdateFunction`]
+	#[doc(alias = "sys::ffi::playdate_sprite::setUpdateFunction")]
+	pub fn set_update_function<F>(&self, f: F)
+		where F: FnMut(&SpriteCb<UD, Api, false>) + 'static {
+		let previous = self.slot().update.replace(Rc::new(RefCell::new(Box::new(f))));
+		let set = self.0.api_ref().set_update_function();
+		unsafe { set(self.0.as_raw(), Some(update_proxy::<UD, Api>)) };
+		drop(previous);
 	}
 
-	pub fn set_collision_response_function(&self) {
-		let f = self.0.api_ref().set_collision_response_function();
-		// 	unsafe { f(self.0.as_raw(), ) }
-		todo!()
+	/// Sets the draw closure for the sprite.
+	///
+	/// ⚠️ Caution:
+	/// Do not forget to set [`bounds`](Sprite::set_bounds) __before__ setting draw function,
+	/// default zero bounds causes UB in the system.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sprite::setDrawFunction`]
+	#[doc(alias = "sys::ffi::playdate_sprite::setDrawFunction")]
+	pub fn set_draw_function<F>(&self, f: F)
+		where F: FnMut(&SpriteCb<UD, Api, false>, PDRect, PDRect) + 'static {
+		let previous = self.slot().draw.replace(Rc::new(RefCell::new(Box::new(f))));
+		let set = self.0.api_ref().set_draw_function();
+		unsafe { set(self.0.as_raw(), Some(draw_proxy::<UD, Api>)) };
+		drop(previous);
+	}
+
+	/// Sets the collision response closure for the sprite,
+	/// called with the other sprite by [`move_with_collisions`](Sprite::move_with_collisions).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sprite::setCollisionResponseFunction`]
+	#[doc(alias = "sys::ffi::playdate_sprite::setCollisionResponseFunction")]
+	pub fn set_collision_response_function<F>(&self, f: F)
+		where F: FnMut(&SpriteCb<UD, Api, false>, SpriteRef) -> SpriteCollisionResponseType + 'static {
+		let previous = self.slot().collision.replace(Rc::new(RefCell::new(Box::new(f))));
+		let set = self.0.api_ref().set_collision_response_function();
+		unsafe { set(self.0.as_raw(), Some(collision_proxy::<UD, Api>)) };
+		drop(previous);
+	}
+}
+
+impl<UD, Api: api::Api + Default, const FOD: bool> SpriteCb<UD, Api, FOD> {
+	/// Returns non-owning view of the sprite, as passed to the callbacks.
+	pub fn to_shared(&self) -> SpriteCb<UD, Api, false> {
+		SpriteCb(SpriteRef::from(unsafe { self.0.as_raw() }).into())
+	}
+}
+
+impl<UD, Api: api::Api, const FOD: bool> SpriteCb<UD, Api, FOD> {
+	pub fn clear_update_function(&self) {
+		let set = self.0.api_ref().set_update_function();
+		unsafe { set(self.0.as_raw(), None) };
+		self.slot().update.take();
+	}
+
+	pub fn clear_draw_function(&self) {
+		let set = self.0.api_ref().set_draw_function();
+		unsafe { set(self.0.as_raw(), None) };
+		self.slot().draw.take();
+	}
+
+	pub fn clear_collision_response_function(&self) {
+		let set = self.0.api_ref().set_collision_response_function();
+		unsafe { set(self.0.as_raw(), None) };
+		self.slot().collision.take();
+	}
+}
+
+
+// proxies //
+
+/// Returns shared view of the sprite and a strong reference to its closure,
+/// so the closure survives being replaced from inside itself.
+unsafe fn callback<UD, Api, F: ?Sized>(sprite: *mut LCDSprite,
+                                       get: impl FnOnce(&Slot<UD, Api>) -> Option<&Shared<F>>)
+                                       -> Option<(SpriteCb<UD, Api, false>, Shared<F>)>
+	where Api: api::Api + Default
+{
+	let sprite = SpriteCb(SpriteRef::from(sprite).into());
+	let f = sprite.0.userdata().and_then(|slot| get(slot)).cloned()?;
+	Some((sprite, f))
+}
+
+unsafe extern "C" fn update_proxy<UD, Api: api::Api + Default>(sprite: *mut LCDSprite) {
+	if let Some((sprite, f)) = callback::<UD, Api, _>(sprite, |slot| slot.update.as_ref()) {
+		if let Ok(mut f) = f.try_borrow_mut() {
+			f(&sprite)
+		};
+	}
+}
+
+unsafe extern "C" fn draw_proxy<UD, Api: api::Api + Default>(sprite: *mut LCDSprite,
+                                                             bounds: PDRect,
+                                                             draw_rect: PDRect) {
+	if let Some((sprite, f)) = callback::<UD, Api, _>(sprite, |slot| slot.draw.as_ref()) {
+		if let Ok(mut f) = f.try_borrow_mut() {
+			f(&sprite, bounds, draw_rect)
+		};
 	}
 }
 
+unsafe extern "C" fn collision_proxy<UD, Api: api::Api + Default>(sprite: *mut LCDSprite,
+                                                                  other: *mut LCDSprite)
+                                                                  -> SpriteCollisionResponseType {
+	callback::<UD, Api, _>(sprite, |slot| slot.collision.as_ref()).and_then(|(sprite, f)| {
+		                                                              let mut f = f.try_borrow_mut().ok()?;
+		                                                              Some(f(&sprite, SpriteRef::from(other)))
+	                                                              })
+	                                                              .unwrap_or(SpriteCollisionResponseType::kCollisionTypeFreeze)
+}
+
+
+// traits //
 
 impl<UD, Api: api::Api, const FOD: bool> Deref for SpriteCb<UD, Api, FOD> {
-	type Target = Sprite<UD, Api, FOD>;
+	type Target = Sprite<Slot<UD, Api>, Api, FOD>;
 	fn deref(&self) -> &Self::Target { &self.0 }
 }
 
-impl<UD, Api: api::Api, const FOD: bool> AnySprite for SpriteCb<UD, Api, FOD>
-	where SpriteCb<UD, Api, FOD>: AnySprite
-{
+impl<UD, Api: api::Api, const FOD: bool> TypedSprite for SpriteCb<UD, Api, FOD> {
+	type Userdata = UD;
+	const FREE_ON_DROP: bool = FOD;
 }
 
-impl<UD, Api: api::Api, const FOD: bool> SpriteApi for SpriteCb<UD, Api, FOD>
-	where SpriteCb<UD, Api, FOD>: SpriteApi,
-	      SpriteCb<UD, Api, FOD>: SpriteApi<Api = Api>
-{
-	type Api = <SpriteCb<UD, Api, FOD> as SpriteApi>::Api;
+impl<UD, Api: api::Api, const FOD: bool> AnySprite for SpriteCb<UD, Api, FOD> {}
+
+impl<UD, Api: api::Api, const FOD: bool> SpriteApi for SpriteCb<UD, Api, FOD> {
+	type Api = Api;
 
-	fn api(&self) -> Self::Api
+	fn api(&self) -> Api
 		where Self::Api: Copy {
-		use api::Api;
 		self.0.api()
 	}
 
-	fn api_ref(&self) -> &Self::Api {
-		use api::Api;
-		self.0.api_ref()
-	}
+	fn api_ref(&self) -> &Api { self.0.api_ref() }
 }
 
-impl<UD, Api: api::Api, const FOD: bool> AsRaw for SpriteCb<UD, Api, FOD>
-	where SpriteCb<UD, Api, FOD>: AsRaw<Type = LCDSprite>
-{
-	type Type = <SpriteCb<UD, Api, FOD> as AsRaw>::Type;
-	unsafe fn as_raw(&self) -> *mut Self::Type { self.0.as_raw() }
+impl<UD, Api: api::Api, const FOD: bool> AsRaw for SpriteCb<UD, Api, FOD> {
+	type Type = LCDSprite;
+	unsafe fn as_raw(&self) -> *mut LCDSprite { self.0.as_raw() }
 }
@@ -0,0 +1,262 @@
+//! Owned results of collision checks and line queries.
+//!
+//! Arrays returned by the engine are freed when [`Collisions`] or [`QueryInfos`] are dropped.
+
+use core::ffi::c_float;
+use core::ffi::c_int;
+use core::ops::Deref;
+
+use sys::ffi::CollisionPoint;
+use sys::ffi::CollisionVector;
+use sys::ffi::LCDSprite;
+use sys::ffi::PDRect;
+use sys::ffi::SpriteCollisionInfo;
+use sys::ffi::SpriteCollisionResponseType;
+use sys::ffi::SpriteQueryInfo;
+use sys::traits::AsRaw;
+
+use crate::api;
+use crate::registry::Registry;
+use crate::Sprite;
+use crate::SpriteApi;
+use crate::SpriteRef;
+
+
+/// Array allocated by the engine, freed on drop.
+pub struct Results<T> {
+	ptr: *mut T,
+	len: usize,
+}
+
+/// Collisions of a moving sprite,
+/// returned by [`Sprite::move_colliding`] and [`Sprite::check_move`].
+pub type Collisions = Results<Collision>;
+
+/// Sprites intersecting a line segment, returned by [`query_info_along_line`].
+pub type QueryInfos = Results<QueryInfo>;
+
+
+impl<T> Results<T> {
+	/// Takes ownership of the array of `len` items at `ptr` allocated by the engine.
+	///
+	/// # Safety
+	/// `ptr` must be null or point to `len` initialized items, allocated by the engine's `realloc`.
+	pub(crate) unsafe fn from_raw(ptr: *mut T, len: c_int) -> Self {
+		let len = if ptr.is_null() { 0 } else { len.max(0) as usize };
+		Self { ptr, len }
+	}
+
+	/// Creates empty results.
+	pub const fn empty() -> Self {
+		Self { ptr: core::ptr::null_mut(),
+		       len: 0 }
+	}
+}
+
+impl<T> Default for Results<T> {
+	fn default() -> Self { Self::empty() }
+}
+
+impl<T> Drop for Results<T> {
+	fn drop(&mut self) {
+		if !self.ptr.is_null() {
+			let f = *sys::api!(system.realloc);
+			unsafe { f(self.ptr as _, 0) };
+			self.ptr = core::ptr::null_mut();
+		}
+	}
+}
+
+impl<T> Deref for Results<T> {
+	type Target = [T];
+
+	fn deref(&self) -> &[T] {
+		if self.ptr.is_null() {
+			&[]
+		} else {
+			unsafe { core::slice::from_raw_parts(self.ptr, self.len) }
+		}
+	}
+}
+
+impl<T> AsRef<[T]> for Results<T> {
+	fn as_ref(&self) -> &[T] { self }
+}
+
+impl<'t, T> IntoIterator for &'t Results<T> {
+	type Item = &'t T;
+	type IntoIter = core::slice::Iter<'t, T>;
+
+	fn into_iter(self) -> Self::IntoIter { self.iter() }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Results<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_list().entries(self.iter()).finish()
+	}
+}
+
+
+/// Result of a sprite movement with collisions.
+#[derive(Default)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Movement {
+	/// Position of the sprite after collisions.
+	pub x: c_float,
+	pub y: c_float,
+	pub collisions: Collisions,
+}
+
+
+/// Collision of a moving sprite with another one.
+#[repr(transparent)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct Collision(SpriteCollisionInfo);
+
+impl Deref for Collision {
+	type Target = SpriteCollisionInfo;
+	fn deref(&self) -> &SpriteCollisionInfo { &self.0 }
+}
+
+impl Collision {
+	/// The sprite being moved.
+	pub fn sprite(&self) -> SpriteRef { self.0.sprite.into() }
+
+	/// The sprite colliding with the sprite being moved.
+	pub fn other(&self) -> SpriteRef { self.0.other.into() }
+
+	/// The other sprite, if it's in the `registry` with `UD` userdata.
+	pub fn other_in<'r, UD: 'static, Api: api::Api + 'static>(&self,
+	                                                          registry: &'r Registry<Api>)
+	                                                          -> Option<&'r Sprite<UD, Api, true>> {
+		registry.get(&self.other())
+	}
+
+	/// Userdata of the other sprite.
+	///
+	/// # Safety
+	/// Userdata of the other sprite must be set by a [`Sprite<UD>`],
+	/// use [`other_in`](Self::other_in) to check its type.
+	pub unsafe fn other_userdata<UD>(&self) -> Option<&UD> {
+		let f = api::Api::get_userdata(&api::Default);
+		(f(self.0.other) as *const UD).as_ref()
+	}
+
+	/// The result of collision response.
+	pub fn response_type(&self) -> SpriteCollisionResponseType { self.0.responseType }
+
+	/// `true` if the sprite was overlapping other when the collision started,
+	/// `false` if it didn’t overlap but tunneled through other.
+	pub fn overlaps(&self) -> bool { self.0.overlaps != 0 }
+
+	/// A number between `0` and `1` indicating how far along the movement to the goal the collision occurred.
+	pub fn ti(&self) -> c_float { self.0.ti }
+
+	/// The difference between the original coordinates and the actual ones when the collision happened.
+	pub fn movement(&self) -> &CollisionPoint { &self.0.move_ }
+
+	/// The collision normal, usually `-1`, `0`, or `1` in `x` and `y`.
+	///
+	/// Use this value to determine things like if your character is touching the ground.
+	pub fn normal(&self) -> &CollisionVector { &self.0.normal }
+
+	/// The coordinates where the sprite started touching other.
+	pub fn touch(&self) -> &CollisionPoint { &self.0.touch }
+
+	/// The rectangle the sprite occupied when the touch happened.
+	pub fn sprite_rect(&self) -> &PDRect { &self.0.spriteRect }
+
+	/// The rectangle the sprite being collided with occupied when the touch happened.
+	pub fn other_rect(&self) -> &PDRect { &self.0.otherRect }
+}
+
+
+/// Sprite intersecting a line segment.
+#[repr(transparent)]
+#[cfg_attr(feature = "bindings-derive-debug", derive(Debug))]
+pub struct QueryInfo(SpriteQueryInfo);
+
+impl Deref for QueryInfo {
+	type Target = SpriteQueryInfo;
+	fn deref(&self) -> &SpriteQueryInfo { &self.0 }
+}
+
+impl QueryInfo {
+	/// The sprite being intersected by the segment.
+	pub fn sprite(&self) -> SpriteRef { self.0.sprite.into() }
+
+	/// The sprite, if it's in the `registry` with `UD` userdata.
+	pub fn sprite_in<'r, UD: 'static, Api: api::Api + 'static>(&self,
+	                                                           registry: &'r Registry<Api>)
+	                                                           -> Option<&'r Sprite<UD, Api, true>> {
+		registry.get(&self.sprite())
+	}
+
+	/// Number between `0` and `1` indicating how far from the start of the segment the sprite was entered.
+	pub fn ti1(&self) -> c_float { self.0.ti1 }
+
+	/// Number between `0` and `1` indicating how far from the start of the segment the sprite was exited.
+	pub fn ti2(&self) -> c_float { self.0.ti2 }
+
+	/// The coordinates of the first intersection between sprite and the line segment.
+	pub fn entry_point(&self) -> &CollisionPoint { &self.0.entryPoint }
+
+	/// The coordinates of the second intersection between sprite and the line segment.
+	pub fn exit_point(&self) -> &CollisionPoint { &self.0.exitPoint }
+}
+
+
+/// Returns [`QueryInfos`] for all sprites with collision rects
+/// that intersect the line connecting `x1`, `y1` and `x2`, `y2`.
+///
+/// Equivalent to [`sys::ffi::playdate_sprite::querySpriteInfoAlongLine`]
+#[doc(alias = "sys::ffi::playdate_sprite::querySpriteInfoAlongLine")]
+pub fn query_info_along_line(x1: c_float, y1: c_float, x2: c_float, y2: c_float) -> QueryInfos {
+	let mut len: c_int = 0;
+	let f = api::Api::query_sprite_info_along_line(&api::Default);
+	let ptr = unsafe { f(x1, y1, x2, y2, &mut len) };
+	unsafe { Results::from_raw(ptr.cast(), len) }
+}
+
+
+// sprite //
+
+impl<UD, Api: api::Api, const FOD: bool> Sprite<UD, Api, FOD> {
+	/// Moves the sprite towards `goal_x`, `goal_y` taking collisions into account.
+	///
+	/// Owned equivalent of [`move_with_collisions`](Self::move_with_collisions).
+	///
+	/// Equivalent to [`sys::ffi::playdate_sprite::moveWithCollisions`]
+	#[doc(alias = "sys::ffi::playdate_sprite::moveWithCollisions")]
+	pub fn move_colliding(&self, goal_x: c_float, goal_y: c_float) -> Movement {
+		let f = self.api_ref().move_with_collisions();
+		self.movement(goal_x, goal_y, f)
+	}
+
+	/// Returns the same values as [`move_colliding`](Self::move_colliding)
+	/// but does not actually move the sprite.
+	///
+	/// Equivalent to [`sys::ffi::playdate_sprite::checkCollisions`]
+	#[doc(alias = "sys::ffi::playdate_sprite::checkCollisions")]
+	pub fn check_move(&self, goal_x: c_float, goal_y: c_float) -> Movement {
+		let f = self.api_ref().check_collisions();
+		self.movement(goal_x, goal_y, f)
+	}
+
+	fn movement(&self,
+	            goal_x: c_float,
+	            goal_y: c_float,
+	            f: unsafe extern "C" fn(*mut LCDSprite,
+	                                 c_float,
+	                                 c_float,
+	                                 *mut c_float,
+	                                 *mut c_float,
+	                                 *mut c_int)
+	                                 -> *mut SpriteCollisionInfo)
+	            -> Movement {
+		let (mut x, mut y, mut len) = (goal_x, goal_y, 0);
+		let ptr = unsafe { f(self.as_raw(), goal_x, goal_y, &mut x, &mut y, &mut len) };
+		let collisions = unsafe { Results::from_raw(ptr.cast(), len) };
+		Movement { x, y, collisions }
+	}
+}
@@ -14,11 +14,16 @@ use sys::ffi::LCDSprite;
 pub mod ext;
 mod sprite;
 pub mod api;
+pub mod animation;
+pub mod collisions;
+pub mod registry;
+pub mod world;
 
 pub mod callback {
 	pub mod draw;
 	pub mod update;
 	pub mod collision;
+	pub mod func;
 }
 
 pub mod prelude {
@@ -28,6 +33,7 @@ pub mod prelude {
 	pub use super::callback::draw::SpriteDraw;
 	pub use super::callback::update::SpriteUpdate;
 	pub use super::callback::collision::SpriteCollisionResponse;
+	pub use super::callback::func::SpriteCb;
 
 	pub use super::{TypedSprite, SpriteApi};
 }
@@ -209,6 +215,7 @@ pub fn query_sprites_along_line(x1: c_float, y1: c_float, x2: c_float, y2: c_flo
 /// that intersect the line connecting `x1`, `y1` and `x2`, `y2`.
 ///
 /// If you don’t need this information, use [`query_sprites_along_line`] as it will be faster.
+/// See [`collisions::query_info_along_line`] for owned results.
 ///
 /// Equivalent to [`sys::ffi::playdate_sprite::querySpriteInfoAlongLine`]
 #[doc(alias = "sys::ffi::playdate_sprite::querySpriteInfoAlongLine")]
@@ -0,0 +1,392 @@
+//! Registry of sprites with their userdata types.
+//!
+//! [`Registry`] owns added sprites and remembers the type of their userdata,
+//! so results of collision queries can be filtered and downcast safely:
+//!
+//! ```no_run
+//! use playdate_sprite::Sprite;
+//! use playdate_sprite::registry::Registry;
+//!
+//! #[derive(Default)]
+//! struct Enemy {
+//!     health: u8,
+//! }
+//!
+//! let mut sprites: Registry = Registry::new();
+//! let enemy = Sprite::<Enemy>::new();
+//! enemy.set_userdata(Enemy::default());
+//! sprites.add(enemy).ok();
+//! for enemy in sprites.query_in_rect::<Enemy>(0.0, 0.0, 100.0, 100.0) {
+//!     if let Some(enemy) = enemy.userdata() {
+//!         enemy.health = enemy.health.saturating_sub(1);
+//!     }
+//! }
+//! ```
+
+use core::any::Any;
+use core::any::TypeId;
+use core::ffi::c_float;
+use core::ffi::c_int;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use sys::ffi::LCDSprite;
+use sys::traits::AsRaw;
+
+use crate::api;
+use crate::AnySprite;
+use crate::Sprite;
+use crate::SpriteRef;
+
+
+/// Registered sprite, type-erased [`Sprite<UD, Api>`].
+struct Entry {
+	userdata: TypeId,
+	sprite: Box<dyn Any>,
+}
+
+
+/// Overlapping registered sprites, see [`Registry::all_overlapping`].
+pub type Pair<'r, A, B, Api> = (&'r Sprite<A, Api, true>, &'r Sprite<B, Api, true>);
+
+
+/// Owner of sprites in the display list, tracking their userdata types.
+///
+/// Sprites are added to the display list when registered,
+/// and removed from it when unregistered or when the registry is dropped.
+pub struct Registry<Api: api::Api = api::Default> {
+	api: Api,
+	entries: BTreeMap<*mut LCDSprite, Entry>,
+}
+
+impl<Api: api::Api + core::fmt::Debug> core::fmt::Debug for Registry<Api> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Registry")
+		 .field("api", &self.api)
+		 .field("sprites", &self.entries.keys())
+		 .finish()
+	}
+}
+
+
+// ctor //
+
+impl<Api: api::Api + Default> Registry<Api> {
+	pub fn new() -> Self { Self::new_with(Default::default()) }
+}
+
+impl<Api: api::Api + Default> Default for Registry<Api> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<Api: api::Api> Registry<Api> {
+	pub fn new_with(api: Api) -> Self {
+		Self { api,
+		       entries: BTreeMap::new() }
+	}
+}
+
+
+impl<Api: api::Api> Drop for Registry<Api> {
+	fn drop(&mut self) { self.clear() }
+}
+
+
+// impl //
+
+impl<Api: api::Api> Registry<Api> {
+	/// Removes all registered sprites from the display list and frees them.
+	pub fn clear(&mut self) {
+		// sprites are freed with their entries, so remove them from the display list first
+		let entries = core::mem::take(&mut self.entries);
+		for ptr in entries.keys() {
+			self.remove_from_display(*ptr);
+		}
+		drop(entries);
+	}
+
+	fn remove_from_display(&self, ptr: *mut LCDSprite) {
+		let f = self.api.remove_sprite();
+		unsafe { f(ptr) }
+	}
+
+	/// Returns the number of registered sprites.
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+}
+
+
+impl<Api: api::Api + 'static> Registry<Api> {
+	/// Takes ownership of the `sprite` and adds it to the display list.
+	///
+	/// Returns reference to the sprite, which can be used to [get](Self::get) it back.
+	///
+	/// If the sprite is already registered, e.g. through another owning handle,
+	/// it can't be owned twice and is returned back as the error.
+	pub fn add<UD: 'static>(&mut self, sprite: Sprite<UD, Api, true>) -> Result<SpriteRef, Sprite<UD, Api, true>> {
+		let ptr = unsafe { sprite.as_raw() };
+		if self.entries.contains_key(&ptr) {
+			return Err(sprite);
+		}
+		sprite.add();
+		let entry = Entry { userdata: TypeId::of::<UD>(),
+		                    sprite: Box::new(sprite) };
+		self.entries.insert(ptr, entry);
+		Ok(SpriteRef::from(ptr))
+	}
+
+	/// Removes the `sprite` from the display list and returns it,
+	/// if it's registered with `UD` userdata.
+	pub fn take<UD: 'static>(&mut self, sprite: &impl AnySprite) -> Option<Sprite<UD, Api, true>> {
+		let ptr = unsafe { sprite.as_raw() };
+		if !self.entries.get(&ptr)?.sprite.is::<Sprite<UD, Api, true>>() {
+			return None;
+		}
+		let entry = self.entries.remove(&ptr)?;
+		self.remove_from_display(ptr);
+		entry.sprite.downcast().ok().map(|sprite| *sprite)
+	}
+
+	/// Removes the `sprite` from the display list and frees it.
+	///
+	/// Returns `false` if the sprite isn't registered.
+	pub fn remove(&mut self, sprite: &impl AnySprite) -> bool {
+		let ptr = unsafe { sprite.as_raw() };
+		let removed = self.entries.remove(&ptr);
+		if removed.is_some() {
+			self.remove_from_display(ptr);
+		}
+		removed.is_some()
+	}
+
+	/// Returns `true` if the `sprite` is registered.
+	pub fn contains(&self, sprite: &impl AnySprite) -> bool {
+		self.entries.contains_key(&unsafe { sprite.as_raw() })
+	}
+
+	/// Returns type of the `sprite`'s userdata, if it's registered.
+	pub fn userdata_type(&self, sprite: &impl AnySprite) -> Option<TypeId> {
+		self.entries
+		    .get(&unsafe { sprite.as_raw() })
+		    .map(|entry| entry.userdata)
+	}
+
+	/// Returns the `sprite` if it's registered with `UD` userdata.
+	pub fn get<UD: 'static>(&self, sprite: &impl AnySprite) -> Option<&Sprite<UD, Api, true>> {
+		self.get_raw(unsafe { sprite.as_raw() })
+	}
+
+	fn get_raw<UD: 'static>(&self, ptr: *mut LCDSprite) -> Option<&Sprite<UD, Api, true>> {
+		self.entries.get(&ptr)?.sprite.downcast_ref()
+	}
+
+	/// Iterates over all registered sprites with `UD` userdata.
+	pub fn iter<UD: 'static>(&self) -> impl Iterator<Item = &Sprite<UD, Api, true>> {
+		let id = TypeId::of::<UD>();
+		self.entries
+		    .values()
+		    .filter(move |entry| entry.userdata == id)
+		    .filter_map(|entry| entry.sprite.downcast_ref())
+	}
+
+	/// Iterates over all registered sprites.
+	pub fn sprites(&self) -> impl Iterator<Item = SpriteRef> + '_ {
+		self.entries.keys().map(|ptr| SpriteRef::from(*ptr))
+	}
+
+	/// Iterates over all registered sprites with the `tag`.
+	pub fn tagged(&self, tag: u8) -> impl Iterator<Item = SpriteRef> + '_ {
+		let f = self.api.get_tag();
+		self.sprites()
+		    .filter(move |sprite| unsafe { f(sprite.as_raw()) } == tag)
+	}
+
+	/// Iterates over all registered sprites with `UD` userdata and the `tag`.
+	pub fn iter_tagged<UD: 'static>(&self, tag: u8) -> impl Iterator<Item = &Sprite<UD, Api, true>> {
+		self.iter::<UD>().filter(move |sprite| sprite.tag() == tag)
+	}
+
+	/// Returns the first registered sprite with `UD` userdata and the `tag`.
+	pub fn find_tagged<UD: 'static>(&self, tag: u8) -> Option<&Sprite<UD, Api, true>> {
+		self.iter_tagged(tag).next()
+	}
+
+
+	/// Returns registered sprites with `UD` userdata
+	/// and collision rects containing the point at `x`, `y`.
+	///
+	/// Uses [`sys::ffi::playdate_sprite::querySpritesAtPoint`]
+	pub fn query_at_point<UD: 'static>(&self, x: c_float, y: c_float) -> Vec<&Sprite<UD, Api, true>> {
+		let f = self.api.query_sprites_at_point();
+		self.typed(|len| unsafe { f(x, y, len) })
+	}
+
+	/// Returns registered sprites with `UD` userdata
+	/// and collision rects that intersect the `width` by `height` rect at `x`, `y`.
+	///
+	/// Uses [`sys::ffi::playdate_sprite::querySpritesInRect`]
+	pub fn query_in_rect<UD: 'static>(&self,
+	                                  x: c_float,
+	                                  y: c_float,
+	                                  width: c_float,
+	                                  height: c_float)
+	                                  -> Vec<&Sprite<UD, Api, true>> {
+		let f = self.api.query_sprites_in_rect();
+		self.typed(|len| unsafe { f(x, y, width, height, len) })
+	}
+
+	/// Returns registered sprites with `UD` userdata
+	/// and collision rects that intersect the line connecting `x1`, `y1` and `x2`, `y2`.
+	///
+	/// Uses [`sys::ffi::playdate_sprite::querySpritesAlongLine`]
+	pub fn query_along_line<UD: 'static>(&self,
+	                                     x1: c_float,
+	                                     y1: c_float,
+	                                     x2: c_float,
+	                                     y2: c_float)
+	                                     -> Vec<&Sprite<UD, Api, true>> {
+		let f = self.api.query_sprites_along_line();
+		self.typed(|len| unsafe { f(x1, y1, x2, y2, len) })
+	}
+
+	/// Returns registered sprites with `UD` userdata that overlap the `sprite`.
+	///
+	/// Uses [`sys::ffi::playdate_sprite::overlappingSprites`]
+	pub fn overlapping<UD: 'static>(&self, sprite: &impl AnySprite) -> Vec<&Sprite<UD, Api, true>> {
+		let f = self.api.overlapping_sprites();
+		let ptr = unsafe { sprite.as_raw() };
+		self.typed(|len| unsafe { f(ptr, len) })
+	}
+
+	/// Returns pairs of currently overlapping registered sprites
+	/// with `A` and `B` userdata, in that order.
+	///
+	/// Uses [`sys::ffi::playdate_sprite::allOverlappingSprites`]
+	pub fn all_overlapping<A: 'static, B: 'static>(&self) -> Vec<Pair<'_, A, B, Api>> {
+		let f = self.api.all_overlapping_sprites();
+		let ptrs = query(|len| unsafe { f(len) });
+		ptrs.chunks_exact(2)
+		    .filter_map(|pair| {
+			    let (a, b) = (pair[0], pair[1]);
+			    self.get_raw(a)
+			        .zip(self.get_raw(b))
+			        .or_else(|| self.get_raw(b).zip(self.get_raw(a)))
+		    })
+		    .collect()
+	}
+
+	/// Calls the query `f` and returns found registered sprites with `UD` userdata.
+	fn typed<UD: 'static>(&self,
+	                      f: impl FnOnce(*mut c_int) -> *mut *mut LCDSprite)
+	                      -> Vec<&Sprite<UD, Api, true>> {
+		query(f).into_iter().filter_map(|ptr| self.get_raw(ptr)).collect()
+	}
+}
+
+
+/// Calls the query `f`, copies the returned sprites and frees the returned array.
+fn query(f: impl FnOnce(*mut c_int) -> *mut *mut LCDSprite) -> Vec<*mut LCDSprite> {
+	let mut len: c_int = 0;
+	let ptr = f(&mut len);
+	if ptr.is_null() {
+		return Vec::new();
+	}
+	let result = unsafe { core::slice::from_raw_parts(ptr, len.max(0) as _) }.to_vec();
+	let realloc = *sys::api!(system.realloc);
+	unsafe { realloc(ptr as _, 0) };
+	result
+}
+
+
+#[cfg(test)]
+mod tests {
+	use core::cell::RefCell;
+	use core::ffi::c_void;
+
+	use super::*;
+
+
+	std::thread_local! {
+		static CALLS: RefCell<Vec<(&'static str, *mut LCDSprite)>> = const { RefCell::new(Vec::new()) };
+	}
+
+	fn log(call: &'static str, sprite: *mut LCDSprite) {
+		CALLS.with(|calls| calls.borrow_mut().push((call, sprite)))
+	}
+
+	fn calls() -> Vec<(&'static str, *mut LCDSprite)> { CALLS.with(|calls| calls.take()) }
+
+
+	/// Sprite end-point logging removals and frees of the sprites.
+	#[derive(Debug, Clone, Copy, Default)]
+	struct Fake;
+
+	impl api::Api for Fake {
+		fn new_sprite(&self) -> unsafe extern "C" fn() -> *mut LCDSprite {
+			unsafe extern "C" fn f() -> *mut LCDSprite { Box::into_raw(Box::new(0_u8)) as _ }
+			f
+		}
+
+		fn free_sprite(&self) -> unsafe extern "C" fn(sprite: *mut LCDSprite) {
+			unsafe extern "C" fn f(sprite: *mut LCDSprite) {
+				log("free", sprite);
+				drop(Box::from_raw(sprite as *mut u8));
+			}
+			f
+		}
+
+		fn add_sprite(&self) -> unsafe extern "C" fn(sprite: *mut LCDSprite) {
+			unsafe extern "C" fn f(_: *mut LCDSprite) {}
+			f
+		}
+
+		fn remove_sprite(&self) -> unsafe extern "C" fn(sprite: *mut LCDSprite) {
+			unsafe extern "C" fn f(sprite: *mut LCDSprite) { log("remove", sprite) }
+			f
+		}
+
+		fn set_userdata(&self) -> unsafe extern "C" fn(sprite: *mut LCDSprite, userdata: *mut c_void) {
+			unsafe extern "C" fn f(_: *mut LCDSprite, _: *mut c_void) {}
+			f
+		}
+
+		fn get_userdata(&self) -> unsafe extern "C" fn(sprite: *mut LCDSprite) -> *mut c_void {
+			unsafe extern "C" fn f(_: *mut LCDSprite) -> *mut c_void { core::ptr::null_mut() }
+			f
+		}
+	}
+
+	/// Adds two sprites, returns their pointers.
+	fn populate(sprites: &mut Registry<Fake>) -> [*mut LCDSprite; 2] {
+		[(); 2].map(|_| {
+			       let sprite = sprites.add(Sprite::<(), _>::new_with(Fake)).ok().unwrap();
+			       unsafe { sprite.as_raw() }
+		       })
+	}
+
+	fn assert_removed_before_freed(sprites: [*mut LCDSprite; 2]) {
+		let calls = calls();
+		assert_eq!(4, calls.len());
+		let (removed, freed) = calls.split_at(2);
+		for sprite in sprites {
+			assert!(removed.contains(&("remove", sprite)));
+			assert!(freed.contains(&("free", sprite)));
+		}
+	}
+
+
+	#[test]
+	fn clear_removes_before_freeing() {
+		let mut sprites = Registry::new_with(Fake);
+		let added = populate(&mut sprites);
+		sprites.clear();
+		assert!(sprites.is_empty());
+		assert_removed_before_freed(added);
+
+		let added = populate(&mut sprites);
+		drop(sprites);
+		assert_removed_before_freed(added);
+	}
+}
@@ -521,6 +521,7 @@ impl<Userdata, Api: api::Api, const FOD: bool> Sprite<Userdata, Api, FOD> {
 	}
 
 	/// Returns the same values as [`move_with_collisions`] but does not actually move the sprite.
+	/// See [`check_move`](Self::check_move) for owned results.
 	///
 	/// Equivalent to [`sys::ffi::playdate_sprite::checkCollisions`]
 	#[doc(alias = "sys::ffi::playdate_sprite::check_collisions")]
@@ -550,6 +551,7 @@ impl<Userdata, Api: api::Api, const FOD: bool> Sprite<Userdata, Api, FOD> {
 	/// If no collisions occurred, this will be the same as `goal_x`, `goal_y`.
 	///
 	/// Resulting slice with entire content can be freely dropped.
+	/// See [`move_colliding`](Self::move_colliding) for owned results.
 	///
 	/// Equivalent to [`sys::ffi::playdate_sprite::moveWithCollisions`]
 	#[doc(alias = "sys::ffi::playdate_sprite::moveWithCollisions")]
@@ -695,6 +697,21 @@ impl<Userdata, Api: api::Api, const FOD: bool> Sprite<Userdata, Api, FOD> {
 			Some(ud)
 		}
 	}
+
+	/// Changes type of the userdata without touching it,
+	/// caller is responsible for replacing the userdata with `T`.
+	pub(crate) fn into_userdata_type<T>(self) -> Sprite<T, Api, FOD> {
+		let sprite = core::mem::ManuallyDrop::new(self);
+		let api = unsafe { core::ptr::read(&sprite.1) };
+		Sprite(sprite.0, api, PhantomData)
+	}
+}
+
+
+/// Positioned sounds can follow sprites.
+#[cfg(feature = "sound")]
+impl<UD, Api: api::Api, const FOD: bool> sound::spatial::Position for Sprite<UD, Api, FOD> {
+	fn position(&self) -> (c_float, c_float) { Sprite::position(self) }
 }
 
 
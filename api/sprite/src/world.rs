@@ -0,0 +1,846 @@
+//! Pure-Rust collision world with the same semantics as the sprite collisions.
+//!
+//! [`World`] keeps axis-aligned rects of items in a spatial hash,
+//! moves them with [slide](Response::Slide), [freeze](Response::Freeze),
+//! [overlap](Response::Overlap) and [bounce](Response::Bounce) responses
+//! and answers point, rect and segment queries
+//! as [`move_with_collisions`](crate::Sprite::move_with_collisions) and the `query_*` functions do,
+//! without the system, e.g. in tests or for off-screen simulation.
+//!
+//! ```
+//! use playdate_sprite::world::Rect;
+//! use playdate_sprite::world::World;
+//!
+//! #[derive(Debug)]
+//! enum Kind {
+//!     Player,
+//!     Wall,
+//! }
+//!
+//! let mut world = World::new();
+//! let player = world.add(Rect::new(0.0, 0.0, 16.0, 16.0), Kind::Player);
+//! let wall = world.add(Rect::new(32.0, 0.0, 16.0, 64.0), Kind::Wall);
+//! let moved = world.move_item(player, 64.0, 0.0);
+//! assert_eq!((16.0, 0.0), (moved.x, moved.y));
+//! assert_eq!(wall, moved.collisions[0].other);
+//! ```
+
+use core::ffi::c_float;
+use core::ops::RangeInclusive;
+use alloc::collections::BTreeMap;
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use sys::ffi::SpriteCollisionResponseType;
+
+
+const DELTA: c_float = 1e-10;
+
+/// Default size of the spatial hash cells.
+pub const CELL_SIZE: c_float = 64.0;
+
+
+/// Axis-aligned rect.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+	pub x: c_float,
+	pub y: c_float,
+	pub width: c_float,
+	pub height: c_float,
+}
+
+impl Rect {
+	pub const fn new(x: c_float, y: c_float, width: c_float, height: c_float) -> Self {
+		Self { x, y, width, height }
+	}
+
+	pub fn right(&self) -> c_float { self.x + self.width }
+	pub fn bottom(&self) -> c_float { self.y + self.height }
+
+	/// Returns `true` if the point at `x`, `y` is strictly inside the rect.
+	pub fn contains(&self, x: c_float, y: c_float) -> bool {
+		x - self.x > DELTA && y - self.y > DELTA && self.right() - x > DELTA && self.bottom() - y > DELTA
+	}
+
+	/// Returns `true` if rects intersect, touching edges don't.
+	pub fn intersects(&self, other: &Rect) -> bool {
+		self.x < other.right() && other.x < self.right() && self.y < other.bottom() && other.y < self.bottom()
+	}
+
+	fn square_distance(&self, other: &Rect) -> c_float {
+		let dx = self.x - other.x + (self.width - other.width) / 2.0;
+		let dy = self.y - other.y + (self.height - other.height) / 2.0;
+		dx * dx + dy * dy
+	}
+
+	/// Minkowski difference of the rects.
+	fn diff(&self, other: &Rect) -> Rect {
+		Rect::new(
+		          other.x - self.x - self.width,
+		          other.y - self.y - self.height,
+		          self.width + other.width,
+		          self.height + other.height,
+		)
+	}
+
+	fn nearest_corner(&self, x: c_float, y: c_float) -> (c_float, c_float) {
+		(nearest(x, self.x, self.right()), nearest(y, self.y, self.bottom()))
+	}
+
+	/// Liang-Barsky clipping of the segment, returns entry and exit times with the entry normal.
+	fn segment_intersection(&self,
+	                        (x1, y1): (c_float, c_float),
+	                        (x2, y2): (c_float, c_float),
+	                        (mut ti1, mut ti2): (c_float, c_float))
+	                        -> Option<Clip> {
+		let (dx, dy) = (x2 - x1, y2 - y1);
+		let mut normal1 = (0, 0);
+		let sides = [
+		             ((-1, 0), -dx, x1 - self.x),
+		             ((1, 0), dx, self.right() - x1),
+		             ((0, -1), -dy, y1 - self.y),
+		             ((0, 1), dy, self.bottom() - y1),
+		];
+		for (normal, p, q) in sides {
+			if p == 0.0 {
+				if q <= 0.0 {
+					return None;
+				}
+			} else {
+				let r = q / p;
+				if p < 0.0 {
+					if r > ti2 {
+						return None;
+					} else if r > ti1 {
+						ti1 = r;
+						normal1 = normal;
+					}
+				} else if r < ti1 {
+					return None;
+				} else if r < ti2 {
+					ti2 = r;
+				}
+			}
+		}
+		Some(Clip { ti1, ti2, normal1 })
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Clip {
+	ti1: c_float,
+	ti2: c_float,
+	normal1: (i32, i32),
+}
+
+fn nearest(x: c_float, a: c_float, b: c_float) -> c_float {
+	if libm::fabsf(a - x) < libm::fabsf(b - x) {
+		a
+	} else {
+		b
+	}
+}
+
+fn sign(x: c_float) -> i32 {
+	if x > 0.0 {
+		1
+	} else if x < 0.0 {
+		-1
+	} else {
+		0
+	}
+}
+
+
+/// Collision response, what happens to the moving item when it hits another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Response {
+	/// Slides along the other item.
+	Slide,
+	/// Stops at the touch point.
+	#[default]
+	Freeze,
+	/// Passes through the other item, the collision is still reported.
+	Overlap,
+	/// Bounces off the other item.
+	Bounce,
+}
+
+impl From<Response> for SpriteCollisionResponseType {
+	fn from(response: Response) -> Self {
+		match response {
+			Response::Slide => Self::kCollisionTypeSlide,
+			Response::Freeze => Self::kCollisionTypeFreeze,
+			Response::Overlap => Self::kCollisionTypeOverlap,
+			Response::Bounce => Self::kCollisionTypeBounce,
+		}
+	}
+}
+
+impl From<SpriteCollisionResponseType> for Response {
+	fn from(response: SpriteCollisionResponseType) -> Self {
+		match response {
+			SpriteCollisionResponseType::kCollisionTypeSlide => Self::Slide,
+			SpriteCollisionResponseType::kCollisionTypeFreeze => Self::Freeze,
+			SpriteCollisionResponseType::kCollisionTypeOverlap => Self::Overlap,
+			SpriteCollisionResponseType::kCollisionTypeBounce => Self::Bounce,
+		}
+	}
+}
+
+
+/// Handle of an item in a [`World`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Item {
+	index: u32,
+	generation: u32,
+}
+
+
+/// Collision of a moving item, fields as in [`SpriteCollisionInfo`](sys::ffi::SpriteCollisionInfo).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+	/// The item being moved.
+	pub item: Item,
+	/// The item colliding with the item being moved.
+	pub other: Item,
+	pub response: Response,
+	/// `true` if the item was overlapping other when the collision started,
+	/// `false` if it didn’t overlap but tunneled through other.
+	pub overlaps: bool,
+	/// How far along the movement to the goal the collision occurred, between `0` and `1`,
+	/// or negative area of the intersection if the item was overlapping other.
+	pub ti: c_float,
+	/// The difference between the original coordinates and the goal.
+	pub movement: (c_float, c_float),
+	/// The collision normal, `-1`, `0`, or `1` in `x` and `y`.
+	pub normal: (i32, i32),
+	/// The coordinates where the item started touching other.
+	pub touch: (c_float, c_float),
+	/// The rect the item occupied when the collision was checked.
+	pub item_rect: Rect,
+	/// The rect of the other item.
+	pub other_rect: Rect,
+}
+
+impl Collision {
+	/// Detects collision of the `rect` moving to `goal` with the `other` rect.
+	fn detect(rect: &Rect, other: &Rect, (goal_x, goal_y): (c_float, c_float)) -> Option<Self> {
+		let (dx, dy) = (goal_x - rect.x, goal_y - rect.y);
+		let diff = rect.diff(other);
+
+		let (overlaps, ti, mut normal) = if diff.contains(0.0, 0.0) {
+			// was intersecting other
+			let (px, py) = diff.nearest_corner(0.0, 0.0);
+			let area = rect.width.min(libm::fabsf(px)) * rect.height.min(libm::fabsf(py));
+			(true, -area, (0, 0))
+		} else {
+			let inf = c_float::INFINITY;
+			let clip = diff.segment_intersection((0.0, 0.0), (dx, dy), (-inf, inf))?;
+			// tunnels into other
+			if clip.ti1 < 1.0 &&
+			   libm::fabsf(clip.ti1 - clip.ti2) >= DELTA &&
+			   (0.0 < clip.ti1 + DELTA || 0.0 == clip.ti1 && clip.ti2 > 0.0)
+			{
+				(false, clip.ti1, clip.normal1)
+			} else {
+				return None;
+			}
+		};
+
+		let touch = if overlaps {
+			if dx == 0.0 && dy == 0.0 {
+				// not moving, use minimum displacement
+				let (mut px, mut py) = diff.nearest_corner(0.0, 0.0);
+				if libm::fabsf(px) < libm::fabsf(py) {
+					py = 0.0;
+				} else {
+					px = 0.0;
+				}
+				normal = (sign(px), sign(py));
+				(rect.x + px, rect.y + py)
+			} else {
+				// moving, go back along the movement
+				let clip = diff.segment_intersection((0.0, 0.0), (dx, dy), (-c_float::INFINITY, 1.0))?;
+				normal = clip.normal1;
+				(rect.x + dx * clip.ti1, rect.y + dy * clip.ti1)
+			}
+		} else {
+			(rect.x + dx * ti, rect.y + dy * ti)
+		};
+
+		Some(Self { item: Item { index: 0,
+		                         generation: 0 },
+		            other: Item { index: 0,
+		                          generation: 0 },
+		            response: Response::Freeze,
+		            overlaps,
+		            ti,
+		            movement: (dx, dy),
+		            normal,
+		            touch,
+		            item_rect: *rect,
+		            other_rect: *other })
+	}
+}
+
+
+/// Result of an item movement with collisions.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Movement {
+	/// Position of the item after collisions.
+	pub x: c_float,
+	pub y: c_float,
+	pub collisions: Vec<Collision>,
+}
+
+
+/// Item intersecting a segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentInfo {
+	pub item: Item,
+	/// Entry, between `0` and `1` along the segment.
+	pub ti1: c_float,
+	/// Exit, between `0` and `1` along the segment.
+	pub ti2: c_float,
+	pub entry_point: (c_float, c_float),
+	pub exit_point: (c_float, c_float),
+}
+
+
+#[derive(Debug)]
+struct Entry<T> {
+	rect: Rect,
+	response: Response,
+	collisions_enabled: bool,
+	data: T,
+}
+
+
+/// Collision world of items with `T` data.
+#[derive(Debug)]
+pub struct World<T = ()> {
+	cell_size: c_float,
+	entries: Vec<(u32, Option<Entry<T>>)>,
+	free: Vec<u32>,
+	cells: BTreeMap<(i32, i32), BTreeSet<Item>>,
+}
+
+
+// ctor //
+
+impl<T> World<T> {
+	pub fn new() -> Self { Self::with_cell_size(CELL_SIZE) }
+
+	/// Creates world with spatial hash of `cell_size` cells,
+	/// about the size of typical items.
+	pub fn with_cell_size(cell_size: c_float) -> Self {
+		Self { cell_size,
+		       entries: Vec::new(),
+		       free: Vec::new(),
+		       cells: BTreeMap::new() }
+	}
+}
+
+impl<T> Default for World<T> {
+	fn default() -> Self { Self::new() }
+}
+
+
+// items //
+
+impl<T> World<T> {
+	/// Adds item with the `rect` and `data`, colliding with [`Response::Freeze`] by default.
+	pub fn add(&mut self, rect: Rect, data: T) -> Item {
+		let entry = Entry { rect,
+		                    response: Response::default(),
+		                    collisions_enabled: true,
+		                    data };
+		let item = if let Some(index) = self.free.pop() {
+			let slot = &mut self.entries[index as usize];
+			slot.0 = slot.0.wrapping_add(1);
+			slot.1 = Some(entry);
+			Item { index,
+			       generation: slot.0 }
+		} else {
+			self.entries.push((0, Some(entry)));
+			Item { index: self.entries.len() as u32 - 1,
+			       generation: 0 }
+		};
+		self.insert_cells(item, &rect);
+		item
+	}
+
+	/// Removes the item, returns its data.
+	pub fn remove(&mut self, item: Item) -> Option<T> {
+		let rect = self.rect(item)?;
+		self.remove_cells(item, &rect);
+		self.free.push(item.index);
+		self.entries[item.index as usize].1.take().map(|entry| entry.data)
+	}
+
+	pub fn contains(&self, item: Item) -> bool { self.entry(item).is_some() }
+
+	pub fn len(&self) -> usize { self.entries.len() - self.free.len() }
+
+	pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+	/// Iterates over all items.
+	pub fn items(&self) -> impl Iterator<Item = Item> + '_ {
+		self.entries
+		    .iter()
+		    .enumerate()
+		    .filter(|(_, (_, entry))| entry.is_some())
+		    .map(|(index, (generation, _))| {
+			    Item { index: index as u32,
+			           generation: *generation }
+		    })
+	}
+
+	fn entry(&self, item: Item) -> Option<&Entry<T>> {
+		match self.entries.get(item.index as usize)? {
+			(generation, Some(entry)) if *generation == item.generation => Some(entry),
+			_ => None,
+		}
+	}
+
+	fn entry_mut(&mut self, item: Item) -> Option<&mut Entry<T>> {
+		match self.entries.get_mut(item.index as usize)? {
+			(generation, Some(entry)) if *generation == item.generation => Some(entry),
+			_ => None,
+		}
+	}
+
+	pub fn data(&self, item: Item) -> Option<&T> { self.entry(item).map(|entry| &entry.data) }
+	pub fn data_mut(&mut self, item: Item) -> Option<&mut T> { self.entry_mut(item).map(|entry| &mut entry.data) }
+
+	pub fn rect(&self, item: Item) -> Option<Rect> { self.entry(item).map(|entry| entry.rect) }
+
+	/// Moves and resizes the item without collisions.
+	pub fn set_rect(&mut self, item: Item, rect: Rect) {
+		if let Some(previous) = self.rect(item) {
+			self.remove_cells(item, &previous);
+			self.insert_cells(item, &rect);
+			self.entry_mut(item).unwrap().rect = rect;
+		}
+	}
+
+	/// Moves the item to `x`, `y` without collisions.
+	pub fn set_position(&mut self, item: Item, x: c_float, y: c_float) {
+		if let Some(rect) = self.rect(item) {
+			self.set_rect(item, Rect { x, y, ..rect })
+		}
+	}
+
+	/// Sets response of the item when it moves into others.
+	pub fn set_response(&mut self, item: Item, response: Response) {
+		if let Some(entry) = self.entry_mut(item) {
+			entry.response = response;
+		}
+	}
+
+	pub fn response(&self, item: Item) -> Option<Response> { self.entry(item).map(|entry| entry.response) }
+
+	/// Disabled items are ignored by collisions and queries.
+	pub fn set_collisions_enabled(&mut self, item: Item, value: bool) {
+		if let Some(entry) = self.entry_mut(item) {
+			entry.collisions_enabled = value;
+		}
+	}
+
+	pub fn collisions_enabled(&self, item: Item) -> bool {
+		self.entry(item).is_some_and(|entry| entry.collisions_enabled)
+	}
+}
+
+
+// spatial hash //
+
+impl<T> World<T> {
+	fn cell_range(&self, rect: &Rect) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
+		let range = |start: c_float, end: c_float| {
+			let first = libm::floorf(start / self.cell_size) as i32;
+			let last = libm::ceilf(end / self.cell_size) as i32 - 1;
+			first..=last.max(first)
+		};
+		(range(rect.x, rect.right()), range(rect.y, rect.bottom()))
+	}
+
+	fn insert_cells(&mut self, item: Item, rect: &Rect) {
+		let (xs, ys) = self.cell_range(rect);
+		for cy in ys {
+			for cx in xs.clone() {
+				self.cells.entry((cx, cy)).or_default().insert(item);
+			}
+		}
+	}
+
+	fn remove_cells(&mut self, item: Item, rect: &Rect) {
+		let (xs, ys) = self.cell_range(rect);
+		for cy in ys {
+			for cx in xs.clone() {
+				if let Some(cell) = self.cells.get_mut(&(cx, cy)) {
+					cell.remove(&item);
+					if cell.is_empty() {
+						self.cells.remove(&(cx, cy));
+					}
+				}
+			}
+		}
+	}
+
+	/// Returns enabled items in cells touched by the `rect`.
+	fn candidates(&self, rect: &Rect) -> BTreeSet<Item> {
+		let (xs, ys) = self.cell_range(rect);
+		let mut items = BTreeSet::new();
+		for cy in ys {
+			for cx in xs.clone() {
+				if let Some(cell) = self.cells.get(&(cx, cy)) {
+					items.extend(cell.iter().filter(|item| self.collisions_enabled(**item)));
+				}
+			}
+		}
+		items
+	}
+}
+
+
+// movement //
+
+impl<T> World<T> {
+	/// Moves the item towards `goal_x`, `goal_y` taking collisions into account,
+	/// with its [response](Self::set_response) to every other item.
+	pub fn move_item(&mut self, item: Item, goal_x: c_float, goal_y: c_float) -> Movement {
+		let response = self.response(item).unwrap_or_default();
+		self.move_with(item, goal_x, goal_y, |_, _| Some(response))
+	}
+
+	/// Moves the item towards `goal_x`, `goal_y` taking collisions into account,
+	/// with response to the other item returned by `filter`, `None` ignores the other.
+	pub fn move_with<F>(&mut self, item: Item, goal_x: c_float, goal_y: c_float, filter: F) -> Movement
+		where F: FnMut(Item, Item) -> Option<Response> {
+		let movement = self.check_with(item, goal_x, goal_y, filter);
+		if self.contains(item) {
+			self.set_position(item, movement.x, movement.y);
+		}
+		movement
+	}
+
+	/// Returns the same values as [`move_item`](Self::move_item) but does not actually move the item.
+	pub fn check(&self, item: Item, goal_x: c_float, goal_y: c_float) -> Movement {
+		let response = self.response(item).unwrap_or_default();
+		self.check_with(item, goal_x, goal_y, |_, _| Some(response))
+	}
+
+	/// Returns the same values as [`move_with`](Self::move_with) but does not actually move the item.
+	///
+	/// Item with collisions disabled reaches the goal without collisions.
+	pub fn check_with<F>(&self, item: Item, mut goal_x: c_float, mut goal_y: c_float, mut filter: F) -> Movement
+		where F: FnMut(Item, Item) -> Option<Response> {
+		let mut rect = match self.entry(item) {
+			Some(entry) if entry.collisions_enabled => entry.rect,
+			_ => {
+				return Movement { x: goal_x,
+				                  y: goal_y,
+				                  collisions: Vec::new() };
+			},
+		};
+
+		let mut visited = BTreeSet::new();
+		visited.insert(item);
+		let mut collisions = Vec::new();
+		let mut projected = self.project(item, &rect, (goal_x, goal_y), &visited, &mut filter);
+
+		while let Some(collision) = projected.first().copied() {
+			collisions.push(collision);
+			visited.insert(collision.other);
+
+			let (touch_x, touch_y) = collision.touch;
+			let moving = collision.movement != (0.0, 0.0);
+			match collision.response {
+				Response::Freeze => {
+					goal_x = touch_x;
+					goal_y = touch_y;
+					break;
+				},
+				Response::Overlap => {},
+				Response::Slide => {
+					if moving {
+						if collision.normal.0 != 0 {
+							goal_x = touch_x;
+						} else {
+							goal_y = touch_y;
+						}
+					}
+					rect.x = touch_x;
+					rect.y = touch_y;
+				},
+				Response::Bounce => {
+					if moving {
+						let (mut bx, mut by) = (goal_x - touch_x, goal_y - touch_y);
+						if collision.normal.0 == 0 {
+							by = -by;
+						} else {
+							bx = -bx;
+						}
+						goal_x = touch_x + bx;
+						goal_y = touch_y + by;
+					} else {
+						goal_x = touch_x;
+						goal_y = touch_y;
+					}
+					rect.x = touch_x;
+					rect.y = touch_y;
+				},
+			}
+			projected = self.project(item, &rect, (goal_x, goal_y), &visited, &mut filter);
+		}
+
+		Movement { x: goal_x,
+		           y: goal_y,
+		           collisions }
+	}
+
+	/// Returns collisions of the item at `rect` moving to `goal`, sorted by time and distance.
+	fn project<F>(&self,
+	              item: Item,
+	              rect: &Rect,
+	              goal: (c_float, c_float),
+	              visited: &BTreeSet<Item>,
+	              filter: &mut F)
+	              -> Vec<Collision>
+		where F: FnMut(Item, Item) -> Option<Response>
+	{
+		let left = goal.0.min(rect.x);
+		let top = goal.1.min(rect.y);
+		let right = (goal.0 + rect.width).max(rect.right());
+		let bottom = (goal.1 + rect.height).max(rect.bottom());
+		let area = Rect::new(left, top, right - left, bottom - top);
+
+		let mut collisions: Vec<Collision> = self.candidates(&area)
+		                                         .into_iter()
+		                                         .filter(|other| !visited.contains(other))
+		                                         .filter_map(|other| {
+			                                         let response = filter(item, other)?;
+			                                         let mut collision =
+				                                         Collision::detect(rect, &self.rect(other)?, goal)?;
+			                                         collision.item = item;
+			                                         collision.other = other;
+			                                         collision.response = response;
+			                                         Some(collision)
+		                                         })
+		                                         .collect();
+
+		collisions.sort_by(|a, b| {
+			          if a.ti == b.ti {
+				          let da = a.item_rect.square_distance(&a.other_rect);
+				          let db = a.item_rect.square_distance(&b.other_rect);
+				          da.total_cmp(&db)
+			          } else {
+				          a.ti.total_cmp(&b.ti)
+			          }
+		          });
+		collisions
+	}
+}
+
+
+// queries //
+
+impl<T> World<T> {
+	/// Returns items with rects containing the point at `x`, `y`.
+	pub fn query_at_point(&self, x: c_float, y: c_float) -> Vec<Item> {
+		self.candidates(&Rect::new(x, y, 0.0, 0.0))
+		    .into_iter()
+		    .filter(|item| self.rect(*item).is_some_and(|rect| rect.contains(x, y)))
+		    .collect()
+	}
+
+	/// Returns items with rects that intersect the `width` by `height` rect at `x`, `y`.
+	pub fn query_in_rect(&self, x: c_float, y: c_float, width: c_float, height: c_float) -> Vec<Item> {
+		let area = Rect::new(x, y, width, height);
+		self.candidates(&area)
+		    .into_iter()
+		    .filter(|item| self.rect(*item).is_some_and(|rect| rect.intersects(&area)))
+		    .collect()
+	}
+
+	/// Returns items with rects that intersect the segment from `x1`, `y1` to `x2`, `y2`,
+	/// sorted from the start of the segment.
+	pub fn query_along_line(&self, x1: c_float, y1: c_float, x2: c_float, y2: c_float) -> Vec<Item> {
+		self.query_info_along_line(x1, y1, x2, y2)
+		    .into_iter()
+		    .map(|info| info.item)
+		    .collect()
+	}
+
+	/// Returns [`SegmentInfo`]s for items with rects that intersect the segment from `x1`, `y1` to `x2`, `y2`,
+	/// sorted from the start of the segment.
+	pub fn query_info_along_line(&self, x1: c_float, y1: c_float, x2: c_float, y2: c_float) -> Vec<SegmentInfo> {
+		let area = Rect::new(x1.min(x2), y1.min(y2), libm::fabsf(x2 - x1), libm::fabsf(y2 - y1));
+		let (dx, dy) = (x2 - x1, y2 - y1);
+		let inf = c_float::INFINITY;
+
+		let mut infos: Vec<(c_float, SegmentInfo)> =
+			self.candidates(&area)
+			    .into_iter()
+			    .filter_map(|item| {
+				    let rect = self.rect(item)?;
+				    let clip = rect.segment_intersection((x1, y1), (x2, y2), (0.0, 1.0))?;
+				    if !((0.0 < clip.ti1 && clip.ti1 < 1.0) || (0.0 < clip.ti2 && clip.ti2 < 1.0)) {
+					    return None;
+				    }
+				    // sorted by the infinite line, not the segment
+				    let line = rect.segment_intersection((x1, y1), (x2, y2), (-inf, inf))?;
+				    let info = SegmentInfo { item,
+				                             ti1: clip.ti1,
+				                             ti2: clip.ti2,
+				                             entry_point: (x1 + dx * clip.ti1, y1 + dy * clip.ti1),
+				                             exit_point: (x1 + dx * clip.ti2, y1 + dy * clip.ti2) };
+				    Some((line.ti1.min(line.ti2), info))
+			    })
+			    .collect();
+		infos.sort_by(|a, b| a.0.total_cmp(&b.0));
+		infos.into_iter().map(|(_, info)| info).collect()
+	}
+
+	/// Returns pairs of currently overlapping items, each pair once.
+	pub fn all_overlapping(&self) -> Vec<(Item, Item)> {
+		let mut pairs = Vec::new();
+		for item in self.items().filter(|item| self.collisions_enabled(*item)) {
+			let rect = self.rect(item).unwrap();
+			for other in self.candidates(&rect) {
+				if item < other && self.rect(other).is_some_and(|other| rect.intersects(&other)) {
+					pairs.push((item, other));
+				}
+			}
+		}
+		pairs
+	}
+
+	/// Returns items overlapping the item.
+	pub fn overlapping(&self, item: Item) -> Vec<Item> {
+		let Some(rect) = self.rect(item) else {
+			return Vec::new();
+		};
+		self.candidates(&rect)
+		    .into_iter()
+		    .filter(|other| *other != item && self.rect(*other).is_some_and(|other| rect.intersects(&other)))
+		    .collect()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::*;
+
+
+	#[test]
+	fn responses() {
+		let mut world = World::new();
+		let item = world.add(Rect::new(0.0, 0.0, 10.0, 10.0), ());
+		let wall = world.add(Rect::new(20.0, -50.0, 10.0, 100.0), ());
+
+		// freeze at the touch point
+		let moved = world.check(item, 40.0, 10.0);
+		assert_eq!((10.0, 2.5), (moved.x, moved.y));
+		assert_eq!(1, moved.collisions.len());
+		let collision = moved.collisions[0];
+		assert_eq!(
+		           (wall, false, (-1, 0), (10.0, 2.5)),
+		           (
+			collision.other,
+			collision.overlaps,
+			collision.normal,
+			collision.touch
+		)
+		);
+		assert_eq!(0.25, collision.ti);
+
+		// slide along the wall
+		let moved = world.check_with(item, 40.0, 10.0, |_, _| Some(Response::Slide));
+		assert_eq!((10.0, 10.0), (moved.x, moved.y));
+
+		// bounce back
+		let moved = world.check_with(item, 40.0, 10.0, |_, _| Some(Response::Bounce));
+		assert_eq!((-20.0, 10.0), (moved.x, moved.y));
+
+		// pass through
+		let moved = world.move_with(item, 40.0, 10.0, |_, _| Some(Response::Overlap));
+		assert_eq!((40.0, 10.0), (moved.x, moved.y));
+		assert_eq!(1, moved.collisions.len());
+		assert_eq!(Some(Rect::new(40.0, 10.0, 10.0, 10.0)), world.rect(item));
+
+		// ignored
+		world.set_position(item, 0.0, 0.0);
+		let moved = world.move_with(item, 40.0, 0.0, |_, _| None);
+		assert!(moved.collisions.is_empty());
+	}
+
+	#[test]
+	fn mover_without_collisions() {
+		let mut world = World::new();
+		let item = world.add(Rect::new(0.0, 0.0, 10.0, 10.0), ());
+		world.add(Rect::new(20.0, -50.0, 10.0, 100.0), ());
+
+		world.set_collisions_enabled(item, false);
+		let moved = world.move_item(item, 40.0, 10.0);
+		assert_eq!((40.0, 10.0), (moved.x, moved.y));
+		assert!(moved.collisions.is_empty());
+		assert_eq!(Some(Rect::new(40.0, 10.0, 10.0, 10.0)), world.rect(item));
+
+		world.set_collisions_enabled(item, true);
+		let moved = world.check(item, 0.0, 10.0);
+		assert_eq!((30.0, 10.0), (moved.x, moved.y));
+		assert_eq!(1, moved.collisions.len());
+	}
+
+	#[test]
+	fn overlapping_and_tunneling() {
+		let mut world = World::with_cell_size(16.0);
+		let item = world.add(Rect::new(0.0, 0.0, 10.0, 10.0), ());
+		let other = world.add(Rect::new(8.0, 2.0, 10.0, 10.0), ());
+		world.add(Rect::new(200.0, 0.0, 4.0, 10.0), ());
+
+		// not moving out of an overlap uses minimum displacement
+		let moved = world.check(item, 0.0, 0.0);
+		let collision = moved.collisions[0];
+		assert!(collision.overlaps);
+		assert_eq!(-16.0, collision.ti);
+		assert_eq!((-2.0, 0.0), (moved.x, moved.y));
+		assert_eq!(vec![(item, other)], world.all_overlapping());
+
+		// fast movement doesn't tunnel through thin items
+		world.remove(other);
+		let moved = world.move_item(item, 400.0, 0.0);
+		assert_eq!((190.0, 0.0), (moved.x, moved.y));
+		assert!(world.data(other).is_none());
+	}
+
+	#[test]
+	fn queries() {
+		let mut world = World::with_cell_size(8.0);
+		let a = world.add(Rect::new(0.0, 0.0, 10.0, 10.0), 'a');
+		let b = world.add(Rect::new(30.0, 0.0, 10.0, 10.0), 'b');
+		let c = world.add(Rect::new(60.0, 40.0, 10.0, 10.0), 'c');
+
+		assert_eq!(vec![a], world.query_at_point(5.0, 5.0));
+		assert!(world.query_at_point(10.0, 5.0).is_empty());
+		assert_eq!(vec![a, b], world.query_in_rect(5.0, 5.0, 30.0, 1.0));
+
+		assert_eq!(vec![b, a], world.query_along_line(50.0, 5.0, -10.0, 5.0));
+		let info = world.query_info_along_line(50.0, 5.0, -10.0, 5.0)[0];
+		assert_eq!(
+		           (b, (40.0, 5.0), (30.0, 5.0)),
+		           (info.item, info.entry_point, info.exit_point)
+		);
+
+		world.set_collisions_enabled(b, false);
+		assert_eq!(vec![a], world.query_along_line(50.0, 5.0, -10.0, 5.0));
+		assert_eq!(Some(&'c'), world.data(c));
+		assert_eq!(3, world.len());
+	}
+}